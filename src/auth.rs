@@ -157,7 +157,7 @@ impl Scheme for TwitterOAuth {
 /// ```rust
 /// let con_token = egg_mode::KeyPair::new("consumer key", "consumer token");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyPair {
     ///A key used to identify an application or user.
     pub key: Cow<'static, str>,
@@ -349,7 +349,33 @@ impl KeyPair {
 /// // for restrictions, see docs for bearer_token
 /// # }
 /// ```
-#[derive(Debug, Clone)]
+///
+/// # Saving Tokens
+///
+/// Access tokens and Bearer tokens stay valid until they're revoked, so you don't need to go
+/// through the authentication process every time your application starts. `Token` and `KeyPair`
+/// implement serde's `Serialize` and `Deserialize`, so you can save a token with whatever format
+/// you like and load it back in later. For example, with `serde_json`:
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate serde_json;
+/// # fn main() {
+/// # let token: egg_mode::Token = unimplemented!();
+/// use std::fs::File;
+///
+/// serde_json::to_writer(File::create("token.json").unwrap(), &token).unwrap();
+///
+/// let token: egg_mode::Token =
+///     serde_json::from_reader(File::open("token.json").unwrap()).unwrap();
+/// # }
+/// ```
+///
+/// Keep in mind that the saved token includes your consumer secret and the access secret (or the
+/// Bearer token itself), which are all anyone needs to make calls as your application or as the
+/// user that signed in. Store the file somewhere only your application can read, and keep it out
+/// of version control. Likewise, the `Debug` output of a `Token` includes its secrets, so be
+/// careful not to print it to logs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Token {
     ///An OAuth Access token indicating the request is coming from a specific user.
     Access {
@@ -392,12 +418,18 @@ fn sign(header: TwitterOAuth,
             add_param(&mut sig_params, "oauth_verifier", verifier.as_str());
         }
 
+        //the signature needs the parameters sorted by their *encoded* key, then value. sorting the
+        //pairs before joining them keeps keys like "a" and "a-b" in the right order, which sorting
+        //the joined "key=value" strings would get wrong
         let mut query = sig_params.iter()
-                                  .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+                                  .map(|(k, v)| (percent_encode(k), percent_encode(v)))
                                   .collect::<Vec<_>>();
         query.sort();
 
-        query.join("&")
+        query.into_iter()
+             .map(|(k, v)| format!("{}={}", k, v))
+             .collect::<Vec<_>>()
+             .join("&")
     };
 
     let base_str = format!("{}&{}&{}",
@@ -456,10 +488,68 @@ fn bearer_request(con_token: &KeyPair) -> Basic {
     }
 }
 
+/// A signed request, along with what's needed to sign it again if `ClientConfig::retry` retries
+/// it.
+///
+/// This is what `get` returns, so the template can be handed to `RawFuture` alongside the request
+/// without every call having to give its token separately. Anything that can't be retried (like a
+/// POST) is converted from a plain `Request`, without a template.
+pub struct SignedRequest {
+    /// The request to send first.
+    pub request: Request,
+    /// What's needed to sign a fresh copy of the request, if it's safe to send twice.
+    pub template: Option<Resign>,
+}
+
+impl From<Request> for SignedRequest {
+    fn from(request: Request) -> SignedRequest {
+        SignedRequest {
+            request: request,
+            template: None,
+        }
+    }
+}
+
+/// What's needed to sign a GET request again, so that each retry gets its own OAuth nonce and
+/// timestamp instead of replaying the first attempt's signature.
+#[derive(Clone)]
+pub struct Resign {
+    uri: String,
+    params: Option<ParamList<'static>>,
+    token: Token,
+}
+
+impl Resign {
+    /// Signs a fresh copy of the original request.
+    pub fn request(&self) -> Request {
+        sign_get(&self.uri, &self.token, self.params.as_ref())
+    }
+}
+
 /// Assemble a signed GET request to the given URL with the given parameters.
+///
+/// The returned request also carries what's needed to sign it again, so `RawFuture` can retry it.
 pub fn get(uri: &str,
            token: &Token,
-           params: Option<&ParamList>) -> Request {
+           params: Option<&ParamList>) -> SignedRequest {
+    let params_owned = params.map(|p| {
+        p.iter()
+         .map(|(k, v)| (Cow::Owned(k.to_string()), Cow::Owned(v.to_string())))
+         .collect()
+    });
+
+    SignedRequest {
+        request: sign_get(uri, token, params),
+        template: Some(Resign {
+            uri: uri.to_string(),
+            params: params_owned,
+            token: token.clone(),
+        }),
+    }
+}
+
+/// Signs a GET request to the given URL with the given parameters.
+fn sign_get(uri: &str, token: &Token, params: Option<&ParamList>) -> Request {
     let full_url = if let Some(p) = params {
         let query = p.iter()
                      .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
@@ -496,7 +586,7 @@ pub fn post(uri: &str,
     let content: Mime = "application/x-www-form-urlencoded".parse().unwrap();
     let body = if let Some(p) = params {
         p.iter()
-         .map(|(k, v)| format!("{}={}", k, percent_encode(v)))
+         .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
          .collect::<Vec<_>>()
          .join("&")
     } else {
@@ -525,6 +615,31 @@ pub fn post(uri: &str,
     request
 }
 
+/// The longest URL that `get_or_post` will send as a GET request.
+const MAX_GET_URL_LENGTH: usize = 2048;
+
+/// Assemble a signed request to the given URL with the given parameters, as a GET request if the
+/// parameters fit in the URL, or as a POST request otherwise.
+///
+/// This is for calls like `users/lookup` that accept either method. A GET is cheaper for Twitter
+/// to serve and can be retried by `ClientConfig::retry`, but URLs longer than about 2KB may be cut
+/// off along the way, so long parameter lists are sent in a POST body instead. Either way, the
+/// OAuth signature is made for the method that's actually used.
+pub fn get_or_post(uri: &str,
+                   token: &Token,
+                   params: Option<&ParamList>) -> SignedRequest {
+    //each param takes up its encoded key and value, plus the '=' and the '?' or '&' before it
+    let query_len = params.map_or(0, |p| {
+        p.iter().map(|(k, v)| percent_encode(k).len() + percent_encode(v).len() + 2).sum()
+    });
+
+    if uri.len() + query_len <= MAX_GET_URL_LENGTH {
+        get(uri, token, params)
+    } else {
+        post(uri, token, params).into()
+    }
+}
+
 /// Assemble a signed POST request to the given URL with the given JSON body.
 pub fn post_json(uri: &str, token: &Token, body: &json::Json) -> Request {
     let content: Mime = "application/json; charset=UTF-8".parse().unwrap();
@@ -923,6 +1038,41 @@ pub fn invalidate_bearer(handle: &Handle, con_token: &KeyPair, token: &Token)
     make_future(handle, request, parse_tok)
 }
 
+/// Invalidate the given Access token, revoking the authorization the user gave to your app.
+///
+/// This is meant for when a user signs out of your application: Once the future returned by this
+/// function completes successfully, the token can no longer be used to make calls, and the user
+/// will need to go through the authentication process again before your app can act on their
+/// behalf. To revoke a Bearer token instead, use [`invalidate_bearer`].
+///
+/// [`invalidate_bearer`]: fn.invalidate_bearer.html
+///
+/// # Panics
+///
+/// If this function is handed a `Token` that is not an Access token, this function will panic.
+pub fn invalidate_access(token: &Token, handle: &Handle) -> TwitterFuture<()> {
+    if let Token::Bearer(_) = *token {
+        panic!("non-access token passed to invalidate_access");
+    }
+
+    let req = post(links::auth::INVALIDATE_ACCESS, token, None);
+
+    make_future(handle, req, parse_invalidated)
+}
+
+///Checks the response from `oauth/invalidate_token`. Twitter either sends back an empty body or
+///echoes the revoked token's key as `access_token`, so either is taken as success.
+fn parse_invalidated(full_resp: String, _: &Headers) -> Result<(), error::Error> {
+    if full_resp.trim().is_empty() {
+        return Ok(());
+    }
+
+    let decoded = try!(json::Json::from_str(&full_resp));
+    try!(decoded.find("access_token").ok_or(error::Error::MissingValue("access_token")));
+
+    Ok(())
+}
+
 /// If the given tokens are valid, return the user information for the authenticated user.
 ///
 /// If you have cached access tokens, using this method is a convenient way to make sure they're
@@ -936,10 +1086,34 @@ pub fn verify_tokens(token: &Token, handle: &Handle)
     make_parsed_future(handle, req)
 }
 
+/// If the given tokens are valid, return the user information for the authenticated user, along
+/// with their email address.
+///
+/// This works like `verify_tokens`, but also asks Twitter to include the user's email address in
+/// the returned user's `email` field. Your app needs to be given permission to request email
+/// addresses (in its settings on apps.twitter.com) for Twitter to send it, and it's left out for
+/// users that don't have a confirmed email address. This is the only call that fills in `email`.
+///
+/// This is useful for applications that use Twitter to sign users in, and need a way to contact
+/// them.
+pub fn verify_tokens_with_email(token: &Token, handle: &Handle)
+    -> FutureResponse<::user::TwitterUser>
+{
+    let mut params = ParamList::new();
+    add_param(&mut params, "include_email", "true");
+
+    let req = get(links::auth::VERIFY_CREDENTIALS, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::bearer_request;
+    use super::{bearer_request, parse_invalidated, percent_encode, sign, TwitterOAuth};
+    use std::collections::HashMap;
+    use hyper::Method;
     use hyper::header::Authorization;
+    use common::*;
 
     #[test]
     fn bearer_header() {
@@ -953,4 +1127,142 @@ mod tests {
 
         assert_eq!(output, "Basic eHZ6MWV2RlM0d0VFUFRHRUZQSEJvZzpMOHFxOVBaeVJnNmllS0dFS2hab2xHQzB2SldMdzhpRUo4OERSZHlPZw==");
     }
+
+    #[test]
+    fn encode_special_characters() {
+        assert_eq!(percent_encode("#rust & cargo"), "%23rust%20%26%20cargo");
+        assert_eq!(percent_encode("Ladies + Gentlemen"), "Ladies%20%2B%20Gentlemen");
+        assert_eq!(percent_encode("caf\u{e9} \u{1f980}"), "caf%C3%A9%20%F0%9F%A6%80");
+        assert_eq!(percent_encode("a-b.c_d~e"), "a-b.c_d~e");
+        assert_eq!(percent_encode("from:rustlang?q=1/2"), "from%3Arustlang%3Fq%3D1%2F2");
+    }
+
+    #[test]
+    fn search_query_string() {
+        let token = super::Token::Bearer("token".to_string());
+        let mut params = HashMap::new();
+        add_param(&mut params, "q", "#rust & cargo");
+
+        let req = super::get("https://api.twitter.com/1.1/search/tweets.json",
+                             &token, Some(&params)).request;
+
+        assert_eq!(req.uri().query(), Some("q=%23rust%20%26%20cargo"));
+    }
+
+    #[test]
+    fn oauth_signature() {
+        //example from https://dev.twitter.com/oauth/overview/creating-signatures
+        let con_token = super::KeyPair::new("xvz1evFS4wEEPTGEFPHBog",
+                                            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw");
+        let access_token = super::KeyPair::new("370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+                                               "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE");
+        let header = TwitterOAuth {
+            consumer_key: con_token.key.to_string(),
+            nonce: "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg".to_string(),
+            signature: None,
+            timestamp: 1318622958,
+            token: Some(access_token.key.to_string()),
+            callback: None,
+            verifier: None,
+        };
+
+        let mut params = HashMap::new();
+        add_param(&mut params, "status", "Hello Ladies + Gentlemen, a signed OAuth request!");
+        add_param(&mut params, "include_entities", "true");
+
+        let header = sign(header, Method::Post, "https://api.twitter.com/1.1/statuses/update.json",
+                          Some(&params), &con_token, Some(&access_token));
+
+        assert_eq!(header.signature, Some("hCtSmYh+iHYCEqBWrE7C7hYmtUk=".to_string()));
+    }
+
+    #[test]
+    fn lookup_methods() {
+        fn oauth_field(header: &str, name: &str) -> String {
+            let start = header.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+            let end = start + header[start..].find('"').unwrap();
+            header[start..end].to_string()
+        }
+
+        let link = "https://api.twitter.com/1.1/users/lookup.json";
+        let con_token = super::KeyPair::new("consumer", "consumer secret");
+        let access_token = super::KeyPair::new("access", "access secret");
+        let token = super::Token::Access {
+            consumer: con_token.clone(),
+            access: access_token.clone(),
+        };
+
+        let mut short = HashMap::new();
+        add_param(&mut short, "user_id", "165262228");
+
+        let ids = (0..200u64).map(|id| (3000000000 + id).to_string()).collect::<Vec<_>>();
+        let mut long = HashMap::new();
+        add_param(&mut long, "user_id", ids.join(","));
+
+        for (params, method) in vec![(short, Method::Get), (long, Method::Post)] {
+            let req = super::get_or_post(link, &token, Some(&params)).request;
+            assert_eq!(*req.method(), method);
+
+            if method == Method::Get {
+                assert!(req.uri().query().unwrap().starts_with("user_id="));
+            } else {
+                assert_eq!(req.uri().query(), None);
+            }
+
+            //the signature should match one made for the method that was chosen
+            let raw = req.headers().get_raw("Authorization").unwrap().one().unwrap();
+            let header = String::from_utf8(raw.to_vec()).unwrap();
+            let unsigned = TwitterOAuth {
+                consumer_key: con_token.key.to_string(),
+                nonce: oauth_field(&header, "oauth_nonce"),
+                signature: None,
+                timestamp: oauth_field(&header, "oauth_timestamp").parse().unwrap(),
+                token: Some(access_token.key.to_string()),
+                callback: None,
+                verifier: None,
+            };
+            let signed = sign(unsigned, method, link, Some(&params), &con_token,
+                              Some(&access_token));
+
+            assert_eq!(percent_encode(&signed.signature.unwrap()),
+                       oauth_field(&header, "oauth_signature"));
+        }
+    }
+
+    #[test]
+    fn token_round_trip() {
+        use serde_json;
+        use super::{KeyPair, Token};
+
+        let token = Token::Access {
+            consumer: KeyPair::new("consumer key", "consumer secret"),
+            access: KeyPair::new("access key".to_string(), "access secret".to_string()),
+        };
+
+        let saved = serde_json::to_string(&token).unwrap();
+        match serde_json::from_str(&saved).unwrap() {
+            Token::Access { consumer, access } => {
+                assert_eq!(consumer.key, "consumer key");
+                assert_eq!(consumer.secret, "consumer secret");
+                assert_eq!(access.key, "access key");
+                assert_eq!(access.secret, "access secret");
+            }
+            Token::Bearer(_) => panic!("access token was loaded back as a bearer token"),
+        }
+
+        let saved = serde_json::to_string(&Token::Bearer("bearer".to_string())).unwrap();
+        match serde_json::from_str(&saved).unwrap() {
+            Token::Bearer(bearer) => assert_eq!(bearer, "bearer"),
+            Token::Access { .. } => panic!("bearer token was loaded back as an access token"),
+        }
+    }
+
+    #[test]
+    fn invalidated_responses() {
+        let headers = Headers::new();
+
+        assert!(parse_invalidated("".to_string(), &headers).is_ok());
+        assert!(parse_invalidated(r#"{"access_token":"ACCESS_TOKEN"}"#.to_string(), &headers).is_ok());
+        assert!(parse_invalidated(r#"{"something":"else"}"#.to_string(), &headers).is_err());
+    }
 }
@@ -0,0 +1,336 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and methods for reading collections, Twitter's curated sets of tweets.
+//!
+//! A collection is a list of tweets that a user has picked out and put in a specific order, like a
+//! playlist. Each collection has an ID that looks like `"custom-539487832448843776"`, which is
+//! what the functions in this module take to refer to it. You can find the collections a user has
+//! made with `list`, load a collection's information with `show`, and load the tweets in it with
+//! `entries`.
+//!
+//! Twitter sends the tweets in a collection in a trimmed-down form, so `entries` only returns
+//! their IDs, in the collection's order. To load the full tweets, hand those IDs to
+//! [`tweet::lookup`].
+//!
+//! [`tweet::lookup`]: ../tweet/fn.lookup.html
+//!
+//! ```rust,no_run
+//! # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+//! # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+//! # fn main() {
+//! # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+//! use egg_mode::{collections, tweet};
+//!
+//! let owned = core.run(collections::list("TwitterDev", None, &token, &handle)).unwrap();
+//!
+//! for collection in &owned.collections {
+//!     let entries = core.run(collections::entries(&collection.id, 20, None, &token, &handle))
+//!                       .unwrap();
+//!     let ids = entries.tweet_ids.iter().cloned();
+//!     let tweets = core.run(tweet::lookup(ids, &token, &handle)).unwrap();
+//!
+//!     println!("{} ({} tweets):", collection.name, tweets.len());
+//!     for tweet in &tweets {
+//!         println!("    {}", tweet.text);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use rustc_serialize::json;
+
+use auth;
+use error;
+use error::Error::{InvalidResponse, MissingValue};
+use links;
+use user::UserID;
+use common::*;
+
+///Represents a collection of tweets, as returned by `show` or `list`.
+#[derive(Debug, Clone)]
+pub struct Collection {
+    ///The ID of this collection, like `"custom-539487832448843776"`.
+    pub id: String,
+    ///The display name of this collection.
+    pub name: String,
+    ///The description given to this collection by its owner, if any.
+    pub description: Option<String>,
+    ///The numeric ID of the user who owns this collection.
+    pub user_id: u64,
+    ///The URL where this collection can be viewed on Twitter.
+    pub collection_url: Option<String>,
+    ///The website link given by the owner to go with this collection, if any.
+    pub url: Option<String>,
+    ///Whether this collection is `"public"` or `"private"`.
+    pub visibility: Option<String>,
+    ///How the tweets in this collection are ordered: `"curation_reverse_chron"` (the order the
+    ///owner added them, newest first), `"tweet_chron"` (the order they were posted, oldest first),
+    ///or `"tweet_reverse_chron"` (the order they were posted, newest first).
+    pub timeline_order: Option<String>,
+}
+
+///Represents a page of tweets from a collection, as returned by `entries`.
+#[derive(Debug, Clone)]
+pub struct CollectionEntries {
+    ///The collection these tweets belong to.
+    pub collection: Collection,
+    ///The IDs of the tweets in this page, in the collection's order.
+    pub tweet_ids: Vec<u64>,
+    ///The position of the last tweet in this page. Hand this to `entries` as its `max_position`
+    ///to load the next page.
+    pub min_position: Option<String>,
+    ///The position of the first tweet in this page.
+    pub max_position: Option<String>,
+    ///Whether Twitter left out some tweets from this page, for example because they were deleted
+    ///or are from protected accounts.
+    pub was_truncated: bool,
+}
+
+///Represents a page of collections owned by a user, as returned by `list`.
+#[derive(Debug, Clone)]
+pub struct CollectionList {
+    ///The collections in this page.
+    pub collections: Vec<Collection>,
+    ///A reference to the next page of results, if there is one. Hand this to `list` as its
+    ///`cursor` to load that page.
+    pub next_cursor: Option<String>,
+}
+
+///Load information about the given collection.
+pub fn show(id: &str, token: &auth::Token, handle: &Handle) -> FutureResponse<Collection> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "id", id);
+
+    let req = auth::get(links::collections::SHOW, token, Some(&params));
+
+    make_future(handle, req, parse_show)
+}
+
+///Load a page of tweet IDs from the given collection.
+///
+///Use the `count` parameter to indicate how many tweets you would like to load. If `count` is 0
+///or greater than 200, it will be defaulted to 200 before making the call. To load the tweets
+///after a page that's already been loaded, give that page's `min_position` as `max_position`.
+pub fn entries(id: &str, count: u32, max_position: Option<&str>, token: &auth::Token,
+               handle: &Handle)
+    -> FutureResponse<CollectionEntries>
+{
+    let mut params = HashMap::new();
+    add_param(&mut params, "id", id);
+
+    if count == 0 || count > 200 {
+        add_param(&mut params, "count", 200.to_string());
+    } else {
+        add_param(&mut params, "count", count.to_string());
+    }
+
+    if let Some(max_position) = max_position {
+        add_param(&mut params, "max_position", max_position);
+    }
+
+    let req = auth::get(links::collections::ENTRIES, token, Some(&params));
+
+    make_future(handle, req, parse_entries)
+}
+
+///Load the collections owned by the given user.
+///
+///Twitter returns up to 20 collections at a time. To load the next page, give the previous page's
+///`next_cursor` as `cursor`.
+pub fn list<'a, T: Into<UserID<'a>>>(owner: T, cursor: Option<&str>, token: &auth::Token,
+                                     handle: &Handle)
+    -> FutureResponse<CollectionList>
+{
+    let mut params = HashMap::new();
+    add_name_param(&mut params, &owner.into());
+
+    if let Some(cursor) = cursor {
+        add_param(&mut params, "cursor", cursor);
+    }
+
+    let req = auth::get(links::collections::LIST, token, Some(&params));
+
+    make_future(handle, req, parse_list)
+}
+
+//The collections endpoints all return their results in the same envelope: The "objects" field
+//holds every tweet, user, and collection ("timeline") mentioned in the response, keyed by their
+//IDs, and the "response" field refers to those by ID, in order.
+
+fn parse_show(full_resp: String, headers: &Headers) -> Result<Response<Collection>, error::Error> {
+    let ret: Response<json::Json> = try!(make_response(full_resp, headers));
+
+    let collection = {
+        let response = try!(ret.response.find("response").ok_or(MissingValue("response")));
+        let id: String = try!(field(response, "timeline_id"));
+        let mut timelines = try!(parse_timelines(&ret.response));
+
+        try!(timelines.remove(&id).ok_or(MissingValue("timelines")))
+    };
+
+    Ok(Response::map(ret, |_| collection))
+}
+
+fn parse_entries(full_resp: String, headers: &Headers)
+    -> Result<Response<CollectionEntries>, error::Error>
+{
+    let ret: Response<json::Json> = try!(make_response(full_resp, headers));
+
+    let entries = {
+        let response = try!(ret.response.find("response").ok_or(MissingValue("response")));
+        let id: String = try!(field(response, "timeline_id"));
+        let mut timelines = try!(parse_timelines(&ret.response));
+        let collection = try!(timelines.remove(&id).ok_or(MissingValue("timelines")));
+
+        let mut tweet_ids = Vec::new();
+        if let Some(timeline) = response.find("timeline").and_then(|t| t.as_array()) {
+            for entry in timeline {
+                let tweet = try!(entry.find("tweet").ok_or(MissingValue("tweet")));
+                tweet_ids.push(try!(parse_id(tweet, "id")));
+            }
+        }
+
+        let position = response.find("position").unwrap_or(&json::Json::Null);
+
+        CollectionEntries {
+            collection: collection,
+            tweet_ids: tweet_ids,
+            min_position: try!(field(position, "min_position")),
+            max_position: try!(field(position, "max_position")),
+            was_truncated: field(position, "was_truncated").unwrap_or(false),
+        }
+    };
+
+    Ok(Response::map(ret, |_| entries))
+}
+
+fn parse_list(full_resp: String, headers: &Headers)
+    -> Result<Response<CollectionList>, error::Error>
+{
+    let ret: Response<json::Json> = try!(make_response(full_resp, headers));
+
+    let list = {
+        let response = try!(ret.response.find("response").ok_or(MissingValue("response")));
+        let mut timelines = try!(parse_timelines(&ret.response));
+
+        let mut collections = Vec::new();
+        if let Some(results) = response.find("results").and_then(|r| r.as_array()) {
+            for result in results {
+                let id: String = try!(field(result, "timeline_id"));
+                collections.extend(timelines.remove(&id));
+            }
+        }
+
+        let cursors = response.find("cursors").unwrap_or(&json::Json::Null);
+
+        CollectionList {
+            collections: collections,
+            next_cursor: try!(field(cursors, "next_cursor")),
+        }
+    };
+
+    Ok(Response::map(ret, |_| list))
+}
+
+///Parses every collection in the "objects" part of a collections response, keyed by ID.
+fn parse_timelines(input: &json::Json) -> Result<HashMap<String, Collection>, error::Error> {
+    let timelines = try!(input.find_path(&["objects", "timelines"])
+                              .ok_or(MissingValue("timelines")));
+    let timelines = try!(timelines.as_object()
+                                  .ok_or_else(|| InvalidResponse("collection timelines weren't an object",
+                                                                 Some(timelines.to_string()))));
+
+    let mut ret = HashMap::new();
+
+    for (id, timeline) in timelines {
+        if !timeline.is_object() {
+            return Err(InvalidResponse("Collection received json that wasn't an object",
+                                       Some(timeline.to_string())));
+        }
+
+        field_present!(timeline, name);
+        field_present!(timeline, user_id);
+
+        ret.insert(id.clone(), Collection {
+            id: id.clone(),
+            name: try!(field(timeline, "name")),
+            description: try!(field(timeline, "description")),
+            user_id: try!(parse_id(timeline, "user_id")),
+            collection_url: try!(field(timeline, "collection_url")),
+            url: try!(field(timeline, "url")),
+            visibility: try!(field(timeline, "visibility")),
+            timeline_order: try!(field(timeline, "timeline_order")),
+        });
+    }
+
+    Ok(ret)
+}
+
+///Parses a numeric ID, which the collections endpoints send as a string.
+fn parse_id(input: &json::Json, name: &'static str) -> Result<u64, error::Error> {
+    let id = try!(input.find(name).ok_or(MissingValue(name)));
+
+    match id.as_string() {
+        Some(id_str) => id_str.parse().map_err(|_| InvalidResponse("expected a numeric ID",
+                                                                   Some(id.to_string()))),
+        None => u64::from_json(id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_entries;
+    use common::Headers;
+
+    #[test]
+    fn parse_collection_entries() {
+        let input = r#"{
+            "objects": {
+                "tweets": {
+                    "504032379045179393": {"id_str": "504032379045179393", "text": "trimmed"},
+                    "503024393150746624": {"id_str": "503024393150746624", "text": "trimmed"}
+                },
+                "users": {},
+                "timelines": {
+                    "custom-539487832448843776": {
+                        "name": "National Park Tweets",
+                        "user_id": "2244994945",
+                        "collection_url": "https://twitter.com/TwitterDev/timelines/539487832448843776",
+                        "description": "Photos from parks",
+                        "url": "",
+                        "visibility": "public",
+                        "timeline_order": "curation_reverse_chron",
+                        "collection_type": "user",
+                        "custom_timeline_type": "default"
+                    }
+                }
+            },
+            "response": {
+                "timeline_id": "custom-539487832448843776",
+                "position": {
+                    "max_position": "371578415352947200",
+                    "min_position": "371578380871797248",
+                    "was_truncated": false
+                },
+                "timeline": [
+                    {"tweet": {"id": "504032379045179393", "sort_index": "371578415352947200"}},
+                    {"tweet": {"id": "503024393150746624", "sort_index": "371578380871797248"}}
+                ]
+            }
+        }"#;
+
+        let entries = parse_entries(input.to_string(), &Headers::new()).unwrap().response;
+
+        assert_eq!(entries.collection.id, "custom-539487832448843776");
+        assert_eq!(entries.collection.name, "National Park Tweets");
+        assert_eq!(entries.collection.user_id, 2244994945);
+        assert_eq!(entries.collection.description, Some("Photos from parks".to_string()));
+        assert_eq!(entries.tweet_ids, vec![504032379045179393, 503024393150746624]);
+        assert_eq!(entries.min_position, Some("371578380871797248".to_string()));
+        assert!(!entries.was_truncated);
+    }
+}
@@ -0,0 +1,271 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Network configuration that applies to every call made on a given event loop.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use hyper;
+use hyper::Request;
+use hyper::client::HttpConnector;
+use hyper_tls::HttpsConnector;
+use tokio_core::reactor::{Handle, CoreId};
+
+use error;
+
+/// The kind of `hyper::Client` that egg-mode uses to make its network calls.
+pub type HttpsClient = hyper::Client<HttpsConnector<HttpConnector>>;
+
+/// The `User-Agent` header egg-mode sends with every call, unless a different one is set with
+/// `ClientConfig::user_agent`. This names the crate and the version it was built from, like
+/// `egg-mode/0.12.0`.
+pub const USER_AGENT: &'static str = concat!("egg-mode/", env!("CARGO_PKG_VERSION"));
+
+thread_local! {
+    static CONFIGS: RefCell<HashMap<CoreId, ClientConfig>> = RefCell::new(HashMap::new());
+}
+
+/// Settings for how egg-mode connects to Twitter.
+///
+/// Every function in egg-mode that calls Twitter takes a `Handle` to the event loop it should run
+/// on. A `ClientConfig` is attached to an event loop by calling `apply` with one of its `Handle`s,
+/// after which every call egg-mode makes on that loop will use its settings. If no configuration
+/// is given, egg-mode will use its defaults.
+///
+/// By default, egg-mode creates its own `hyper::Client` the first time it needs to make a call on
+/// a given event loop, and reuses it for every call after that. If you need more control over the
+/// connection (say, to use a custom TLS configuration or to share a connection pool with the rest
+/// of your application), you can hand in your own client with the `client` method:
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate hyper; extern crate hyper_tls;
+/// # use tokio_core::reactor::Core;
+/// # fn main() {
+/// # let core: Core = unimplemented!();
+/// let handle = core.handle();
+/// let connector = hyper_tls::HttpsConnector::new(4, &handle).unwrap();
+/// let client = hyper::Client::configure().connector(connector).build(&handle);
+///
+/// egg_mode::ClientConfig::new()
+///     .client(client)
+///     .apply(&handle);
+/// # }
+/// ```
+///
+/// You can also use a `ClientConfig` to set a timeout on every call, with the `timeout` method,
+/// or to have calls retried when Twitter has a temporary problem, with the `retry` method.
+///
+/// Twitter asks that applications send a `User-Agent` header that describes them. By default,
+/// egg-mode sends [`USER_AGENT`], which names egg-mode and its version. You can set your own with
+/// the `user_agent` method, and add any other headers you need with the `header` method:
+///
+/// [`USER_AGENT`]: constant.USER_AGENT.html
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core;
+/// # use tokio_core::reactor::Core;
+/// # fn main() {
+/// # let core: Core = unimplemented!();
+/// egg_mode::ClientConfig::new()
+///     .user_agent("my-twitter-app/1.0 (+https://example.com)")
+///     .header("X-Request-Source", "nightly-sync")
+///     .apply(&core.handle());
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientConfig {
+    client: Option<HttpsClient>,
+    timeout: Option<Duration>,
+    retry: Option<(u32, Duration)>,
+    headers: Vec<(String, String)>,
+}
+
+impl ClientConfig {
+    /// Creates a new `ClientConfig` with the default settings.
+    pub fn new() -> ClientConfig {
+        ClientConfig::default()
+    }
+
+    /// Sets the `hyper::Client` to use for network calls.
+    pub fn client(self, client: HttpsClient) -> ClientConfig {
+        ClientConfig {
+            client: Some(client),
+            ..self
+        }
+    }
+
+    /// Sets how long a call can take before giving up on it.
+    ///
+    /// The timer starts when the call is first polled, and covers the whole call, from connecting
+    /// to Twitter to loading the full response. If the timeout elapses first, the call will return
+    /// an `Error::IOError` with the kind `TimedOut`. By default, calls have no timeout.
+    ///
+    /// This does not apply to streams from the `stream` module, since those are meant to stay open
+    /// indefinitely. To give a specific call a different timeout (or one at all, if none is set
+    /// here), use `TwitterFuture::with_timeout` on its future, which takes precedence over this
+    /// setting.
+    pub fn timeout(self, timeout: Duration) -> ClientConfig {
+        ClientConfig {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets calls to be retried when Twitter returns a status of 429 (Too Many Requests) or a 5xx
+    /// server error, up to `max_retries` times.
+    ///
+    /// Only GET requests (i.e. calls that only load information) are retried, so that calls that
+    /// post or change something won't be accidentally run twice. Before each retry, egg-mode will
+    /// wait for the time given in the response's `Retry-After` header, or until the time in
+    /// `X-Rate-Limit-Reset` for a 429 response. If neither is present, it will wait for
+    /// `base_delay` on the first retry, doubling it for each retry after that. No single wait is
+    /// longer than 15 minutes (one rate-limit window), whatever Twitter asks for. If the call still
+    /// fails after `max_retries` retries, the last error is returned as usual.
+    ///
+    /// Each retry is signed again before it's sent, so it gets a fresh OAuth nonce and timestamp.
+    /// By default, calls are not retried.
+    pub fn retry(self, max_retries: u32, base_delay: Duration) -> ClientConfig {
+        ClientConfig {
+            retry: Some((max_retries, base_delay)),
+            ..self
+        }
+    }
+
+    /// Sets the `User-Agent` header to send with every call, replacing any that was set before.
+    ///
+    /// This replaces egg-mode's default of [`USER_AGENT`]. If you'd still like egg-mode to show up
+    /// in the header, you can add it to the end of yours, like
+    /// `format!("my-twitter-app/1.0 {}", egg_mode::USER_AGENT)`.
+    ///
+    /// [`USER_AGENT`]: constant.USER_AGENT.html
+    pub fn user_agent<S: Into<String>>(self, user_agent: S) -> ClientConfig {
+        self.header("User-Agent", user_agent)
+    }
+
+    /// Adds a header to send with every call. If a header with the same name has already been
+    /// added, it is replaced.
+    ///
+    /// These headers are sent alongside the ones egg-mode sets for each call. If a call already
+    /// sets a header with the same name (like `Authorization` or `Content-Type`), the call's own
+    /// value is used instead. To send a header with just one call, use `TwitterFuture::with_header`
+    /// on its future, which also takes precedence over the headers set here.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> ClientConfig {
+        let name = name.into();
+        self.headers.retain(|&(ref existing, _)| !existing.eq_ignore_ascii_case(&name));
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Attaches this configuration to the event loop represented by the given `Handle`, replacing
+    /// any configuration that was there before.
+    ///
+    /// Requests that have already been started are not affected.
+    pub fn apply(self, handle: &Handle) {
+        CONFIGS.with(|configs| {
+            configs.borrow_mut().insert(handle.id(), self);
+        });
+    }
+}
+
+/// Returns the `hyper::Client` to use for calls on the given event loop, creating one if it
+/// hasn't been set up yet.
+pub fn client_for(handle: &Handle) -> Result<HttpsClient, error::Error> {
+    CONFIGS.with(|configs| {
+        let mut configs = configs.borrow_mut();
+        let config = configs.entry(handle.id()).or_insert_with(ClientConfig::new);
+
+        if let Some(ref client) = config.client {
+            return Ok(client.clone());
+        }
+
+        // TODO: num-cpus?
+        let connector = try!(HttpsConnector::new(1, handle));
+        let client = hyper::Client::configure().connector(connector).build(handle);
+        config.client = Some(client.clone());
+
+        Ok(client)
+    })
+}
+
+/// Adds the extra headers set for the given event loop to the given request, skipping any that
+/// the request already sets itself. If neither sets a `User-Agent`, egg-mode's default one is
+/// added.
+pub fn apply_headers(handle: &Handle, request: &mut Request) {
+    CONFIGS.with(|configs| {
+        if let Some(config) = configs.borrow().get(&handle.id()) {
+            for &(ref name, ref value) in &config.headers {
+                if request.headers().get_raw(name).is_none() {
+                    request.headers_mut().set_raw(name.clone(), value.clone());
+                }
+            }
+        }
+    });
+
+    if request.headers().get_raw("User-Agent").is_none() {
+        request.headers_mut().set_raw("User-Agent", USER_AGENT);
+    }
+}
+
+/// Returns the timeout that calls on the given event loop should use, if one has been set.
+pub fn timeout_for(handle: &Handle) -> Option<Duration> {
+    CONFIGS.with(|configs| configs.borrow().get(&handle.id()).and_then(|c| c.timeout))
+}
+
+/// Returns the maximum number of retries and the base retry delay that calls on the given event
+/// loop should use, if retries have been turned on.
+pub fn retry_for(handle: &Handle) -> Option<(u32, Duration)> {
+    CONFIGS.with(|configs| configs.borrow().get(&handle.id()).and_then(|c| c.retry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientConfig, USER_AGENT, apply_headers};
+    use hyper::{Method, Request};
+
+    #[test]
+    fn extra_headers() {
+        let core = ::tokio_core::reactor::Core::new().unwrap();
+        let handle = core.handle();
+
+        ClientConfig::new()
+            .user_agent("first/1.0")
+            .user_agent("second/2.0")
+            .header("Content-Type", "text/plain")
+            .apply(&handle);
+
+        let mut req = Request::new(Method::Get, "https://api.twitter.com/".parse().unwrap());
+        req.headers_mut().set_raw("content-type", "application/json");
+        apply_headers(&handle, &mut req);
+
+        assert_eq!(req.headers().get_raw("User-Agent").unwrap(), "second/2.0");
+        assert_eq!(req.headers().get_raw("Content-Type").unwrap(), "application/json");
+    }
+
+    #[test]
+    fn default_user_agent() {
+        let core = ::tokio_core::reactor::Core::new().unwrap();
+        let handle = core.handle();
+
+        assert_eq!(USER_AGENT, format!("egg-mode/{}", env!("CARGO_PKG_VERSION")));
+
+        let mut req = Request::new(Method::Get, "https://api.twitter.com/".parse().unwrap());
+        apply_headers(&handle, &mut req);
+        assert_eq!(req.headers().get_raw("User-Agent").unwrap(), USER_AGENT);
+
+        ClientConfig::new().header("X-Request-Source", "tests").apply(&handle);
+
+        let mut req = Request::new(Method::Get, "https://api.twitter.com/".parse().unwrap());
+        apply_headers(&handle, &mut req);
+        assert_eq!(req.headers().get_raw("User-Agent").unwrap(), USER_AGENT);
+        assert_eq!(req.headers().get_raw("X-Request-Source").unwrap(), "tests");
+
+        ClientConfig::new().user_agent("my-app/1.0").apply(&handle);
+
+        let mut req = Request::new(Method::Get, "https://api.twitter.com/".parse().unwrap());
+        apply_headers(&handle, &mut req);
+        assert_eq!(req.headers().get_raw("User-Agent").unwrap(), "my-app/1.0");
+    }
+}
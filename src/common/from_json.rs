@@ -4,8 +4,10 @@
 
 //! Infrastructure trait and related functions for deserializing data from Twitter.
 
+use std::collections::HashSet;
+use std::hash::Hash;
 use rustc_serialize::json;
-use chrono::{self, TimeZone};
+use chrono;
 use error;
 use error::Error::InvalidResponse;
 use mime;
@@ -61,6 +63,15 @@ impl<T> FromJson for Vec<T> where T: FromJson {
     }
 }
 
+///Turn JSON arrays into HashSets, dropping any duplicate values.
+impl<T> FromJson for HashSet<T> where T: FromJson + Eq + Hash {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        let arr = try!(input.as_array().ok_or_else(|| InvalidResponse("expected an array", Some(input.to_string()))));
+
+        arr.iter().map(|x| T::from_json(x)).collect()
+    }
+}
+
 ///Turn a value that can be null into an optional value. Also turns empty arrays into None.
 impl<T> FromJson for Option<T> where T: FromJson {
     fn from_json(input: &json::Json) -> Result<Self, error::Error> {
@@ -209,9 +220,11 @@ impl FromJson for mime::Mime {
 impl FromJson for chrono::DateTime<chrono::Utc> {
     fn from_json(input: &json::Json) -> Result<Self, error::Error> {
         let str = try!(input.as_string().ok_or_else(|| InvalidResponse("expected string for DateTime", Some(input.to_string()))));
-        let date = try!((chrono::Utc).datetime_from_str(str, "%a %b %d %T %z %Y"));
+        //parse with whatever offset was given, so non-UTC timestamps are converted instead of
+        //rejected
+        let date = try!(chrono::DateTime::parse_from_str(str, "%a %b %d %T %z %Y"));
 
-        Ok(date)
+        Ok(date.with_timezone(&chrono::Utc))
     }
 }
 
@@ -0,0 +1,261 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A stand-in for the network, so tests can run whole web calls against canned responses.
+//!
+//! This is only compiled for tests. Like `ClientConfig`, it's keyed by the event loop: `respond`
+//! queues up a response for a given `Handle`, and the next call made on that loop gets it instead
+//! of going out to Twitter. Calls made when nothing is queued go to the network as usual. Every
+//! request that's answered this way is saved, so tests can check what was sent with `requests`.
+//! To test timeouts, `respond_stalled` queues a response whose body never finishes arriving.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use futures::sync::mpsc;
+use hyper::{self, Body, Method, Request, StatusCode};
+use hyper::header::Headers;
+use tokio_core::reactor::{Handle, CoreId};
+
+thread_local! {
+    static MOCKS: RefCell<HashMap<CoreId, Mock>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Default)]
+struct Mock {
+    responses: VecDeque<(StatusCode, Headers, Option<String>)>,
+    requests: Vec<(Method, String)>,
+    headers: Vec<Headers>,
+    stalled: Vec<mpsc::Sender<Result<hyper::Chunk, hyper::Error>>>,
+}
+
+/// Queues a response to hand to the next call made on the given event loop.
+pub fn respond<S: Into<String>>(handle: &Handle, status: StatusCode, headers: Headers, body: S) {
+    MOCKS.with(|mocks| {
+        let mut mocks = mocks.borrow_mut();
+        let mock = mocks.entry(handle.id()).or_insert_with(Mock::default);
+        mock.responses.push_back((status, headers, Some(body.into())));
+    })
+}
+
+/// Queues a response for the next call made on the given event loop, whose body never finishes
+/// arriving.
+pub fn respond_stalled(handle: &Handle) {
+    MOCKS.with(|mocks| {
+        let mut mocks = mocks.borrow_mut();
+        let mock = mocks.entry(handle.id()).or_insert_with(Mock::default);
+        mock.responses.push_back((StatusCode::Ok, Headers::new(), None));
+    })
+}
+
+/// Returns the method and URI of each request that was given a queued response on the given
+/// event loop, in the order they were sent.
+pub fn requests(handle: &Handle) -> Vec<(Method, String)> {
+    MOCKS.with(|mocks| {
+        mocks.borrow().get(&handle.id()).map_or(vec![], |mock| mock.requests.clone())
+    })
+}
+
+/// Returns the headers of each request that was given a queued response on the given event loop,
+/// in the order they were sent.
+pub fn request_headers(handle: &Handle) -> Vec<Headers> {
+    MOCKS.with(|mocks| {
+        mocks.borrow().get(&handle.id()).map_or(vec![], |mock| mock.headers.clone())
+    })
+}
+
+/// If a response has been queued for the given event loop, saves the given request and returns
+/// the response.
+pub fn take_response(handle: &Handle, request: &Request) -> Option<hyper::Response> {
+    MOCKS.with(|mocks| {
+        let mut mocks = mocks.borrow_mut();
+        let mock = match mocks.get_mut(&handle.id()) {
+            Some(mock) => mock,
+            None => return None,
+        };
+
+        let (status, headers, body) = match mock.responses.pop_front() {
+            Some(resp) => resp,
+            None => return None,
+        };
+
+        mock.requests.push((request.method().clone(), request.uri().to_string()));
+        mock.headers.push(request.headers().clone());
+
+        let body = match body {
+            Some(body) => Body::from(body),
+            None => {
+                //keep the sender around so the body stays open without sending anything
+                let (sender, body) = Body::pair();
+                mock.stalled.push(sender);
+                body
+            }
+        };
+
+        Some(hyper::Response::new()
+                 .with_status(status)
+                 .with_headers(headers)
+                 .with_body(body))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{respond, respond_stalled, requests, request_headers};
+    use auth;
+    use std::time::Duration;
+    use common::*;
+    use error::Error;
+    use hyper::{Method, Request, StatusCode};
+    use futures::{Async, Future};
+    use tokio_core::reactor::Core;
+
+    fn get(url: &str) -> Request {
+        Request::new(Method::Get, url.parse().unwrap())
+    }
+
+    #[test]
+    fn mock_rate_limits() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Limit", "900");
+        headers.set_raw("X-Rate-Limit-Remaining", "899");
+        headers.set_raw("X-Rate-Limit-Reset", "1500000000");
+        respond(&handle, StatusCode::Ok, headers, "[1, 2, 3]");
+
+        let mut future: FutureResponse<Vec<u64>> =
+            make_parsed_future(&handle, get("https://api.twitter.com/1.1/friends/ids.json"));
+
+        match future.poll() {
+            Ok(Async::Ready(resp)) => {
+                assert_eq!(resp.response, vec![1, 2, 3]);
+                assert_eq!(resp.rate_limit, 900);
+                assert_eq!(resp.rate_limit_remaining, 899);
+                assert_eq!(resp.rate_limit_reset, 1500000000);
+            }
+            Ok(Async::NotReady) => panic!("mocked call wasn't ready"),
+            Err(e) => panic!("mocked call returned an error: {}", e),
+        }
+
+        assert_eq!(requests(&handle),
+                   vec![(Method::Get, "https://api.twitter.com/1.1/friends/ids.json".to_string())]);
+    }
+
+    #[test]
+    fn mock_retry_signing() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Access {
+            consumer: auth::KeyPair::new("consumer key", "consumer secret"),
+            access: auth::KeyPair::new("access key", "access secret"),
+        };
+
+        ClientConfig::new().retry(2, Duration::from_millis(0)).apply(&handle);
+        respond(&handle, StatusCode::ServiceUnavailable, Headers::new(), "");
+        respond(&handle, StatusCode::Ok, Headers::new(), "[1]");
+
+        let url = "https://api.twitter.com/1.1/friends/ids.json";
+        let req = auth::get(url, &token, None);
+        let resp: Response<Vec<u64>> = core.run(make_parsed_future(&handle, req)).unwrap();
+        assert_eq!(resp.response, vec![1]);
+
+        assert_eq!(requests(&handle), vec![(Method::Get, url.to_string()),
+                                           (Method::Get, url.to_string())]);
+
+        let headers = request_headers(&handle);
+        let first = headers[0].get_raw("Authorization").unwrap();
+        let second = headers[1].get_raw("Authorization").unwrap();
+        assert!(first != second, "retry reused the first attempt's signature");
+    }
+
+    #[test]
+    fn mock_call_timeout() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        let url = "https://api.twitter.com/1.1/friends/ids.json";
+
+        //timeouts need to be polled inside a task, so these run on the core
+        respond_stalled(&handle);
+        let future: FutureResponse<Vec<u64>> = make_parsed_future(&handle, get(url))
+            .with_timeout(Duration::from_millis(10));
+
+        match core.run(future) {
+            Err(Error::IOError(ref e)) if e.kind() == ::std::io::ErrorKind::TimedOut => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        //a per-call timeout takes precedence over the event loop's
+        ClientConfig::new().timeout(Duration::from_secs(3600)).apply(&handle);
+        respond_stalled(&handle);
+        let future: FutureResponse<Vec<u64>> = make_parsed_future(&handle, get(url))
+            .with_timeout(Duration::from_millis(10));
+
+        match core.run(future) {
+            Err(Error::IOError(ref e)) if e.kind() == ::std::io::ErrorKind::TimedOut => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn mock_call_headers() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let url = "https://api.twitter.com/1.1/friends/ids.json";
+
+        ClientConfig::new()
+            .user_agent("my-app/1.0")
+            .header("X-Request-Source", "config")
+            .apply(&handle);
+        respond(&handle, StatusCode::Ok, Headers::new(), "[1]");
+
+        let mut future: FutureResponse<Vec<u64>> = make_parsed_future(&handle, get(url))
+            .with_header("x-request-source", "first")
+            .with_header("X-Request-Source", "call");
+
+        match future.poll() {
+            Ok(Async::Ready(resp)) => assert_eq!(resp.response, vec![1]),
+            Ok(Async::NotReady) => panic!("mocked call wasn't ready"),
+            Err(e) => panic!("mocked call returned an error: {}", e),
+        }
+
+        let headers = request_headers(&handle);
+        assert_eq!(headers[0].get_raw("X-Request-Source").unwrap(), "call");
+        assert_eq!(headers[0].get_raw("User-Agent").unwrap(), "my-app/1.0");
+    }
+
+    #[test]
+    fn mock_errors() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Reset", "1500000000");
+        respond(&handle, StatusCode::TooManyRequests, headers,
+                r#"{"errors":[{"code":88,"message":"Rate limit exceeded"}]}"#);
+        respond(&handle, StatusCode::Unauthorized, Headers::new(),
+                r#"{"errors":[{"code":89,"message":"Invalid or expired token."}]}"#);
+        respond(&handle, StatusCode::NotFound, Headers::new(), "");
+
+        let url = "https://api.twitter.com/1.1/users/show.json";
+
+        match make_raw_future(&handle, get(url)).poll() {
+            Err(Error::RateLimit(1500000000)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match make_raw_future(&handle, get(url)).poll() {
+            Err(Error::TwitterError(ref errs)) => assert_eq!(errs.errors[0].code, 89),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match make_raw_future(&handle, get(url)).poll() {
+            Err(Error::BadStatus(StatusCode::NotFound)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(requests(&handle).len(), 3);
+    }
+}
@@ -46,6 +46,10 @@
 //! lookup functions, so this saves up all that handling and splits the iterator into two strings:
 //! one for the user IDs, one for the screen names.
 //!
+//! `join_ids` is the simpler version of that for when a function only takes numeric IDs: it just
+//! turns them into a comma-separated list. Since lookup functions usually cap how many IDs they
+//! take in one call, it takes a slice, so it can be handed the pieces from `chunks` directly.
+//!
 //! ## `WebResponse` and `FutureResponse`
 //!
 //! These are just convenience type aliases for when i need to return rate-limit information with a
@@ -122,12 +126,42 @@
 //! `make_response` (which just calls `FromJson` and loads up the rate-limit headers - it's also
 //! exported) as the processor.
 //!
+//! `parse_empty_response` is the processor to hand to `make_future` for calls that don't return
+//! anything worth parsing (like removing a profile banner, or setting alt text on media). It skips
+//! the body entirely and only loads the rate-limit headers, since Twitter sends back anything from
+//! an empty body to `{}` for these.
+//!
+//! `BatchFuture` is the odd one out, since it doesn't wrap a `RawFuture` directly. Instead, it
+//! holds several `TwitterFuture`s that all load a collection, and concatenates their results when
+//! they're all done. This is for the lookup functions that can only take 100 items per call, so
+//! they can split up their input and still hand back a single Future. `make_batch_future` is its
+//! constructor.
+//!
 //! `rate_headers` is an infra function that takes the `Headers` and returns an empty `Response`
 //! with the rate-limit info parsed out. It's only exported for a couple functions in `list` which
 //! need to get that info even on an error.
+//!
+//! ## `ClientConfig`
+//!
+//! `ClientConfig` is in its own file too, and is the one place where egg-mode keeps state
+//! between calls. Since every network call is handed a `Handle`, the config is stored in a
+//! thread-local map keyed by the `CoreId` of the event loop, and `get_response` pulls the
+//! `hyper::Client` out of there with `client_for`, creating and saving a default one if nobody has
+//! set one yet. This way, calls on the same event loop share a connection pool instead of setting
+//! up a new client every time. `get_response` also adds the config's extra headers (like a custom
+//! `User-Agent`) to each request with `apply_headers`, which falls back to egg-mode's own
+//! `USER_AGENT` if nobody set one.
+//!
+//! ## `mock`
+//!
+//! `mock` only exists for tests. It lets a test queue up canned responses for an event loop, which
+//! `get_response` hands back (as a `ResponseFuture`) instead of calling Twitter. This way tests can
+//! run a whole call, from signing the request to parsing the rate-limit headers and errors, without
+//! touching the network.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::iter::Peekable;
 use user;
 use list;
@@ -137,9 +171,13 @@ pub use hyper::Headers;
 
 #[macro_use] mod from_json;
 mod response;
+mod client;
+#[cfg(test)]
+pub mod mock;
 
 pub use common::response::*;
 pub use common::from_json::*;
+pub use common::client::*;
 
 ///Convenience type used to hold parameters to an API call.
 pub type ParamList<'a> = HashMap<Cow<'a, str>, Cow<'a, str>>;
@@ -155,7 +193,7 @@ pub fn add_param<'a, K, V>(list: &mut ParamList<'a>, key: K, value: V) -> Option
 pub fn add_name_param<'a>(list: &mut ParamList<'a>, id: &user::UserID<'a>) -> Option<Cow<'a, str>> {
     match *id {
         user::UserID::ID(id) => add_param(list, "user_id", id.to_string()),
-        user::UserID::ScreenName(name) => add_param(list, "screen_name", name),
+        user::UserID::ScreenName(ref name) => add_param(list, "screen_name", name.clone()),
     }
 }
 
@@ -166,8 +204,8 @@ pub fn add_list_param<'a>(params: &mut ParamList<'a>, list: &list::ListID<'a>) {
                 user::UserID::ID(id) => {
                     add_param(params, "owner_id", id.to_string());
                 },
-                user::UserID::ScreenName(name) => {
-                    add_param(params, "owner_screen_name", name);
+                user::UserID::ScreenName(ref name) => {
+                    add_param(params, "owner_screen_name", name.clone());
                 },
             }
             add_param(params, "slug", name);
@@ -194,6 +232,21 @@ pub fn multiple_names_param<'a, T, I>(accts: I) -> (String, String)
     (ids.join(","), names.join(","))
 }
 
+pub fn join_ids(ids: &[u64]) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",")
+}
+
+///Collects the given items into a `Vec`, skipping any that were already seen. Unlike
+///`Vec::dedup`, this removes repeats anywhere in the list, and keeps the first of each in its
+///original place.
+pub fn dedup_in_order<T, I>(items: I) -> Vec<T>
+    where T: Eq + Hash + Clone, I: IntoIterator<Item=T>
+{
+    let mut seen = HashSet::new();
+
+    items.into_iter().filter(|item| seen.insert(item.clone())).collect()
+}
+
 ///Type alias for responses from Twitter.
 pub type WebResponse<T> = Result<Response<T>, ::error::Error>;
 
@@ -321,4 +374,267 @@ mod tests {
         codepoints_to_bytes(&mut range, unicode);
         assert_eq!(&unicode[range.0..range.1], "Iñtërnâtiônàližætiøn ënd");
     }
+
+    #[test]
+    fn parse_twitter_datetime() {
+        use chrono::{self, Datelike, Timelike};
+        use rustc_serialize::json::Json;
+
+        let input = Json::String("Wed Aug 27 13:08:45 +0000 2008".to_string());
+        let date = chrono::DateTime::<chrono::Utc>::from_json(&input).unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2008, 8, 27));
+        assert_eq!((date.hour(), date.minute(), date.second()), (13, 8, 45));
+
+        //non-UTC offsets are converted to UTC
+        let input = Json::String("Wed Aug 27 13:08:45 -0500 2008".to_string());
+        let date = chrono::DateTime::<chrono::Utc>::from_json(&input).unwrap();
+        assert_eq!(date.hour(), 18);
+
+        let input = Json::String("2008-08-27T13:08:45Z".to_string());
+        assert!(chrono::DateTime::<chrono::Utc>::from_json(&input).is_err());
+    }
+
+    #[test]
+    fn name_param_matrix() {
+        use std::collections::HashMap;
+        use user::UserID;
+
+        fn param_for<'a, T: Into<UserID<'a>>>(acct: T) -> (String, String) {
+            let mut params = HashMap::new();
+            add_name_param(&mut params, &acct.into());
+
+            assert_eq!(params.len(), 1);
+            params.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).next().unwrap()
+        }
+
+        let id = 165262228u64;
+        let name = "rustlang";
+        let owned = String::from("rustlang");
+        let numeric_name = String::from("165262228");
+
+        let user_id = ("user_id".to_string(), "165262228".to_string());
+        let screen_name = ("screen_name".to_string(), "rustlang".to_string());
+
+        assert_eq!(param_for(165262228), user_id);
+        assert_eq!(param_for(id), user_id);
+        assert_eq!(param_for(&id), user_id);
+        assert_eq!(param_for(UserID::ID(id)), user_id);
+        assert_eq!(param_for(&UserID::ID(id)), user_id);
+        assert_eq!(param_for(UserID::parse("165262228")), user_id);
+
+        assert_eq!(param_for(name), screen_name);
+        assert_eq!(param_for(&name), screen_name);
+        assert_eq!(param_for(owned.clone()), screen_name);
+        assert_eq!(param_for(&owned), screen_name);
+        assert_eq!(param_for(UserID::parse("@rustlang")), screen_name);
+        assert_eq!(param_for(&UserID::from(name)), screen_name);
+
+        //strings are always screen names, even if they look like an ID
+        assert_eq!(param_for(numeric_name.as_str()),
+                   ("screen_name".to_string(), "165262228".to_string()));
+        assert_eq!(param_for(&numeric_name),
+                   ("screen_name".to_string(), "165262228".to_string()));
+    }
+
+    #[test]
+    fn missing_rate_limit_headers() {
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Limit", "900");
+        headers.set_raw("X-Rate-Limit-Reset", "1500000000");
+
+        let resp = rate_headers(&headers);
+        assert_eq!(resp.rate_limit, 900);
+        assert_eq!(resp.rate_limit_remaining, -1);
+        assert_eq!(resp.rate_limit_reset, 1500000000);
+
+        //malformed values are treated the same as missing ones
+        headers.set_raw("X-Rate-Limit-Remaining", "lots");
+        headers.set_raw("X-Rate-Limit-Reset", "");
+        let resp: Response<Vec<u64>> = make_response("[1, 2]".to_string(), &headers).unwrap();
+        assert_eq!(resp.rate_limit, 900);
+        assert_eq!(resp.rate_limit_remaining, -1);
+        assert_eq!(resp.rate_limit_reset, -1);
+        assert!(Response::reset_time(&resp).is_none());
+        assert_eq!(Response::time_until_reset(&resp).as_secs(), 0);
+
+        let resp = rate_headers(&Headers::new());
+        assert_eq!((resp.rate_limit, resp.rate_limit_remaining, resp.rate_limit_reset),
+                   (-1, -1, -1));
+    }
+
+    #[test]
+    fn response_map_keeps_rate_limits() {
+        let mut resp = Response {
+            rate_limit: 900,
+            rate_limit_remaining: 899,
+            rate_limit_reset: 1500000000,
+            response: vec![1, 2, 3],
+        };
+
+        {
+            let borrowed = Response::as_ref(&resp);
+            assert_eq!(borrowed.response.len(), 3);
+            assert_eq!(borrowed.rate_limit_remaining, 899);
+        }
+
+        Response::as_mut(&mut resp).response.push(4);
+
+        let mapped = Response::map(resp, |nums| nums.iter().sum::<i32>());
+        assert_eq!(mapped.response, 10);
+        assert_eq!(mapped.rate_limit, 900);
+        assert_eq!(mapped.rate_limit_remaining, 899);
+        assert_eq!(mapped.rate_limit_reset, 1500000000);
+    }
+
+    #[test]
+    fn dedup_ids() {
+        assert_eq!(dedup_in_order(vec![3, 1, 3, 2, 1]), vec![3, 1, 2]);
+        assert!(dedup_in_order(Vec::<u64>::new()).is_empty());
+    }
+
+    #[test]
+    fn empty_responses() {
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Remaining", "14");
+
+        for body in &["", "  \n", "{}"] {
+            let resp = parse_empty_response(body.to_string(), &headers).unwrap();
+            assert_eq!(resp.rate_limit_remaining, 14);
+            assert_eq!(resp.rate_limit, -1);
+        }
+    }
+
+    #[test]
+    fn response_iteration() {
+        let mut resp = Response {
+            rate_limit: 900,
+            rate_limit_remaining: 899,
+            rate_limit_reset: 1500000000,
+            response: vec!["a".to_string(), "b".to_string()],
+        };
+
+        for item in &mut resp {
+            assert_eq!(item.rate_limit_remaining, 899);
+            item.response.push('!');
+        }
+
+        assert!(resp.iter().all(|item| item.rate_limit_reset == 1500000000));
+
+        let items = resp.into_iter().collect::<Vec<_>>();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].response, "b!");
+        assert_eq!(items[1].rate_limit, 900);
+
+        //collecting the items again preserves the shared rate-limit information
+        let collected = items.into_iter().collect::<Response<Vec<String>>>();
+        assert_eq!(collected.response, vec!["a!", "b!"]);
+        assert_eq!(collected.rate_limit_remaining, 899);
+    }
+
+    #[test]
+    fn retry_waits() {
+        use std::time::Duration;
+
+        let base = Duration::from_millis(500);
+        let max = Duration::from_secs(15 * 60);
+
+        assert_eq!(retry_wait(None, None, base, 0), base);
+        assert_eq!(retry_wait(None, None, base, 3), Duration::from_secs(4));
+        assert_eq!(retry_wait(Some(30), None, base, 3), Duration::from_secs(30));
+
+        //huge headers and retry counts are capped instead of overflowing
+        assert_eq!(retry_wait(Some(u64::max_value()), None, base, 0), max);
+        assert_eq!(retry_wait(None, None, base, 20), max);
+        assert_eq!(retry_wait(None, None, base, 32), max);
+        assert_eq!(retry_wait(None, None, Duration::from_secs(u64::max_value()), 1), max);
+        assert_eq!(retry_wait(None, Some(i32::max_value()), base, 0), max);
+    }
+
+    #[test]
+    fn owned_response_iteration() {
+        let resp = Response {
+            rate_limit: 180,
+            rate_limit_remaining: 179,
+            rate_limit_reset: 1500000000,
+            response: vec![1u64, 2],
+        };
+
+        let mut seen = Vec::new();
+        for item in resp {
+            assert_eq!(Response::rate_limits(&item),
+                       RateLimit { limit: 180, remaining: 179, reset: 1500000000 });
+            seen.push(item.response);
+        }
+
+        assert_eq!(seen, vec![1, 2]);
+    }
+
+    #[test]
+    fn response_parts() {
+        let resp = Response {
+            rate_limit: 15,
+            rate_limit_remaining: 14,
+            rate_limit_reset: 1500000000,
+            response: vec![1u64, 2, 3],
+        };
+
+        let (limits, ids) = Response::into_parts(resp);
+        assert_eq!(limits, RateLimit { limit: 15, remaining: 14, reset: 1500000000 });
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(limits.reset_time().unwrap().timestamp(), 1500000000);
+
+        let resp = Response::from_parts(limits, ids.len());
+        assert_eq!(resp.response, 3);
+        assert_eq!(resp.rate_limit_remaining, 14);
+        assert_eq!(Response::rate_limits(&resp), limits);
+
+        let empty = RateLimit { limit: -1, remaining: -1, reset: -1 };
+        assert_eq!(Response::rate_limits(&rate_headers(&Headers::new())), empty);
+        assert!(empty.reset_time().is_none());
+    }
+
+    #[test]
+    fn response_reset_time() {
+        use chrono;
+
+        let now = chrono::Utc::now().timestamp() as i32;
+        let mut resp = Response {
+            rate_limit: 15,
+            rate_limit_remaining: 0,
+            rate_limit_reset: now + 600,
+            response: (),
+        };
+
+        assert_eq!(Response::reset_time(&resp).unwrap().timestamp(), (now + 600) as i64);
+        let wait = Response::time_until_reset(&resp).as_secs();
+        assert!(wait <= 600 && wait >= 590);
+
+        resp.rate_limit_reset = now - 600;
+        assert_eq!(Response::time_until_reset(&resp).as_secs(), 0);
+
+        resp.rate_limit_reset = -1;
+        assert!(Response::reset_time(&resp).is_none());
+        assert_eq!(Response::time_until_reset(&resp).as_secs(), 0);
+    }
+
+    #[test]
+    fn gzip_bodies() {
+        use std::io::Write;
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use hyper::header::{ContentEncoding, Encoding};
+
+        let body = br#"{"id":1234}"#.to_vec();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut headers = Headers::new();
+        assert_eq!(decode_body(&headers, body.clone()).unwrap(), body);
+
+        headers.set(ContentEncoding(vec![Encoding::Gzip]));
+        assert_eq!(decode_body(&headers, compressed).unwrap(), body);
+        assert!(decode_body(&headers, body.clone()).is_err());
+    }
 }
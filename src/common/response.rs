@@ -5,23 +5,30 @@
 //! Infrastructure types related to packaging rate-limit information alongside responses from
 //! Twitter.
 
-use std::{slice, vec, io, mem};
+use std::{cmp, slice, vec, io, mem};
+use std::io::Read;
 use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
-use hyper::client::FutureResponse;
-use hyper::{self, Body, StatusCode, Request};
-use hyper::header::{Headers, ContentLength};
-use hyper_tls::HttpsConnector;
-use tokio_core::reactor::Handle;
+use std::time::Duration;
+use chrono;
+use hyper::client::FutureResponse as HyperFuture;
+use hyper::{self, Body, Method, StatusCode, Request};
+use hyper::header::{Headers, ContentLength, AcceptEncoding, ContentEncoding, Encoding, qitem};
+use flate2::read::GzDecoder;
+use tokio_core::reactor::{Handle, Timeout};
 use futures::{Async, Future, Poll, Stream};
 use rustc_serialize::json;
-use super::{FromJson, field};
+use super::{FromJson, field, client_for, apply_headers, timeout_for, retry_for};
+#[cfg(test)]
+use super::mock;
+use auth;
 use error::{self, TwitterErrors};
 use error::Error::*;
 
 header! { (XRateLimitLimit, "X-Rate-Limit-Limit") => [i32] }
 header! { (XRateLimitRemaining, "X-Rate-Limit-Remaining") => [i32] }
 header! { (XRateLimitReset, "X-Rate-Limit-Reset") => [i32] }
+header! { (RetryAfter, "Retry-After") => [u64] }
 
 ///A helper struct to wrap response data with accompanying rate limit information.
 ///
@@ -31,13 +38,37 @@ header! { (XRateLimitReset, "X-Rate-Limit-Reset") => [i32] }
 ///
 ///As this implements `Deref` and `DerefMut`, you can transparently use the contained `response`'s
 ///methods as if they were methods on this struct.
+///
+///When the response is a collection, iterating over a `Response<Vec<T>>` (by value, by reference,
+///or mutably) yields each item wrapped in its own `Response`, with the rate-limit information
+///copied over. The items themselves are moved or borrowed, never cloned; the only thing copied is
+///the rate-limit information, which is just three integers, so this costs no more than iterating
+///over the `Vec` directly:
+///
+///```rust
+///# use egg_mode::Response;
+///# let users = Response { rate_limit: 900, rate_limit_remaining: 899, rate_limit_reset: 0,
+///#                        response: vec!["rustlang".to_string(), "ThisWeekInRust".to_string()] };
+///for user in users {
+///    println!("{} ({} calls left)", user.response, user.rate_limit_remaining);
+///}
+///```
 #[derive(Debug)]
 pub struct Response<T> {
     ///The rate limit ceiling for the given request.
+    ///
+    ///As with the other rate-limit fields, this is -1 if Twitter didn't send (or sent a malformed)
+    ///`X-Rate-Limit-Limit` header, which happens for some calls outside the usual REST API, like
+    ///uploading media.
     pub rate_limit: i32,
-    ///The number of requests left for the 15-minute window.
+    ///The number of requests left for the 15-minute window, or -1 if Twitter didn't send this
+    ///information.
     pub rate_limit_remaining: i32,
-    ///The UTC Unix timestamp at which the rate window resets.
+    ///The UTC Unix timestamp at which the rate window resets, or -1 if Twitter didn't send this
+    ///information.
+    ///
+    ///To get this as a `DateTime`, or as the time left until then, use `Response::reset_time` or
+    ///`Response::time_until_reset`.
     pub rate_limit_reset: i32,
     ///The decoded response from the request.
     pub response: T,
@@ -49,6 +80,18 @@ impl<T> Response<T> {
     ///
     ///Note that this is not a member function, so as to not conflict with potential methods on the
     ///contained `T`.
+    ///
+    ///```rust
+    ///# use egg_mode::Response;
+    ///# let users = Response { rate_limit: 900, rate_limit_remaining: 899, rate_limit_reset: 0,
+    ///#                        response: vec![("rustlang", 165262228)] };
+    ///let names = Response::map(users, |users| {
+    ///    users.into_iter().map(|(name, _)| name.to_string()).collect::<Vec<String>>()
+    ///});
+    ///
+    ///assert_eq!(names.response, vec!["rustlang"]);
+    ///assert_eq!(names.rate_limit_remaining, 899);
+    ///```
     pub fn map<F, U>(src: Response<T>, fun: F) -> Response<U>
         where F: FnOnce(T) -> U
     {
@@ -59,6 +102,149 @@ impl<T> Response<T> {
             response: fun(src.response)
         }
     }
+
+    ///Borrow the contained response, creating a new `Response` with the same rate-limit
+    ///information. This can be combined with `map` to look at part of a response without taking
+    ///it apart.
+    ///
+    ///As with `map`, this is not a member function, so it doesn't hide an `as_ref` method on the
+    ///contained `T`.
+    pub fn as_ref(src: &Response<T>) -> Response<&T> {
+        Response {
+            rate_limit: src.rate_limit,
+            rate_limit_remaining: src.rate_limit_remaining,
+            rate_limit_reset: src.rate_limit_reset,
+            response: &src.response,
+        }
+    }
+
+    ///Mutably borrow the contained response, creating a new `Response` with the same rate-limit
+    ///information.
+    ///
+    ///As with `map`, this is not a member function, so it doesn't hide an `as_mut` method on the
+    ///contained `T`.
+    pub fn as_mut(src: &mut Response<T>) -> Response<&mut T> {
+        Response {
+            rate_limit: src.rate_limit,
+            rate_limit_remaining: src.rate_limit_remaining,
+            rate_limit_reset: src.rate_limit_reset,
+            response: &mut src.response,
+        }
+    }
+
+    ///Returns the time at which the rate-limit window for this call resets, as a UTC timestamp.
+    ///
+    ///This is the same moment as `rate_limit_reset`, just as a `DateTime` instead of a raw Unix
+    ///timestamp. If the response didn't come with rate-limit information (i.e. `rate_limit_reset`
+    ///is -1), this returns `None`.
+    ///
+    ///As with `map`, this is not a member function, so as to not conflict with potential methods
+    ///on the contained `T`.
+    pub fn reset_time(src: &Response<T>) -> Option<chrono::DateTime<chrono::Utc>> {
+        Response::rate_limits(src).reset_time()
+    }
+
+    ///Returns how long it will be until the rate-limit window for this call resets.
+    ///
+    ///If the reset time has already passed, or the response didn't come with rate-limit
+    ///information, this returns a zero `Duration`. This is handy to hand to a
+    ///`tokio_core::reactor::Timeout` when `rate_limit_remaining` has reached zero.
+    ///
+    ///As with `map`, this is not a member function, so as to not conflict with potential methods
+    ///on the contained `T`.
+    pub fn time_until_reset(src: &Response<T>) -> Duration {
+        until_reset(src.rate_limit_reset)
+    }
+
+    ///Copies out the rate-limit information from this response, so it can be passed around
+    ///separately.
+    ///
+    ///As with `map`, this is not a member function, so as to not conflict with potential methods
+    ///on the contained `T`.
+    pub fn rate_limits(src: &Response<T>) -> RateLimit {
+        RateLimit {
+            limit: src.rate_limit,
+            remaining: src.rate_limit_remaining,
+            reset: src.rate_limit_reset,
+        }
+    }
+
+    ///Splits this response into its rate-limit information and the contained response.
+    ///
+    ///This is handy when you want to handle the rate-limit information on its own (or throw it
+    ///away) without keeping the whole `Response` around. `from_parts` puts them back together.
+    ///
+    ///As with `map`, this is not a member function, so as to not conflict with potential methods
+    ///on the contained `T`.
+    ///
+    ///```rust
+    ///# use egg_mode::Response;
+    ///# let users = Response { rate_limit: 900, rate_limit_remaining: 899, rate_limit_reset: 0,
+    ///#                        response: vec!["rustlang".to_string()] };
+    ///let (limits, names) = Response::into_parts(users);
+    ///
+    ///assert_eq!(limits.remaining, 899);
+    ///assert_eq!(names, vec!["rustlang"]);
+    ///```
+    pub fn into_parts(src: Response<T>) -> (RateLimit, T) {
+        (Response::rate_limits(&src), src.response)
+    }
+
+    ///Creates a new `Response` from the given rate-limit information and response.
+    pub fn from_parts(rate: RateLimit, response: T) -> Response<T> {
+        Response {
+            rate_limit: rate.limit,
+            rate_limit_remaining: rate.remaining,
+            rate_limit_reset: rate.reset,
+            response: response,
+        }
+    }
+}
+
+///The rate-limit information that comes with a call to Twitter.
+///
+///This holds the same information as the `rate_limit`, `rate_limit_remaining`, and
+///`rate_limit_reset` fields on `Response`, for when you want to keep it apart from the response
+///itself. You can get one from a `Response` with `Response::rate_limits` or
+///`Response::into_parts`. As on `Response`, each field is -1 if Twitter didn't send that
+///information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    ///The rate limit ceiling for the given request.
+    pub limit: i32,
+    ///The number of requests left for the 15-minute window.
+    pub remaining: i32,
+    ///The UTC Unix timestamp at which the rate window resets.
+    pub reset: i32,
+}
+
+impl RateLimit {
+    ///Returns the time at which the rate-limit window resets, as a UTC timestamp, or `None` if
+    ///`reset` is -1.
+    pub fn reset_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        if self.reset < 0 {
+            return None;
+        }
+
+        Some(chrono::DateTime::from_utc(
+                chrono::NaiveDateTime::from_timestamp(self.reset as i64, 0),
+                chrono::Utc))
+    }
+
+    ///Returns how long it will be until the rate-limit window resets, or a zero `Duration` if it
+    ///has already passed.
+    pub fn time_until_reset(&self) -> Duration {
+        until_reset(self.reset)
+    }
+}
+
+///Returns how long it will be until the given Unix timestamp, or a zero `Duration` if it has
+///already passed.
+fn until_reset(reset: i32) -> Duration {
+    let now = chrono::Utc::now().timestamp();
+    let secs = if (reset as i64) > now { reset as i64 - now } else { 0 };
+
+    Duration::from_secs(secs as u64)
 }
 
 impl<T> Response<Vec<T>> {
@@ -66,9 +252,7 @@ impl<T> Response<Vec<T>> {
     ///rate-limit information for the whole method call.
     pub fn iter(&self) -> ResponseIterRef<T> {
         ResponseIterRef {
-            rate_limit: self.rate_limit,
-            rate_limit_remaining: self.rate_limit_remaining,
-            rate_limit_reset: self.rate_limit_reset,
+            rate: Response::rate_limits(self),
             resp_iter: self.response.iter(),
         }
     }
@@ -77,9 +261,7 @@ impl<T> Response<Vec<T>> {
     ///rate-limit information for the whole method call.
     pub fn iter_mut(&mut self) -> ResponseIterMut<T> {
         ResponseIterMut {
-            rate_limit: self.rate_limit,
-            rate_limit_remaining: self.rate_limit_remaining,
-            rate_limit_reset: self.rate_limit_reset,
+            rate: Response::rate_limits(self),
             resp_iter: self.response.iter_mut(),
         }
     }
@@ -125,9 +307,7 @@ impl<T> DerefMut for Response<T> {
 ///This provides a convenient method to iterate over a response that returned a collection, while
 ///copying rate-limit information across the entire iteration.
 pub struct ResponseIterRef<'a, T> where T: 'a {
-    rate_limit: i32,
-    rate_limit_remaining: i32,
-    rate_limit_reset: i32,
+    rate: RateLimit,
     resp_iter: slice::Iter<'a, T>,
 }
 
@@ -136,12 +316,7 @@ impl<'a, T> Iterator for ResponseIterRef<'a, T> where T: 'a {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -155,12 +330,7 @@ impl<'a, T> Iterator for ResponseIterRef<'a, T> where T: 'a {
 impl<'a, T> DoubleEndedIterator for ResponseIterRef<'a, T> where T: 'a {
     fn next_back(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next_back() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -188,9 +358,7 @@ impl<'a, T> IntoIterator for &'a Response<Vec<T>> where T: 'a {
 ///This provides a convenient method to iterate over a response that returned a collection, while
 ///copying rate-limit information across the entire iteration.
 pub struct ResponseIterMut<'a, T> where T: 'a {
-    rate_limit: i32,
-    rate_limit_remaining: i32,
-    rate_limit_reset: i32,
+    rate: RateLimit,
     resp_iter: slice::IterMut<'a, T>,
 }
 
@@ -199,12 +367,7 @@ impl<'a, T> Iterator for ResponseIterMut<'a, T> where T: 'a {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -218,12 +381,7 @@ impl<'a, T> Iterator for ResponseIterMut<'a, T> where T: 'a {
 impl<'a, T> DoubleEndedIterator for ResponseIterMut<'a, T> where T: 'a {
     fn next_back(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next_back() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -253,9 +411,7 @@ impl<'a, T> IntoIterator for &'a mut Response<Vec<T>> where T: 'a {
 ///copying rate-limit information across the entire iteration. For example, this is used in
 ///`CursorIter`'s implemention to propagate rate-limit information across a given page of results.
 pub struct ResponseIter<T> {
-    rate_limit: i32,
-    rate_limit_remaining: i32,
-    rate_limit_reset: i32,
+    rate: RateLimit,
     resp_iter: vec::IntoIter<T>,
 }
 
@@ -264,12 +420,7 @@ impl<T> Iterator for ResponseIter<T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -283,12 +434,7 @@ impl<T> Iterator for ResponseIter<T> {
 impl<T> DoubleEndedIterator for ResponseIter<T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         if let Some(resp) = self.resp_iter.next_back() {
-            Some(Response {
-                rate_limit: self.rate_limit,
-                rate_limit_remaining: self.rate_limit_remaining,
-                rate_limit_reset: self.rate_limit_reset,
-                response: resp,
-            })
+            Some(Response::from_parts(self.rate, resp))
         } else {
             None
         }
@@ -309,9 +455,7 @@ impl<T> IntoIterator for Response<Vec<T>> {
 
     fn into_iter(self) -> Self::IntoIter {
         ResponseIter {
-            rate_limit: self.rate_limit,
-            rate_limit_remaining: self.rate_limit_remaining,
-            rate_limit_reset: self.rate_limit_reset,
+            rate: Response::rate_limits(&self),
             resp_iter: self.response.into_iter(),
         }
     }
@@ -348,11 +492,52 @@ impl<T> FromIterator<Response<T>> for Response<Vec<T>> {
     }
 }
 
-pub fn get_response(handle: &Handle, request: Request) -> Result<FutureResponse, error::Error> {
-    // TODO: num-cpus?
-    let connector = try!(HttpsConnector::new(1, handle));
-    let client = hyper::Client::configure().connector(connector).build(handle);
-    Ok(client.request(request))
+/// The `Future` returned by `get_response`, which resolves to the response headers of a web call.
+///
+/// Outside of tests, this is always a call going out over the network. In tests, it can also be a
+/// canned response from the `mock` module.
+pub enum ResponseFuture {
+    Live(HyperFuture),
+    #[cfg(test)]
+    Mock(Option<hyper::Response>),
+}
+
+impl Future for ResponseFuture {
+    type Item = hyper::Response;
+    type Error = hyper::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match *self {
+            ResponseFuture::Live(ref mut resp) => resp.poll(),
+            #[cfg(test)]
+            ResponseFuture::Mock(ref mut resp) => {
+                Ok(Async::Ready(resp.take().expect("mock response polled after it completed")))
+            }
+        }
+    }
+}
+
+pub fn get_response(handle: &Handle, mut request: Request)
+    -> Result<ResponseFuture, error::Error>
+{
+    apply_headers(handle, &mut request);
+
+    if let Some(resp) = mock_response(handle, &request) {
+        return Ok(resp);
+    }
+
+    let client = try!(client_for(handle));
+    Ok(ResponseFuture::Live(client.request(request)))
+}
+
+#[cfg(test)]
+fn mock_response(handle: &Handle, request: &Request) -> Option<ResponseFuture> {
+    mock::take_response(handle, request).map(|resp| ResponseFuture::Mock(Some(resp)))
+}
+
+#[cfg(not(test))]
+fn mock_response(_: &Handle, _: &Request) -> Option<ResponseFuture> {
+    None
 }
 
 /// A `Future` that resolves a web request and loads the complete response into a String.
@@ -363,17 +548,94 @@ pub fn get_response(handle: &Handle, request: Request) -> Result<FutureResponse,
 pub struct RawFuture {
     handle: Handle,
     request: Option<Request>,
-    response: Option<FutureResponse>,
+    response: Option<ResponseFuture>,
     resp_headers: Option<Headers>,
     resp_status: Option<StatusCode>,
     body_stream: Option<Body>,
     body: Vec<u8>,
+    timeout: Option<Timeout>,
+    call_timeout: Option<Duration>,
+    call_headers: Vec<(String, String)>,
+    template: Option<auth::Resign>,
+    attempts: u32,
+    retry_wait: Option<Timeout>,
+    error: Option<error::Error>,
+    expected_status: Option<StatusCode>,
 }
 
 impl RawFuture {
     fn headers(&self) -> &Headers {
         self.resp_headers.as_ref().unwrap()
     }
+
+    /// Sets a timeout for this call only, in place of the one from the event loop's
+    /// `ClientConfig`. See `TwitterFuture::with_timeout` for details.
+    pub fn with_timeout(self, timeout: Duration) -> RawFuture {
+        RawFuture {
+            call_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets a header to send with this call only. See `TwitterFuture::with_header` for details.
+    pub fn with_header(mut self, name: String, value: String) -> RawFuture {
+        self.call_headers.retain(|&(ref existing, _)| !existing.eq_ignore_ascii_case(&name));
+        self.call_headers.push((name, value));
+        self
+    }
+
+    /// If the given response should be retried according to the event loop's `ClientConfig`,
+    /// returns how long to wait before doing so.
+    fn retry_delay(&self, resp: &hyper::Response) -> Option<Duration> {
+        let (max_retries, base_delay) = match retry_for(&self.handle) {
+            Some(retry) => retry,
+            None => return None,
+        };
+
+        if self.template.is_none() || self.attempts >= max_retries {
+            return None;
+        }
+
+        let status = resp.status();
+        let limited = status.as_u16() == 429;
+        if !limited && !status.is_server_error() {
+            return None;
+        }
+
+        let retry_after = resp.headers().get::<RetryAfter>().map(|after| after.0);
+        let reset = if limited {
+            resp.headers().get::<XRateLimitReset>().map(|reset| reset.0)
+        } else {
+            None
+        };
+
+        Some(retry_wait(retry_after, reset, base_delay, self.attempts))
+    }
+}
+
+/// The longest egg-mode will wait before retrying a call, no matter what Twitter asks for.
+const MAX_RETRY_DELAY: u64 = 15 * 60;
+
+/// Works out how long to wait before a retry, from the response's `Retry-After` header (in
+/// seconds), its rate-limit reset time, or else by doubling `base_delay` for each earlier attempt.
+/// The result never goes past `MAX_RETRY_DELAY`, so a huge header or a long run of retries can't
+/// make egg-mode wait forever (or overflow).
+pub fn retry_wait(retry_after: Option<u64>, reset: Option<i32>, base_delay: Duration, attempts: u32)
+    -> Duration
+{
+    let max = Duration::from_secs(MAX_RETRY_DELAY);
+
+    let delay = if let Some(after) = retry_after {
+        Duration::from_secs(cmp::min(after, MAX_RETRY_DELAY))
+    } else if let Some(reset) = reset {
+        until_reset(reset)
+    } else {
+        2u32.checked_pow(attempts)
+            .and_then(|factor| base_delay.checked_mul(factor))
+            .unwrap_or(max)
+    };
+
+    cmp::min(delay, max)
 }
 
 impl Future for RawFuture {
@@ -381,11 +643,64 @@ impl Future for RawFuture {
     type Error = error::Error;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        if let Some(req) = self.request.take() {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        if let Some(mut wait) = self.retry_wait.take() {
+            match wait.poll() {
+                Ok(Async::NotReady) => self.retry_wait = Some(wait),
+                Ok(Async::Ready(())) => {
+                    //sign the request again rather than resending the old signature, since
+                    //twitter rejects a repeated nonce or an old timestamp
+                    if let Some(ref template) = self.template {
+                        self.request = Some(gzip_request(template.request()));
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if let Some(mut req) = self.request.take() {
+            //set these on every attempt, since retries start from a freshly-signed request
+            for &(ref name, ref value) in &self.call_headers {
+                req.headers_mut().set_raw(name.clone(), value.clone());
+            }
+
+            if self.attempts == 0 {
+                if let Some(timeout) = self.call_timeout.or_else(|| timeout_for(&self.handle)) {
+                    self.timeout = Some(try!(Timeout::new(timeout, &self.handle)));
+                }
+
+                // only GETs are safe to send twice, so only keep the template for those
+                if *req.method() != Method::Get || retry_for(&self.handle).is_none() {
+                    self.template = None;
+                }
+            }
+
             // needed to pull this section into the future so i could try!() on the connector
             self.response = Some(try!(get_response(&self.handle, req)));
         }
 
+        if let Some(mut timeout) = self.timeout.take() {
+            match timeout.poll() {
+                Ok(Async::NotReady) => self.timeout = Some(timeout),
+                Ok(Async::Ready(())) => {
+                    self.response = None;
+                    self.body_stream = None;
+                    self.retry_wait = None;
+                    return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                              "request timed out").into());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // poll the timeout before bailing out here, so it can still interrupt a retry
+        if self.retry_wait.is_some() {
+            return Ok(Async::NotReady);
+        }
+
         if let Some(mut resp) = self.response.take() {
             match resp.poll() {
                 Err(e) => return Err(e.into()),
@@ -394,6 +709,12 @@ impl Future for RawFuture {
                     return Ok(Async::NotReady);
                 }
                 Ok(Async::Ready(resp)) => {
+                    if let Some(delay) = self.retry_delay(&resp) {
+                        self.attempts += 1;
+                        self.retry_wait = Some(try!(Timeout::new(delay, &self.handle)));
+                        return self.poll();
+                    }
+
                     self.resp_headers = Some(resp.headers().clone());
                     self.resp_status = Some(resp.status());
                     if let Some(len) = resp.headers().get::<ContentLength>() {
@@ -422,25 +743,32 @@ impl Future for RawFuture {
             return Err(FutureAlreadyCompleted);
         };
 
-        match String::from_utf8(mem::replace(&mut self.body, Vec::new())) {
+        let body = mem::replace(&mut self.body, Vec::new());
+        let body = try!(decode_body(self.headers(), body));
+
+        match String::from_utf8(body) {
             Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData,
                                          "stream did not contain valid UTF-8").into()),
             Ok(resp) => {
+                let status = self.resp_status.unwrap();
+
+                //some calls answer "no" with an error; those get handed on to be parsed
+                if self.expected_status == Some(status) {
+                    return Ok(Async::Ready(resp));
+                }
+
                 if let Ok(err) = json::decode::<TwitterErrors>(&resp) {
-                    if err.errors.iter().any(|e| e.code == 88) &&
-                        self.headers().has::<XRateLimitReset>()
-                    {
-                        return Err(
-                            RateLimit(
-                                self.headers().get::<XRateLimitReset>().map(|h| h.0).unwrap()
-                            )
-                        );
-                    } else {
-                        return Err(TwitterError(err));
+                    //only use the reset time if it parsed; a malformed header shouldn't panic
+                    if err.has_code(error::codes::RATE_LIMIT_EXCEEDED) {
+                        if let Some(reset) = self.headers().get::<XRateLimitReset>() {
+                            return Err(error::Error::RateLimit(reset.0));
+                        }
                     }
+
+                    return Err(TwitterError(err));
                 }
 
-                match self.resp_status.unwrap() {
+                match status {
                     st if st.is_success() => Ok(Async::Ready(resp)),
                     st => Err(BadStatus(st)),
                 }
@@ -449,17 +777,56 @@ impl Future for RawFuture {
     }
 }
 
+/// Decompresses the given response body, if its headers say it was sent with gzip.
+pub fn decode_body(headers: &Headers, body: Vec<u8>) -> Result<Vec<u8>, error::Error> {
+    let gzipped = headers.get::<ContentEncoding>()
+                         .map_or(false, |enc| enc.contains(&Encoding::Gzip));
+
+    if gzipped {
+        let mut decoded = Vec::new();
+        try!(GzDecoder::new(&body[..]).read_to_end(&mut decoded));
+        Ok(decoded)
+    } else {
+        Ok(body)
+    }
+}
+
+/// Asks for a gzipped response on the given request, unless it already asks for something else.
+fn gzip_request(mut request: Request) -> Request {
+    if !request.headers().has::<AcceptEncoding>() {
+        request.headers_mut().set(AcceptEncoding(vec![qitem(Encoding::Gzip)]));
+    }
+
+    request
+}
+
 /// Creates a new `RawFuture` starting with the given `Request`, to be run on the Core represented
 /// by the given `Handle`.
-pub fn make_raw_future(handle: &Handle, request: Request) -> RawFuture {
+///
+/// This asks Twitter to compress the response with gzip, since every response loaded this way is
+/// read in full before being parsed. (Streams are read as they arrive, so they don't get this.)
+///
+/// If the request came with a template to sign it again (as GET requests from `auth::get` do),
+/// `ClientConfig::retry` can retry it.
+pub fn make_raw_future<R: Into<auth::SignedRequest>>(handle: &Handle, request: R) -> RawFuture {
+    let request = request.into();
+
     RawFuture {
         handle: handle.clone(),
-        request: Some(request),
+        request: Some(gzip_request(request.request)),
         response: None,
         resp_headers: None,
         resp_status: None,
         body_stream: None,
         body: Vec::new(),
+        timeout: None,
+        call_timeout: None,
+        call_headers: Vec::new(),
+        template: request.template,
+        attempts: 0,
+        retry_wait: None,
+        error: None,
+        expected_status: None,
     }
 }
 
@@ -486,6 +853,49 @@ pub struct TwitterFuture<T> {
     make_resp: fn(String, &Headers) -> Result<T, error::Error>,
 }
 
+impl<T> TwitterFuture<T> {
+    /// Sets how long this call can take before giving up on it, in place of the timeout from the
+    /// event loop's `ClientConfig` (if any).
+    ///
+    /// This works the same way as `ClientConfig::timeout`: the timer starts when the call is
+    /// first polled, and if it elapses first, the call returns an `Error::IOError` with the kind
+    /// `TimedOut`. Since the timer is set up on that first poll, this needs to be called before
+    /// polling the future to have any effect.
+    ///
+    /// ```rust,no_run
+    /// # extern crate egg_mode; extern crate tokio_core;
+    /// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    /// let call = egg_mode::user::show("rustlang", &token, &handle)
+    ///                            .with_timeout(Duration::from_secs(5));
+    /// let rustlang = core.run(call).unwrap();
+    /// # }
+    /// ```
+    pub fn with_timeout(self, timeout: Duration) -> TwitterFuture<T> {
+        TwitterFuture {
+            request: self.request.with_timeout(timeout),
+            make_resp: self.make_resp,
+        }
+    }
+
+    /// Sets a header to send with this call only. If a header with the same name has already
+    /// been set on this call, it is replaced.
+    ///
+    /// This takes precedence over the headers from the event loop's `ClientConfig`, including
+    /// its `User-Agent`. It also replaces any header egg-mode sets for the call itself, so avoid
+    /// setting `Authorization` here unless you mean to replace the call's signature.
+    pub fn with_header<K: Into<String>, V: Into<String>>(self, name: K, value: V)
+        -> TwitterFuture<T>
+    {
+        TwitterFuture {
+            request: self.request.with_header(name.into(), value.into()),
+            make_resp: self.make_resp,
+        }
+    }
+}
+
 impl<T> Future for TwitterFuture<T> {
     type Item = T;
     type Error = error::Error;
@@ -511,10 +921,21 @@ pub fn make_response<T: FromJson>(full_resp: String, headers: &Headers)
     Ok(Response::map(rate_headers(headers), |_| out))
 }
 
-pub fn make_future<T>(handle: &Handle,
-                      request: Request,
-                      make_resp: fn(String, &Headers) -> Result<T, error::Error>)
+/// Shortcut `MakeResponse` method for calls that don't return anything useful, which only loads
+/// rate-limit information from the response headers.
+///
+/// Several write calls answer with an empty body, an empty object, or some other body that isn't
+/// worth parsing, so this doesn't look at the body at all. By the time this is called, the call
+/// has already succeeded: `RawFuture` turns error statuses and error bodies into errors first.
+pub fn parse_empty_response(_: String, headers: &Headers) -> Result<Response<()>, error::Error> {
+    Ok(rate_headers(headers))
+}
+
+pub fn make_future<T, R>(handle: &Handle,
+                         request: R,
+                         make_resp: fn(String, &Headers) -> Result<T, error::Error>)
     -> TwitterFuture<T>
+    where R: Into<auth::SignedRequest>
 {
     TwitterFuture {
         request: make_raw_future(handle, request),
@@ -522,13 +943,156 @@ pub fn make_future<T>(handle: &Handle,
     }
 }
 
+/// Like `make_future`, but responses with the given error status are handed to `make_resp` like
+/// any other, instead of being turned into an error.
+///
+/// This is for calls where Twitter answers with an error to say "no" or "nothing here". The
+/// parser gets the error body along with the response headers, so it can tell that answer apart
+/// from a real error and still load the rate-limit information that came with it.
+pub fn make_future_expecting<T, R>(handle: &Handle,
+                                   request: R,
+                                   status: StatusCode,
+                                   make_resp: fn(String, &Headers) -> Result<T, error::Error>)
+    -> TwitterFuture<T>
+    where R: Into<auth::SignedRequest>
+{
+    TwitterFuture {
+        request: RawFuture {
+            expected_status: Some(status),
+            ..make_raw_future(handle, request)
+        },
+        make_resp: make_resp,
+    }
+}
+
+/// Creates a `TwitterFuture` that fails with the given error the first time it's polled, without
+/// calling Twitter.
+///
+/// This is for functions that can tell their input is invalid before making a call, but still
+/// need to return the same type of future as when the input is fine.
+pub fn make_failed_future<T>(handle: &Handle, err: error::Error) -> TwitterFuture<T> {
+    fn never_called<T>(_: String, _: &Headers) -> Result<T, error::Error> {
+        Err(FutureAlreadyCompleted)
+    }
+
+    TwitterFuture {
+        request: RawFuture {
+            handle: handle.clone(),
+            request: None,
+            response: None,
+            resp_headers: None,
+            resp_status: None,
+            body_stream: None,
+            body: Vec::new(),
+            timeout: None,
+            call_timeout: None,
+            call_headers: Vec::new(),
+            template: None,
+            attempts: 0,
+            retry_wait: None,
+            error: Some(err),
+            expected_status: None,
+        },
+        make_resp: never_called,
+    }
+}
+
 /// Shortcut function to create a `TwitterFuture` that parses out the given type from its response.
-pub fn make_parsed_future<T: FromJson>(handle: &Handle, request: Request)
-    -> TwitterFuture<Response<T>>
+pub fn make_parsed_future<T, R>(handle: &Handle, request: R) -> TwitterFuture<Response<T>>
+    where T: FromJson, R: Into<auth::SignedRequest>
 {
     make_future(handle, request, make_response)
 }
 
+/// A `Future` that runs several calls to Twitter at once, and combines their results into one
+/// collection when they all complete.
+///
+/// This is returned by functions that need to split their input across multiple calls, like
+/// `tweet::lookup`, which can only load 100 tweets at a time. The calls are made in parallel, and
+/// their results are combined in the order of the input. If any call fails, the whole future
+/// returns that error.
+///
+/// The rate-limit information in the final `Response` is taken from the call with the latest
+/// `rate_limit_reset`, or the one with the fewest calls remaining if several share that reset
+/// time, the same as when collecting several `Response`s into one. If no calls needed to be made
+/// (for example, because the input was empty), the future will complete immediately with an empty
+/// collection and all the rate-limit fields set to -1.
+#[must_use = "futures do nothing unless polled"]
+pub struct BatchFuture<T> {
+    loaders: Vec<Option<TwitterFuture<Response<T>>>>,
+    results: Vec<Option<Response<T>>>,
+    completed: bool,
+}
+
+impl<T> Future for BatchFuture<T>
+    where T: Default + IntoIterator + Extend<<T as IntoIterator>::Item>
+{
+    type Item = Response<T>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.completed {
+            return Err(FutureAlreadyCompleted);
+        }
+
+        let mut pending = false;
+
+        for (loader, result) in self.loaders.iter_mut().zip(self.results.iter_mut()) {
+            if let Some(mut fut) = loader.take() {
+                match fut.poll() {
+                    Ok(Async::Ready(resp)) => *result = Some(resp),
+                    Ok(Async::NotReady) => {
+                        *loader = Some(fut);
+                        pending = true;
+                    }
+                    Err(e) => {
+                        self.completed = true;
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        if pending {
+            return Ok(Async::NotReady);
+        }
+
+        self.completed = true;
+
+        let mut resp = Response {
+            rate_limit: -1,
+            rate_limit_remaining: -1,
+            rate_limit_reset: -1,
+            response: T::default(),
+        };
+
+        for item in self.results.drain(..).filter_map(|r| r) {
+            if item.rate_limit_reset > resp.rate_limit_reset ||
+                ((item.rate_limit_reset == resp.rate_limit_reset) &&
+                 (item.rate_limit_remaining < resp.rate_limit_remaining))
+            {
+                resp.rate_limit = item.rate_limit;
+                resp.rate_limit_remaining = item.rate_limit_remaining;
+                resp.rate_limit_reset = item.rate_limit_reset;
+            }
+            resp.response.extend(item.response);
+        }
+
+        Ok(Async::Ready(resp))
+    }
+}
+
+/// Creates a new `BatchFuture` that will run all the given futures and combine their results.
+pub fn make_batch_future<T>(loaders: Vec<TwitterFuture<Response<T>>>) -> BatchFuture<T> {
+    let results = loaders.iter().map(|_| None).collect();
+
+    BatchFuture {
+        loaders: loaders.into_iter().map(Some).collect(),
+        results: results,
+        completed: false,
+    }
+}
+
 pub fn rate_headers(resp: &Headers) -> Response<()> {
     Response {
         rate_limit: resp.get::<XRateLimitLimit>().map_or(-1, |h| h.0),
@@ -9,8 +9,14 @@
 //! module. The rest of it is available to make sure consumers of the API can understand precisely
 //! what types come out of functions that return `CursorIter`.
 
+use std::cmp;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono;
 use futures::{Future, Stream, Poll, Async};
 use rustc_serialize::json;
+use tokio_core::reactor::Timeout;
 use common::*;
 use auth;
 use error;
@@ -241,6 +247,38 @@ impl Cursor for ListCursor {
 /// re-initiate the late network call; this way, you can wait for your network connection to return
 /// or for your rate limit to refresh and try again with the same state.
 ///
+/// ## Waiting out the rate limit
+///
+/// If you're loading a large collection (say, all the followers of a popular account), odds are
+/// you'll run out of calls before you run out of pages. To save you from catching the error and
+/// re-polling yourself, `CursorIter` has another adaptor, `with_backoff`. When this is set, the
+/// stream will check the rate-limit information on each page it loads, and if it sees that there
+/// are no more calls left in the current window, it will wait until the window resets before
+/// loading the next page. The same happens if Twitter returns a rate-limit error outright. The
+/// duration you give to `with_backoff` is the longest it will wait at a time, in case Twitter
+/// hands back a reset time that's ridiculous.
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use std::time::Duration;
+/// use futures::Stream;
+///
+/// let ids = egg_mode::user::followers_ids("rustlang", &token, &handle)
+///                          .with_page_size(5000)
+///                          .with_backoff(Duration::from_secs(15 * 60));
+/// let ids = core.run(ids.collect()).unwrap();
+/// # }
+/// ```
+///
+/// ## Loading whole pages
+///
+/// If you'd rather handle results a page at a time (say, to save your place after each one), the
+/// `pages` adaptor turns a `CursorIter` into a stream of `Response<Vec<T::Item>>`, with one item
+/// per network call. It follows the same `with_page_size` and `with_backoff` settings.
+///
 /// ## Manual paging
 ///
 /// The `Stream` implementation works by loading in a page of results (with size set by the
@@ -286,6 +324,7 @@ pub struct CursorIter<'a, T>
     ///some calls don't allow you to set the size of the pages at all. Refer to the individual
     ///methods' documentation for specifics.
     pub page_size: Option<i32>,
+    max_page_size: Option<i32>,
     ///Numeric reference to the previous page of results. A value of zero indicates that the
     ///current page of results is the first page of the cursor.
     ///
@@ -300,8 +339,12 @@ pub struct CursorIter<'a, T>
     ///implementation. It is made available for those who wish to manually manage network calls and
     ///pagination.
     pub next_cursor: i64,
+    page_cursor: i64,
     loader: Option<FutureResponse<T>>,
     iter: Option<ResponseIter<T::Item>>,
+    backoff: Option<Duration>,
+    reset_at: Option<i32>,
+    wait: Option<Timeout>,
 }
 
 impl<'a, T> CursorIter<'a, T>
@@ -314,11 +357,21 @@ impl<'a, T> CursorIter<'a, T>
     ///methods' documentation for specifics. If this method is called for a response that does not
     ///accept changing the page size, no change to the underlying struct will occur.
     ///
+    ///If the given page size is larger than the call's maximum, the maximum will be used instead,
+    ///rather than sending a value Twitter would reject. Likewise, page sizes below 1 are raised to
+    ///1. Note that this only applies to this method; if you set the `page_size` field directly,
+    ///it will be sent as-is.
+    ///
     ///Calling this function will invalidate any current results, if any were previously loaded.
     pub fn with_page_size(self, page_size: i32) -> CursorIter<'a, T> {
         if self.page_size.is_some() {
+            let page_size = match self.max_page_size {
+                Some(max) => cmp::min(page_size, max),
+                None => page_size,
+            };
+
             CursorIter {
-                page_size: Some(page_size),
+                page_size: Some(cmp::max(page_size, 1)),
                 previous_cursor: -1,
                 next_cursor: -1,
                 loader: None,
@@ -330,6 +383,68 @@ impl<'a, T> CursorIter<'a, T>
         }
     }
 
+    ///Sets this `CursorIter` to wait for the rate-limit window to reset instead of running into a
+    ///rate-limit error.
+    ///
+    ///When this is set, if a page of results comes back with no calls remaining in the current
+    ///rate-limit window, the next page won't be requested until the time given in
+    ///`rate_limit_reset`. If Twitter returns a rate-limit error anyway, the stream will wait the
+    ///same way and retry the call instead of returning the error. Each wait is capped at
+    ///`max_wait`, in case the reset time is further out than expected.
+    pub fn with_backoff(self, max_wait: Duration) -> CursorIter<'a, T> {
+        CursorIter {
+            backoff: Some(max_wait),
+            ..self
+        }
+    }
+
+    ///Returns a snapshot of where this `CursorIter` is in its results, so it can be saved and
+    ///picked up again later with `resume`.
+    ///
+    ///If some results from the current page haven't been returned from the stream yet, the
+    ///snapshot points at the start of that page, so those results aren't lost. This means a
+    ///resumed `CursorIter` may return some results a second time. To avoid this, save the state
+    ///between pages, for example by using `pages` and calling `CursorPages::state` after each one.
+    pub fn state(&self) -> CursorState {
+        let mid_page = self.iter.as_ref().map_or(false, |iter| iter.len() > 0);
+
+        CursorState {
+            cursor: if mid_page { self.page_cursor } else { self.next_cursor },
+            page_size: self.page_size,
+        }
+    }
+
+    ///Picks up this `CursorIter` from the given saved state, as returned by `state`.
+    ///
+    ///The state should come from a `CursorIter` for the same call and parameters as this one;
+    ///Twitter's cursor IDs don't mean anything for other calls. Calling this function will
+    ///invalidate any current results, if any were previously loaded.
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core; extern crate futures;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///# let saved_state: egg_mode::cursor::CursorState = unimplemented!();
+    ///use futures::Stream;
+    ///
+    ///let pages = egg_mode::user::followers_ids("rustlang", &token, &handle)
+    ///                           .resume(saved_state)
+    ///                           .pages();
+    ///# }
+    ///```
+    pub fn resume(self, state: CursorState) -> CursorIter<'a, T> {
+        CursorIter {
+            page_size: if self.page_size.is_some() { state.page_size } else { None },
+            previous_cursor: -1,
+            next_cursor: state.cursor,
+            page_cursor: state.cursor,
+            loader: None,
+            iter: None,
+            ..self
+        }
+    }
+
     ///Loads the next page of results.
     ///
     ///This is intended to be used as part of this struct's Iterator implementation. It is provided
@@ -360,21 +475,59 @@ impl<'a, T> CursorIter<'a, T>
             handle: handle.clone(),
             params_base: params_base,
             page_size: page_size,
+            max_page_size: None,
             previous_cursor: -1,
             next_cursor: -1,
+            page_cursor: -1,
             loader: None,
             iter: None,
+            backoff: None,
+            reset_at: None,
+            wait: None,
+        }
+    }
+
+    ///Sets the largest page size the call will accept, for `with_page_size` to clamp to.
+    ///
+    ///This is essentially an internal infrastructure function, not meant to be used from consumer
+    ///code.
+    #[doc(hidden)]
+    pub fn max_page_size(self, max_page_size: i32) -> CursorIter<'a, T> {
+        CursorIter {
+            max_page_size: Some(max_page_size),
+            ..self
         }
     }
+
+    ///Creates a `Timeout` that will fire when the given rate-limit reset time has passed, or
+    ///after `max_wait`, whichever is sooner.
+    fn wait_until(&self, reset: i32, max_wait: Duration) -> Result<Timeout, error::Error> {
+        let now = chrono::Utc::now().timestamp();
+        //add a second of slack, so we don't wake up right before the window resets
+        let secs = cmp::max(reset as i64 - now + 1, 0) as u64;
+        let wait = cmp::min(Duration::from_secs(secs), max_wait);
+
+        Ok(try!(Timeout::new(wait, &self.handle)))
+    }
 }
 
-impl<'a, T> Stream for CursorIter<'a, T>
+impl<'a, T> CursorIter<'a, T>
     where T: Cursor + FromJson + 'a
 {
-    type Item = Response<T::Item>;
-    type Error = error::Error;
+    ///Loads the next full page of results, waiting out the rate limit if `with_backoff` was set.
+    ///Returns `None` once the last page has been loaded.
+    fn poll_page(&mut self) -> Poll<Option<Response<Vec<T::Item>>>, error::Error> {
+        if let Some(mut wait) = self.wait.take() {
+            match wait.poll() {
+                Ok(Async::NotReady) => {
+                    self.wait = Some(wait);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(())) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
 
-    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
         if let Some(mut fut) = self.loader.take() {
             match fut.poll() {
                 Ok(Async::NotReady) => {
@@ -385,31 +538,368 @@ impl<'a, T> Stream for CursorIter<'a, T>
                     self.previous_cursor = resp.previous_cursor_id();
                     self.next_cursor = resp.next_cursor_id();
 
-                    let resp = Response::map(resp, |r| r.into_inner());
+                    if resp.rate_limit_remaining == 0 {
+                        self.reset_at = Some(resp.rate_limit_reset);
+                    }
 
-                    let mut iter = resp.into_iter();
-                    let first = iter.next();
-                    self.iter = Some(iter);
+                    return Ok(Async::Ready(Some(Response::map(resp, |r| r.into_inner()))));
+                }
+                Err(error::Error::RateLimit(reset)) if self.backoff.is_some() => {
+                    let max_wait = self.backoff.unwrap();
+                    self.wait = Some(try!(self.wait_until(reset, max_wait)));
+                    return self.poll_page();
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
-                    match first {
-                        Some(item) => return Ok(Async::Ready(Some(item))),
-                        None => return Ok(Async::Ready(None)),
-                    }
+        if self.next_cursor == 0 {
+            return Ok(Async::Ready(None));
+        }
+
+        if let (Some(reset), Some(max_wait)) = (self.reset_at.take(), self.backoff) {
+            self.wait = Some(try!(self.wait_until(reset, max_wait)));
+            return self.poll_page();
+        }
+
+        self.page_cursor = self.next_cursor;
+        self.loader = Some(self.call());
+        self.poll_page()
+    }
+
+    ///Converts this `CursorIter` into a stream that yields whole pages of results instead of
+    ///individual items.
+    ///
+    ///Each item of the returned stream is the full result of one network call, along with its
+    ///rate-limit information. This is useful if you need to know where the page boundaries are,
+    ///for example to save your progress (via `next_cursor`) after handling each page. Settings
+    ///like `with_page_size` and `with_backoff` carry over, but any results that were already
+    ///loaded and not yet returned by this `CursorIter` are discarded.
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core; extern crate futures;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///use futures::Stream;
+    ///
+    ///let pages = egg_mode::user::followers_ids("rustlang", &token, &handle)
+    ///                           .with_page_size(5000)
+    ///                           .pages();
+    ///
+    ///core.run(pages.for_each(|page| {
+    ///    println!("loaded {} IDs, {} calls left", page.len(), page.rate_limit_remaining);
+    ///    Ok(())
+    ///})).unwrap();
+    ///# }
+    ///```
+    pub fn pages(self) -> CursorPages<'a, T> {
+        CursorPages {
+            iter: CursorIter {
+                iter: None,
+                ..self
+            },
+        }
+    }
+}
+
+impl<'a> CursorIter<'a, IDCursor> {
+    ///Loads every remaining page of IDs and collects them into a `HashSet`.
+    ///
+    ///This is a shortcut for the most common way to use calls like `user::followers_ids`: loading
+    ///the whole list at once, to compare against or check membership in later. Since this is
+    ///likely to run into the rate limit for large lists, this sets `with_backoff` with a maximum
+    ///wait of 15 minutes (the length of Twitter's rate-limit windows), unless a backoff has
+    ///already been set. Any IDs that were already loaded but not yet returned by this `CursorIter`
+    ///are included in the set.
+    ///
+    ///The rate-limit information in the final `Response` is from the last page that was loaded.
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///let followers = egg_mode::user::followers_ids("rustlang", &token, &handle)
+    ///                               .with_page_size(5000)
+    ///                               .collect_ids();
+    ///let followers = core.run(followers).unwrap();
+    ///
+    ///println!("rustlang has {} followers", followers.len());
+    ///# }
+    ///```
+    pub fn collect_ids(self) -> IDSetFuture<'a> {
+        let mut iter = if self.backoff.is_some() {
+            self
+        } else {
+            self.with_backoff(Duration::from_secs(15 * 60))
+        };
+
+        let ids = iter.iter.take()
+                      .map_or_else(HashSet::new, |rest| rest.map(|id| id.response).collect());
+
+        IDSetFuture {
+            iter: iter,
+            ids: Some(Response {
+                rate_limit: -1,
+                rate_limit_remaining: -1,
+                rate_limit_reset: -1,
+                response: ids,
+            }),
+        }
+    }
+}
+
+///A `Future` that loads every page of a cursored list of IDs into a `HashSet`, returned by
+///`CursorIter::collect_ids`.
+#[must_use = "futures do nothing unless polled"]
+pub struct IDSetFuture<'a> {
+    iter: CursorIter<'a, IDCursor>,
+    ids: Option<Response<HashSet<u64>>>,
+}
+
+impl<'a> Future for IDSetFuture<'a> {
+    type Item = Response<HashSet<u64>>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match self.iter.poll_page() {
+                Ok(Async::Ready(Some(page))) => {
+                    let ids = try!(self.ids.as_mut().ok_or(error::Error::FutureAlreadyCompleted));
+                    ids.rate_limit = page.rate_limit;
+                    ids.rate_limit_remaining = page.rate_limit_remaining;
+                    ids.rate_limit_reset = page.rate_limit_reset;
+                    ids.response.extend(page.response);
+                }
+                Ok(Async::Ready(None)) => {
+                    let ids = try!(self.ids.take().ok_or(error::Error::FutureAlreadyCompleted));
+                    return Ok(Async::Ready(ids));
                 }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Err(e) => return Err(e),
             }
         }
+    }
+}
+
+impl<'a, T> Stream for CursorIter<'a, T>
+    where T: Cursor + FromJson + 'a
+{
+    type Item = Response<T::Item>;
+    type Error = error::Error;
 
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
         if let Some(ref mut results) = self.iter {
             if let Some(item) = results.next() {
                 return Ok(Async::Ready(Some(item)));
             }
-            else if self.next_cursor == 0 {
-                return Ok(Async::Ready(None));
+        }
+
+        match self.poll_page() {
+            Ok(Async::Ready(Some(page))) => {
+                let mut iter = page.into_iter();
+                let first = iter.next();
+                self.iter = Some(iter);
+
+                Ok(Async::Ready(first))
             }
+            Ok(Async::Ready(None)) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(e) => Err(e),
         }
+    }
+}
 
-        self.loader = Some(self.call());
-        self.poll()
+///A stream over the pages of a cursored collection, returned by `CursorIter::pages`.
+///
+///Each item is one page of results, as loaded by a single network call. The `next_cursor` and
+///`previous_cursor` of the page that was just returned can be read with the methods of the same
+///name, so you can save your place and pick up from there later with `CursorIter`'s fields.
+#[must_use = "cursor iterators are lazy and do nothing unless consumed"]
+pub struct CursorPages<'a, T>
+    where T: Cursor + FromJson + 'a
+{
+    iter: CursorIter<'a, T>,
+}
+
+impl<'a, T> CursorPages<'a, T>
+    where T: Cursor + FromJson + 'a
+{
+    ///Returns the cursor ID of the page after the one most recently returned. A value of zero
+    ///means the last page has been reached.
+    pub fn next_cursor(&self) -> i64 {
+        self.iter.next_cursor
+    }
+
+    ///Returns the cursor ID of the page before the one most recently returned. A value of zero
+    ///means the most recent page was the first one.
+    pub fn previous_cursor(&self) -> i64 {
+        self.iter.previous_cursor
+    }
+
+    ///Returns a snapshot of where this stream is in its results, pointing at the page after the
+    ///one most recently returned. Give this to `CursorIter::resume` to pick up from there later.
+    pub fn state(&self) -> CursorState {
+        self.iter.state()
+    }
+}
+
+///A saved position in a cursored collection, returned by `CursorIter::state`.
+///
+///This implements serde's `Serialize` and `Deserialize`, so it can be saved to disk and loaded
+///back in after a restart, to resume a long-running load with `CursorIter::resume`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorState {
+    ///The cursor ID of the next page to load. A value of zero means every page has been loaded.
+    pub cursor: i64,
+    ///The page size that was set on the `CursorIter`, if the call takes one.
+    pub page_size: Option<i32>,
+}
+
+impl<'a, T> Stream for CursorPages<'a, T>
+    where T: Cursor + FromJson + 'a
+{
+    type Item = Response<Vec<T::Item>>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.iter.poll_page()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CursorIter, CursorState, UserCursor, IDCursor};
+    use auth;
+    use common::Response;
+    use links;
+
+    use futures::{Stream, Async};
+    use tokio_core::reactor::Core;
+
+    #[test]
+    fn clamp_page_size() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        let new_iter = || {
+            CursorIter::<UserCursor>::new(links::users::FRIENDS_LIST, &token, &handle,
+                                          None, Some(20))
+                .max_page_size(200)
+        };
+
+        assert_eq!(new_iter().with_page_size(150).page_size, Some(150));
+        assert_eq!(new_iter().with_page_size(200).page_size, Some(200));
+        assert_eq!(new_iter().with_page_size(201).page_size, Some(200));
+        assert_eq!(new_iter().with_page_size(5000).page_size, Some(200));
+        assert_eq!(new_iter().with_page_size(1).page_size, Some(1));
+        assert_eq!(new_iter().with_page_size(0).page_size, Some(1));
+        assert_eq!(new_iter().with_page_size(-5).page_size, Some(1));
+
+        //calls without a maximum only get the lower bound
+        let iter = CursorIter::<UserCursor>::new(links::users::FRIENDS_LIST, &token, &handle,
+                                                 None, Some(20));
+        assert_eq!(iter.with_page_size(5000).page_size, Some(5000));
+
+        //calls that don't take a page size ignore it
+        let iter = CursorIter::<UserCursor>::new(links::users::BLOCKS_LIST, &token, &handle,
+                                                 None, None);
+        assert_eq!(iter.with_page_size(5000).page_size, None);
+    }
+
+    #[test]
+    fn finished_pages() {
+        //the core is never run, so a stream that tried to load another page would return NotReady
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        let mut iter = CursorIter::<IDCursor>::new(links::users::FOLLOWERS_IDS, &token, &handle,
+                                                   None, Some(5000));
+        iter.next_cursor = 0;
+        let mut pages = iter.pages();
+
+        match pages.poll() {
+            Ok(Async::Ready(None)) => (),
+            Ok(Async::Ready(Some(_))) => panic!("finished cursor returned another page"),
+            Ok(Async::NotReady) => panic!("finished cursor tried to load another page"),
+            Err(e) => panic!("finished cursor returned an error: {}", e),
+        }
+        assert_eq!(pages.next_cursor(), 0);
+    }
+
+    #[test]
+    fn collect_ids() {
+        use futures::Future;
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        //IDs left over from a partly-read page are kept, and repeats are dropped
+        let mut iter = CursorIter::<IDCursor>::new(links::users::FOLLOWERS_IDS, &token, &handle,
+                                                   None, Some(5000));
+        iter.next_cursor = 0;
+        iter.iter = Some(Response {
+            rate_limit: 15,
+            rate_limit_remaining: 14,
+            rate_limit_reset: 0,
+            response: vec![1, 2, 2, 3],
+        }.into_iter());
+
+        let mut ids = iter.collect_ids();
+        assert!(ids.iter.backoff.is_some());
+
+        match ids.poll() {
+            Ok(Async::Ready(ids)) => {
+                let mut ids = ids.response.into_iter().collect::<Vec<_>>();
+                ids.sort();
+                assert_eq!(ids, vec![1, 2, 3]);
+            }
+            Ok(Async::NotReady) => panic!("finished cursor tried to load another page"),
+            Err(e) => panic!("finished cursor returned an error: {}", e),
+        }
+    }
+
+    #[test]
+    fn cursor_state() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+        let new_iter = || CursorIter::<IDCursor>::new(links::users::FOLLOWERS_IDS, &token, &handle,
+                                                      None, Some(5000));
+
+        let mut iter = new_iter();
+        assert_eq!(iter.state(), CursorState { cursor: -1, page_size: Some(5000) });
+
+        //partway through a page, the state points back at the start of that page
+        iter.page_cursor = 1234;
+        iter.next_cursor = 5678;
+        iter.iter = Some(Response {
+            rate_limit: 15,
+            rate_limit_remaining: 14,
+            rate_limit_reset: 0,
+            response: vec![1, 2],
+        }.into_iter());
+        assert_eq!(iter.state().cursor, 1234);
+
+        iter.iter.as_mut().unwrap().next();
+        iter.iter.as_mut().unwrap().next();
+        assert_eq!(iter.state().cursor, 5678);
+
+        let saved = ::serde_json::to_string(&iter.state()).unwrap();
+        let state: CursorState = ::serde_json::from_str(&saved).unwrap();
+
+        let resumed = new_iter().with_page_size(100).resume(state);
+        assert_eq!(resumed.next_cursor, 5678);
+        assert_eq!(resumed.page_size, Some(5000));
+        assert!(resumed.iter.is_none());
+
+        //calls that don't take a page size keep ignoring it
+        let resumed = CursorIter::<UserCursor>::new(links::users::BLOCKS_LIST, &token, &handle,
+                                                    None, None).resume(state);
+        assert_eq!(resumed.page_size, None);
     }
 }
@@ -49,7 +49,6 @@ use std::mem;
 
 use rustc_serialize::json;
 use chrono;
-use hyper::client::Request;
 use futures::{Async, Future, Poll};
 use futures::future::Join;
 
@@ -354,7 +353,7 @@ impl Timeline {
     }
 
     ///Helper function to construct a `Request` from the current state.
-    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> Request {
+    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> auth::SignedRequest {
         let mut params = self.params_base.as_ref().cloned().unwrap_or_default();
         add_param(&mut params, "count", self.count.to_string());
 
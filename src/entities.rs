@@ -175,7 +175,7 @@ pub struct VideoVariant {
 }
 
 ///Represents a link extracted from another piece of text.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UrlEntity {
     ///A truncated URL meant to be displayed inline with the text.
     pub display_url: String,
@@ -14,6 +14,32 @@
 //! [`Error`]: enum.Error.html
 //! [`TwitterErrorCode`]: struct.TwitterErrorCode.html
 //! [`TwitterErrors`]: struct.TwitterErrors.html
+//!
+//! The numeric codes that Twitter uses for some of its more common errors are available as
+//! constants in the [`codes`] module, so you can match on them without having to keep the list of
+//! error codes on hand:
+//!
+//! ```rust,no_run
+//! # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+//! # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+//! # fn main() {
+//! # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+//! use egg_mode::error::{self, Error};
+//!
+//! match core.run(egg_mode::tweet::show(1234, &token, &handle)) {
+//!     Ok(tweet) => println!("{}", tweet.text),
+//!     Err(Error::TwitterError(ref errs)) if errs.has_code(error::codes::NO_STATUS_FOUND) => {
+//!         println!("that tweet doesn't exist!");
+//!     }
+//!     Err(ref e) if e.is_rate_limit() => {
+//!         println!("slow down! try again at {:?}", e.rate_limit_reset());
+//!     }
+//!     Err(e) => println!("something else went wrong: {}", e),
+//! }
+//! # }
+//! ```
+//!
+//! [`codes`]: codes/index.html
 
 use std::{self, fmt};
 use hyper;
@@ -24,6 +50,47 @@ use chrono;
 
 use common::*;
 
+///Numeric error codes that Twitter can return in an [`Error::TwitterError`][].
+///
+///This is not a complete listing; the full list is available in [Twitter's API
+///documentation][error-codes].
+///
+///[`Error::TwitterError`]: ../enum.Error.html
+///[error-codes]: https://dev.twitter.com/overview/api/response-codes
+pub mod codes {
+    ///"Could not authenticate you." The tokens used to sign the request were invalid.
+    pub const COULD_NOT_AUTHENTICATE: i32 = 32;
+    ///"Sorry, that page does not exist." Usually returned when the requested resource is missing.
+    pub const PAGE_NOT_FOUND: i32 = 34;
+    ///"User not found."
+    pub const USER_NOT_FOUND: i32 = 50;
+    ///"User has been suspended."
+    pub const USER_SUSPENDED: i32 = 63;
+    ///"Rate limit exceeded." The rate limit for the requested method has been reached.
+    pub const RATE_LIMIT_EXCEEDED: i32 = 88;
+    ///"Invalid or expired token." The access token has been revoked or is otherwise unusable.
+    pub const INVALID_TOKEN: i32 = 89;
+    ///"Over capacity." Twitter is temporarily unable to serve the request.
+    pub const OVER_CAPACITY: i32 = 130;
+    ///"Internal error." Twitter encountered an unknown error while processing the request.
+    pub const INTERNAL_ERROR: i32 = 131;
+    ///"The specified user is not a member of this list." Also returned when checking for a list
+    ///subscriber that isn't subscribed.
+    pub const NOT_IN_LIST: i32 = 109;
+    ///"No status found with that ID."
+    pub const NO_STATUS_FOUND: i32 = 144;
+    ///"You are unable to follow more people at this time."
+    pub const FOLLOW_LIMIT: i32 = 161;
+    ///"Sorry, you are not authorized to see this status." The tweet is from a protected account.
+    pub const PROTECTED_STATUS: i32 = 179;
+    ///"Status is a duplicate."
+    pub const DUPLICATE_STATUS: i32 = 187;
+    ///"Bad authentication data." The request was missing authentication or it was malformed.
+    pub const BAD_AUTHENTICATION: i32 = 215;
+    ///"You have already retweeted this Tweet."
+    pub const ALREADY_RETWEETED: i32 = 327;
+}
+
 ///Represents a collection of errors returned from a Twitter API call.
 ///
 ///This is returned as part of [`Error::TwitterError`][] whenever Twitter has rejected a call.
@@ -35,6 +102,17 @@ pub struct TwitterErrors {
     pub errors: Vec<TwitterErrorCode>,
 }
 
+impl TwitterErrors {
+    ///Returns whether any of the contained errors have the given error code.
+    ///
+    ///Constants for common error codes are available in the [`codes`][] module.
+    ///
+    ///[`codes`]: codes/index.html
+    pub fn has_code(&self, code: i32) -> bool {
+        self.errors.iter().any(|e| e.code == code)
+    }
+}
+
 impl fmt::Display for TwitterErrors {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut first = true;
@@ -97,6 +175,12 @@ impl FromJson for MediaError {
 pub enum Error {
     ///A URL was passed to a shortcut function that didn't match the method being called.
     BadUrl,
+    ///The text given for a new tweet was longer than Twitter allows, so it wasn't sent. The
+    ///enclosed value is the length of the text, as counted by `tweet::text_length`.
+    TweetTooLong(usize),
+    ///An argument given to a function can't be used for that call, so egg-mode didn't send it to
+    ///Twitter. The enclosed values are the name of the argument and a description of the problem.
+    InvalidArgument(&'static str, &'static str),
     ///The response from Twitter was formatted incorrectly or in an unexpected manner. The enclosed
     ///values are an explanatory string and, if applicable, the input that caused the error.
     ///
@@ -148,10 +232,48 @@ pub enum Error {
     TimestampParseError(chrono::ParseError),
 }
 
+impl Error {
+    ///Returns whether this error was caused by hitting the rate limit.
+    ///
+    ///This checks both for `RateLimit`, and for a `TwitterError` with
+    ///`codes::RATE_LIMIT_EXCEEDED`, which is returned instead if Twitter didn't say when the
+    ///rate-limit window would reset.
+    pub fn is_rate_limit(&self) -> bool {
+        match *self {
+            Error::RateLimit(_) => true,
+            Error::TwitterError(ref errs) => errs.has_code(codes::RATE_LIMIT_EXCEEDED),
+            _ => false,
+        }
+    }
+
+    ///If this is a `RateLimit` error, returns the Unix timestamp in UTC when the next rate-limit
+    ///window will open.
+    pub fn rate_limit_reset(&self) -> Option<i32> {
+        match *self {
+            Error::RateLimit(reset) => Some(reset),
+            _ => None,
+        }
+    }
+
+    ///Returns whether this is a `TwitterError` that contains the given error code.
+    ///
+    ///Constants for common error codes are available in the [`codes`][] module.
+    ///
+    ///[`codes`]: codes/index.html
+    pub fn has_twitter_code(&self, code: i32) -> bool {
+        match *self {
+            Error::TwitterError(ref errs) => errs.has_code(code),
+            _ => false,
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
             Error::BadUrl => write!(f, "URL given did not match API method"),
+            Error::TweetTooLong(len) => write!(f, "Tweet text is too long ({} characters)", len),
+            Error::InvalidArgument(name, err) => write!(f, "Invalid argument `{}`: {}", name, err),
             Error::InvalidResponse(err, ref ext) => write!(f, "Invalid response received: {} ({:?})", err, ext),
             Error::MissingValue(val) => write!(f, "Value missing from response: {}", val),
             Error::FutureAlreadyCompleted => write!(f, "Future has already been completed"),
@@ -173,6 +295,8 @@ impl std::error::Error for Error {
     fn description(&self) -> &str {
         match *self {
             Error::BadUrl => "URL given did not match API method",
+            Error::TweetTooLong(_) => "Tweet text is too long",
+            Error::InvalidArgument(_, _) => "Invalid argument given",
             Error::InvalidResponse(_, _) => "Invalid response received",
             Error::MissingValue(_) => "Value missing from response",
             Error::FutureAlreadyCompleted => "Future has already been completed",
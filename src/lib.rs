@@ -91,9 +91,30 @@
 //! In addition, there is also a `FutureResponse` type alias, that corresponds to
 //! `TwitterFuture<'a, Response<T>>`, for methods that return rate-limit information.
 //!
+//! Since every call is a `Future`, egg-mode never blocks the thread it runs on, and can share an
+//! event loop with the rest of your application. If you'd rather make calls synchronously, run
+//! each future to completion with `Core::run`, the way the examples do, or with the `wait` method
+//! from the `Future` trait, as long as its event loop is running on another thread.
+//!
 //! [`TwitterFuture`]: struct.TwitterFuture.html
 //! [Tokio documentation guides]: https://tokio.rs/docs/getting-started/tokio/
 //!
+//! ## `ClientConfig`
+//!
+//! By default, egg-mode sets up its own connection to Twitter for each event loop you hand it. If
+//! you need to customize that connection, you can create a [`ClientConfig`][] and apply it to your
+//! event loop's `Handle`.
+//!
+//! [`ClientConfig`]: struct.ClientConfig.html
+//!
+//! ## `prelude`
+//!
+//! If you'd rather not import the handful of items you need for every program one at a time, the
+//! [`prelude`][] module re-exports the most common ones, so you can import them all with `use
+//! egg_mode::prelude::*;`.
+//!
+//! [`prelude`]: prelude/index.html
+//!
 //! ## Authentication Types/Functions
 //!
 //! The remaining types and methods are explained as part of the [authentication overview][Token],
@@ -117,6 +138,8 @@
 //!   their profile information, blocking or muting them, or showing the relationship between two
 //!   users.
 //! * `search`: Due to the complexity of searching for tweets, it gets its own module.
+//! * `saved_searches`: Here you can load and manage the search queries a user has saved to their
+//!   account.
 //! * `direct`: Here you can work with a user's Direct Messages, either by loading DMs they've sent
 //!   or received, or by sending new ones.
 //! * `list`: This module lets you act on lists, from creating and deleting them, adding and
@@ -131,6 +154,10 @@
 //!
 //! * `place`: Here are actions that look up physical locations that can be attached to tweets, as
 //!   well at the `Place` struct that appears on tweets with locations attached.
+//! * `suggestions`: Here you can load the accounts Twitter suggests following to new users, sorted
+//!   into categories.
+//! * `collections`: Here you can load collections, the curated sets of tweets users can put
+//!   together, and the tweets in them.
 //! * `service`: These are some miscellaneous methods that show information about the Twitter
 //!   service as a whole, like loading the maximum length of t.co URLs or loading the current Terms
 //!   of Service or Privacy Policy.
@@ -154,6 +181,7 @@
 
 #[macro_use] extern crate hyper;
 #[macro_use] extern crate lazy_static;
+#[macro_use] extern crate serde_derive;
 extern crate futures;
 extern crate tokio_core;
 extern crate hyper_tls;
@@ -165,6 +193,8 @@ extern crate sha_1;
 extern crate rustc_serialize;
 extern crate mime;
 extern crate chrono;
+extern crate flate2;
+#[cfg(test)] extern crate serde_json;
 extern crate regex;
 
 #[macro_use] mod common;
@@ -176,14 +206,20 @@ pub mod cursor;
 pub mod tweet;
 pub mod media;
 pub mod search;
+pub mod saved_searches;
+pub mod suggestions;
+pub mod collections;
 pub mod place;
 pub mod direct;
 pub mod service;
 pub mod list;
 pub mod stream;
+pub mod prelude;
 mod links;
 
 pub use auth::{KeyPair, Token, AuthFuture, request_token, authorize_url, authenticate_url,
-               access_token, verify_tokens, bearer_token, invalidate_bearer};
-pub use common::{Response, ResponseIter, ResponseIterRef,
-                 ResponseIterMut, FutureResponse, TwitterFuture};
+               access_token, verify_tokens, verify_tokens_with_email, bearer_token,
+               invalidate_bearer, invalidate_access};
+pub use common::{Response, RateLimit, ResponseIter, ResponseIterRef,
+                 ResponseIterMut, FutureResponse, TwitterFuture, BatchFuture};
+pub use common::{ClientConfig, HttpsClient, USER_AGENT};
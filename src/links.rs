@@ -7,6 +7,7 @@ pub mod auth {
     pub const ACCESS_TOKEN: &'static str = "https://api.twitter.com/oauth/access_token";
     pub const BEARER_TOKEN: &'static str = "https://api.twitter.com/oauth2/token";
     pub const INVALIDATE_BEARER: &'static str = "https://api.twitter.com/oauth2/invalidate_token";
+    pub const INVALIDATE_ACCESS: &'static str = "https://api.twitter.com/1.1/oauth/invalidate_token";
     pub const AUTHORIZE: &'static str = "https://api.twitter.com/oauth/authorize";
     pub const AUTHENTICATE: &'static str = "https://api.twitter.com/oauth/authenticate";
     pub const VERIFY_CREDENTIALS: &'static str = "https://api.twitter.com/1.1/account/verify_credentials.json";
@@ -16,6 +17,7 @@ pub mod users {
     pub const LOOKUP: &'static str = "https://api.twitter.com/1.1/users/lookup.json";
     pub const SHOW: &'static str = "https://api.twitter.com/1.1/users/show.json";
     pub const SEARCH: &'static str = "https://api.twitter.com/1.1/users/search.json";
+    pub const PROFILE_BANNER: &'static str = "https://api.twitter.com/1.1/users/profile_banner.json";
     pub const FRIENDS_LIST: &'static str = "https://api.twitter.com/1.1/friends/list.json";
     pub const FRIENDS_IDS: &'static str = "https://api.twitter.com/1.1/friends/ids.json";
     pub const FOLLOWERS_LIST: &'static str = "https://api.twitter.com/1.1/followers/list.json";
@@ -37,27 +39,34 @@ pub mod users {
     pub const REPORT_SPAM: &'static str = "https://api.twitter.com/1.1/users/report_spam.json";
     pub const MUTE: &'static str = "https://api.twitter.com/1.1/mutes/users/create.json";
     pub const UNMUTE: &'static str = "https://api.twitter.com/1.1/mutes/users/destroy.json";
+    pub const UPDATE_PROFILE_BACKGROUND_IMAGE: &'static str = "https://api.twitter.com/1.1/account/update_profile_background_image.json";
+    pub const REMOVE_PROFILE_BANNER: &'static str = "https://api.twitter.com/1.1/account/remove_profile_banner.json";
+    pub const SETTINGS: &'static str = "https://api.twitter.com/1.1/account/settings.json";
 }
 
 pub mod statuses {
     pub const SHOW: &'static str = "https://api.twitter.com/1.1/statuses/show.json";
     pub const RETWEETS_OF_STEM: &'static str = "https://api.twitter.com/1.1/statuses/retweets";
     pub const LOOKUP: &'static str = "https://api.twitter.com/1.1/statuses/lookup.json";
+    pub const OEMBED: &'static str = "https://api.twitter.com/1.1/statuses/oembed.json";
     pub const HOME_TIMELINE: &'static str = "https://api.twitter.com/1.1/statuses/home_timeline.json";
     pub const MENTIONS_TIMELINE: &'static str = "https://api.twitter.com/1.1/statuses/mentions_timeline.json";
     pub const USER_TIMELINE: &'static str = "https://api.twitter.com/1.1/statuses/user_timeline.json";
     pub const RETWEETS_OF_ME: &'static str = "https://api.twitter.com/1.1/statuses/retweets_of_me.json";
     pub const RETWEETERS_OF: &'static str = "https://api.twitter.com/1.1/statuses/retweeters/ids.json";
-    pub const LIKES_OF: &'static str = "https://api.twitter.com/1.1/favorites/list.json";
     pub const SEARCH: &'static str = "https://api.twitter.com/1.1/search/tweets.json";
     pub const RETWEET_STEM: &'static str = "https://api.twitter.com/1.1/statuses/retweet";
     pub const UNRETWEET_STEM: &'static str = "https://api.twitter.com/1.1/statuses/unretweet";
-    pub const LIKE: &'static str = "https://api.twitter.com/1.1/favorites/create.json";
-    pub const UNLIKE: &'static str = "https://api.twitter.com/1.1/favorites/destroy.json";
     pub const UPDATE: &'static str = "https://api.twitter.com/1.1/statuses/update.json";
     pub const DELETE_STEM: &'static str = "https://api.twitter.com/1.1/statuses/destroy";
 }
 
+pub mod favorites {
+    pub const LIST: &'static str = "https://api.twitter.com/1.1/favorites/list.json";
+    pub const CREATE: &'static str = "https://api.twitter.com/1.1/favorites/create.json";
+    pub const DESTROY: &'static str = "https://api.twitter.com/1.1/favorites/destroy.json";
+}
+
 pub mod media {
     pub const UPLOAD: &'static str = "https://upload.twitter.com/1.1/media/upload.json";
     pub const METADATA: &'static str = "https://upload.twitter.com/1.1/media/metadata/create.json";
@@ -106,8 +115,165 @@ pub mod service {
     pub const RATE_LIMIT_STATUS: &'static str = "https://api.twitter.com/1.1/application/rate_limit_status.json";
 }
 
+pub mod saved_searches {
+    pub const LIST: &'static str = "https://api.twitter.com/1.1/saved_searches/list.json";
+    pub const SHOW_STEM: &'static str = "https://api.twitter.com/1.1/saved_searches/show";
+    pub const CREATE: &'static str = "https://api.twitter.com/1.1/saved_searches/create.json";
+    pub const DESTROY_STEM: &'static str = "https://api.twitter.com/1.1/saved_searches/destroy";
+}
+
+pub mod suggestions {
+    pub const CATEGORIES: &'static str = "https://api.twitter.com/1.1/users/suggestions.json";
+    pub const SUGGESTIONS_STEM: &'static str = "https://api.twitter.com/1.1/users/suggestions";
+}
+
+pub mod collections {
+    pub const SHOW: &'static str = "https://api.twitter.com/1.1/collections/show.json";
+    pub const ENTRIES: &'static str = "https://api.twitter.com/1.1/collections/entries.json";
+    pub const LIST: &'static str = "https://api.twitter.com/1.1/collections/list.json";
+}
+
 pub mod stream {
     pub const USER: &'static str = "https://userstream.twitter.com/1.1/user.json";
     pub const SAMPLE: &'static str = "https://stream.twitter.com/1.1/statuses/sample.json";
     pub const FILTER: &'static str = "https://stream.twitter.com/1.1/statuses/filter.json";
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    //every link in this file; add new ones here so they get checked too
+    fn all_links() -> Vec<(&'static str, &'static str, &'static str)> {
+        vec![
+            ("auth", "REQUEST_TOKEN", auth::REQUEST_TOKEN),
+            ("auth", "ACCESS_TOKEN", auth::ACCESS_TOKEN),
+            ("auth", "BEARER_TOKEN", auth::BEARER_TOKEN),
+            ("auth", "INVALIDATE_BEARER", auth::INVALIDATE_BEARER),
+            ("auth", "INVALIDATE_ACCESS", auth::INVALIDATE_ACCESS),
+            ("auth", "AUTHORIZE", auth::AUTHORIZE),
+            ("auth", "AUTHENTICATE", auth::AUTHENTICATE),
+            ("auth", "VERIFY_CREDENTIALS", auth::VERIFY_CREDENTIALS),
+            ("users", "LOOKUP", users::LOOKUP),
+            ("users", "SHOW", users::SHOW),
+            ("users", "SEARCH", users::SEARCH),
+            ("users", "PROFILE_BANNER", users::PROFILE_BANNER),
+            ("users", "FRIENDS_LIST", users::FRIENDS_LIST),
+            ("users", "FRIENDS_IDS", users::FRIENDS_IDS),
+            ("users", "FOLLOWERS_LIST", users::FOLLOWERS_LIST),
+            ("users", "FOLLOWERS_IDS", users::FOLLOWERS_IDS),
+            ("users", "BLOCKS_LIST", users::BLOCKS_LIST),
+            ("users", "BLOCKS_IDS", users::BLOCKS_IDS),
+            ("users", "MUTES_LIST", users::MUTES_LIST),
+            ("users", "MUTES_IDS", users::MUTES_IDS),
+            ("users", "FOLLOW", users::FOLLOW),
+            ("users", "UNFOLLOW", users::UNFOLLOW),
+            ("users", "FRIENDSHIPS_INCOMING", users::FRIENDSHIPS_INCOMING),
+            ("users", "FRIENDSHIPS_OUTGOING", users::FRIENDSHIPS_OUTGOING),
+            ("users", "FRIENDSHIP_SHOW", users::FRIENDSHIP_SHOW),
+            ("users", "FRIENDSHIP_UPDATE", users::FRIENDSHIP_UPDATE),
+            ("users", "FRIENDS_NO_RETWEETS", users::FRIENDS_NO_RETWEETS),
+            ("users", "FRIENDSHIP_LOOKUP", users::FRIENDSHIP_LOOKUP),
+            ("users", "BLOCK", users::BLOCK),
+            ("users", "UNBLOCK", users::UNBLOCK),
+            ("users", "REPORT_SPAM", users::REPORT_SPAM),
+            ("users", "MUTE", users::MUTE),
+            ("users", "UNMUTE", users::UNMUTE),
+            ("users", "UPDATE_PROFILE_BACKGROUND_IMAGE", users::UPDATE_PROFILE_BACKGROUND_IMAGE),
+            ("users", "REMOVE_PROFILE_BANNER", users::REMOVE_PROFILE_BANNER),
+            ("users", "SETTINGS", users::SETTINGS),
+            ("statuses", "SHOW", statuses::SHOW),
+            ("statuses", "RETWEETS_OF_STEM", statuses::RETWEETS_OF_STEM),
+            ("statuses", "LOOKUP", statuses::LOOKUP),
+            ("statuses", "OEMBED", statuses::OEMBED),
+            ("statuses", "HOME_TIMELINE", statuses::HOME_TIMELINE),
+            ("statuses", "MENTIONS_TIMELINE", statuses::MENTIONS_TIMELINE),
+            ("statuses", "USER_TIMELINE", statuses::USER_TIMELINE),
+            ("statuses", "RETWEETS_OF_ME", statuses::RETWEETS_OF_ME),
+            ("statuses", "RETWEETERS_OF", statuses::RETWEETERS_OF),
+            ("statuses", "SEARCH", statuses::SEARCH),
+            ("statuses", "RETWEET_STEM", statuses::RETWEET_STEM),
+            ("statuses", "UNRETWEET_STEM", statuses::UNRETWEET_STEM),
+            ("statuses", "UPDATE", statuses::UPDATE),
+            ("statuses", "DELETE_STEM", statuses::DELETE_STEM),
+            ("favorites", "LIST", favorites::LIST),
+            ("favorites", "CREATE", favorites::CREATE),
+            ("favorites", "DESTROY", favorites::DESTROY),
+            ("media", "UPLOAD", media::UPLOAD),
+            ("media", "METADATA", media::METADATA),
+            ("lists", "STATUSES", lists::STATUSES),
+            ("lists", "MEMBERS", lists::MEMBERS),
+            ("lists", "IS_MEMBER", lists::IS_MEMBER),
+            ("lists", "LIST", lists::LIST),
+            ("lists", "MEMBERSHIPS", lists::MEMBERSHIPS),
+            ("lists", "OWNERSHIPS", lists::OWNERSHIPS),
+            ("lists", "SHOW", lists::SHOW),
+            ("lists", "SUBSCRIBERS", lists::SUBSCRIBERS),
+            ("lists", "IS_SUBSCRIBER", lists::IS_SUBSCRIBER),
+            ("lists", "SUBSCRIPTIONS", lists::SUBSCRIPTIONS),
+            ("lists", "ADD", lists::ADD),
+            ("lists", "REMOVE_MEMBER", lists::REMOVE_MEMBER),
+            ("lists", "CREATE", lists::CREATE),
+            ("lists", "DELETE", lists::DELETE),
+            ("lists", "SUBSCRIBE", lists::SUBSCRIBE),
+            ("lists", "UNSUBSCRIBE", lists::UNSUBSCRIBE),
+            ("lists", "ADD_LIST", lists::ADD_LIST),
+            ("lists", "REMOVE_LIST", lists::REMOVE_LIST),
+            ("lists", "UPDATE", lists::UPDATE),
+            ("place", "SHOW_STEM", place::SHOW_STEM),
+            ("place", "REVERSE_GEOCODE", place::REVERSE_GEOCODE),
+            ("place", "SEARCH", place::SEARCH),
+            ("direct", "SHOW", direct::SHOW),
+            ("direct", "RECEIVED", direct::RECEIVED),
+            ("direct", "SENT", direct::SENT),
+            ("direct", "SEND", direct::SEND),
+            ("direct", "DELETE", direct::DELETE),
+            ("service", "TERMS", service::TERMS),
+            ("service", "PRIVACY", service::PRIVACY),
+            ("service", "CONFIG", service::CONFIG),
+            ("service", "RATE_LIMIT_STATUS", service::RATE_LIMIT_STATUS),
+            ("saved_searches", "LIST", saved_searches::LIST),
+            ("saved_searches", "SHOW_STEM", saved_searches::SHOW_STEM),
+            ("saved_searches", "CREATE", saved_searches::CREATE),
+            ("saved_searches", "DESTROY_STEM", saved_searches::DESTROY_STEM),
+            ("suggestions", "CATEGORIES", suggestions::CATEGORIES),
+            ("suggestions", "SUGGESTIONS_STEM", suggestions::SUGGESTIONS_STEM),
+            ("collections", "SHOW", collections::SHOW),
+            ("collections", "ENTRIES", collections::ENTRIES),
+            ("collections", "LIST", collections::LIST),
+            ("stream", "USER", stream::USER),
+            ("stream", "SAMPLE", stream::SAMPLE),
+            ("stream", "FILTER", stream::FILTER),
+        ]
+    }
+
+    #[test]
+    fn links_are_well_formed() {
+        let mut seen = HashSet::new();
+
+        for (module, name, url) in all_links() {
+            let desc = format!("links::{}::{} ({})", module, name, url);
+
+            assert!(url.starts_with("https://api.twitter.com/") ||
+                    url.starts_with("https://upload.twitter.com/") ||
+                    url.starts_with("https://stream.twitter.com/") ||
+                    url.starts_with("https://userstream.twitter.com/"),
+                    "unexpected host for {}", desc);
+            assert!(seen.insert(url), "duplicate link for {}", desc);
+
+            if name.ends_with("_STEM") {
+                assert!(!url.ends_with(".json") && !url.ends_with('/'),
+                        "stem should leave off the ID and extension: {}", desc);
+            } else if !(module == "auth" && url.contains("/oauth")) {
+                assert!(url.ends_with(".json"), "link should end with .json: {}", desc);
+            }
+
+            if !(module == "auth" && !url.contains("/1.1/")) {
+                assert!(url.contains(".twitter.com/1.1/"),
+                        "link should use version 1.1 of the API: {}", desc);
+            }
+        }
+    }
+}
@@ -9,21 +9,25 @@ use std::collections::HashMap;
 
 use auth;
 use cursor::{CursorIter, UserCursor, ListCursor};
-use error::Error::TwitterError;
 use links;
-use user::{UserID, TwitterUser};
+use hyper::StatusCode;
+use user::UserID;
 use tweet;
 
 ///Look up the lists the given user has been added to.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
+///
+///This includes lists made by anyone, not just the lists the given user owns or subscribes to.
+///For those, see `ownerships`, `subscriptions`, or `list`.
 pub fn memberships<'a, T: Into<UserID<'a>>>(user: T, token: &auth::Token, handle: &Handle)
     -> CursorIter<'a, ListCursor>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &user.into());
     CursorIter::new(links::lists::MEMBERSHIPS, token, handle, Some(params), Some(20))
+        .max_page_size(1000)
 }
 
 ///Return up to 100 lists the given user is subscribed to, including those the user made
@@ -36,7 +40,8 @@ pub fn memberships<'a, T: Into<UserID<'a>>>(user: T, token: &auth::Token, handle
 ///the lists are loaded in the opposite order.
 ///
 ///If the user has more than 100 lists total like this, you'll need to call `ownerships` and
-///`subscriptions` separately to be able to properly load everything.
+///`subscriptions` separately to be able to properly load everything. Unlike those functions, this
+///one isn't cursored, so it loads every list it returns in one network call.
 pub fn list<'id, T: Into<UserID<'id>>>(user: T,
                                        owned_first: bool,
                                        token: &auth::Token,
@@ -55,25 +60,27 @@ pub fn list<'id, T: Into<UserID<'id>>>(user: T,
 ///Look up the lists the given user is subscribed to, but not ones the user made themselves.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
 pub fn subscriptions<'a, T: Into<UserID<'a>>>(user: T, token: &auth::Token, handle: &Handle)
     -> CursorIter<'a, ListCursor>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &user.into());
     CursorIter::new(links::lists::SUBSCRIPTIONS, token, handle, Some(params), Some(20))
+        .max_page_size(1000)
 }
 
 ///Look up the lists created by the given user.
 ///
 ///This function returns a `Stream` over the lists returned by Twitter. This method defaults to
-///reeturning 20 lists in a single network call; the maximum is 1000.
+///returning 20 lists in a single network call; the maximum is 1000.
 pub fn ownerships<'a, T: Into<UserID<'a>>>(user: T, token: &auth::Token, handle: &Handle)
     -> CursorIter<'a, ListCursor>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &user.into());
     CursorIter::new(links::lists::OWNERSHIPS, token, handle, Some(params), Some(20))
+        .max_page_size(1000)
 }
 
 ///Look up information for a single list.
@@ -90,7 +97,7 @@ pub fn show(list: ListID, token: &auth::Token, handle: &Handle) -> FutureRespons
 ///Look up the users that have been added to the given list.
 ///
 ///This function returns a `Stream` over the users returned by Twitter. This method defaults to
-///reeturning 20 users in a single network call; the maximum is 5000.
+///returning 20 users in a single network call; the maximum is 5000.
 pub fn members<'a>(list: ListID<'a>, token: &auth::Token, handle: &Handle)
     -> CursorIter<'a, UserCursor>
 {
@@ -99,12 +106,13 @@ pub fn members<'a>(list: ListID<'a>, token: &auth::Token, handle: &Handle)
     add_list_param(&mut params, &list);
 
     CursorIter::new(links::lists::MEMBERS, token, handle, Some(params), Some(20))
+        .max_page_size(5000)
 }
 
 ///Look up the users that have subscribed to the given list.
 ///
 ///This function returns a `Stream` over the users returned by Twitter. This method defaults to
-///reeturning 20 users in a single network call; the maximum is 5000.
+///returning 20 users in a single network call; the maximum is 5000.
 pub fn subscribers<'a>(list: ListID<'a>, token: &auth::Token, handle: &Handle)
     -> CursorIter<'a, UserCursor>
 {
@@ -113,14 +121,21 @@ pub fn subscribers<'a>(list: ListID<'a>, token: &auth::Token, handle: &Handle)
     add_list_param(&mut params, &list);
 
     CursorIter::new(links::lists::SUBSCRIBERS, token, handle, Some(params), Some(20))
+        .max_page_size(5000)
 }
 
 ///Check whether the given user is subscribed to the given list.
+///
+///If the user isn't subscribed, Twitter returns an error, which this function turns into a `false`
+///result, so the future only returns an error if something else went wrong (e.g. the list
+///doesn't exist). See [`ListCheckFuture`] for details.
+///
+///[`ListCheckFuture`]: struct.ListCheckFuture.html
 pub fn is_subscribed<'id, T: Into<UserID<'id>>>(user: T,
                                                 list: ListID,
                                                 token: &auth::Token,
                                                 handle: &Handle) ->
-    FutureResponse<bool>
+    ListCheckFuture
 {
     let mut params = HashMap::new();
 
@@ -129,35 +144,22 @@ pub fn is_subscribed<'id, T: Into<UserID<'id>>>(user: T,
 
     let req = auth::get(links::lists::IS_SUBSCRIBER, token, Some(&params));
 
-    fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
-        let out: WebResponse<TwitterUser> = make_response(full_resp, headers);
-
-        match out {
-            Ok(user) => Ok(Response::map(user, |_| true)),
-            Err(TwitterError(terrs)) => {
-                if terrs.errors.iter().any(|e| e.code == 109) {
-                    //here's a fun conundrum: since "is not in this list" is returned as an error code,
-                    //the rate limit info that would otherwise be part of the response isn't there. the
-                    //rate_headers method was factored out specifically for this location, since it's
-                    //still there, just accompanying an error response instead of a user.
-                    Ok(Response::map(rate_headers(headers), |_| false))
-                } else {
-                    Err(TwitterError(terrs))
-                }
-            },
-            Err(err) => Err(err),
-        }
-    }
-
-    make_future(handle, req, parse_resp)
+    ListCheckFuture::new(make_future_expecting(handle, req, StatusCode::NotFound,
+                                                parse_list_check))
 }
 
 ///Check whether the given user has been added to the given list.
+///
+///If the user isn't a member, Twitter returns an error, which this function turns into a `false`
+///result, so the future only returns an error if something else went wrong (e.g. the list
+///doesn't exist). See [`ListCheckFuture`] for details.
+///
+///[`ListCheckFuture`]: struct.ListCheckFuture.html
 pub fn is_member<'id, T: Into<UserID<'id>>>(user: T,
                                             list: ListID,
                                             token: &auth::Token,
                                             handle: &Handle) ->
-    FutureResponse<bool>
+    ListCheckFuture
 {
     let mut params = HashMap::new();
 
@@ -166,27 +168,8 @@ pub fn is_member<'id, T: Into<UserID<'id>>>(user: T,
 
     let req = auth::get(links::lists::IS_MEMBER, token, Some(&params));
 
-    fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
-        let out: WebResponse<TwitterUser> = make_response(full_resp, headers);
-
-        match out {
-            Ok(user) => Ok(Response::map(user, |_| true)),
-            Err(TwitterError(terrs)) => {
-                if terrs.errors.iter().any(|e| e.code == 109) {
-                    //here's a fun conundrum: since "is not in this list" is returned as an error code,
-                    //the rate limit info that would otherwise be part of the response isn't there. the
-                    //rate_headers method was factored out specifically for this location, since it's
-                    //still there, just accompanying an error response instead of a user.
-                    Ok(Response::map(rate_headers(headers), |_| false))
-                } else {
-                    Err(TwitterError(terrs))
-                }
-            },
-            Err(err) => Err(err),
-        }
-    }
-
-    make_future(handle, req, parse_resp)
+    ListCheckFuture::new(make_future_expecting(handle, req, StatusCode::NotFound,
+                                                parse_list_check))
 }
 
 ///Begin navigating the collection of tweets made by the users added to the given list.
@@ -314,6 +297,9 @@ pub fn remove_member_list<'a, T, I>(members: I, list: ListID, token: &auth::Toke
 ///The new list is owned by the authenticated user, and its slug can be created with their handle
 ///and the name given to `name`. Twitter places an upper limit on 1000 lists owned by a single
 ///account.
+///
+///If `public` is `false`, the list will be private, so only the authenticated user can see it.
+///Upon success, the future returned by this function yields the freshly-created list.
 pub fn create(name: &str, public: bool, desc: Option<&str>, token: &auth::Token, handle: &Handle)
     -> FutureResponse<List>
 {
@@ -335,7 +321,8 @@ pub fn create(name: &str, public: bool, desc: Option<&str>, token: &auth::Token,
 
 ///Deletes the given list.
 ///
-///The authenticated user must have created the list.
+///The authenticated user must have created the list. Upon success, the future returned by this
+///function yields the list that was just deleted.
 pub fn delete(list: ListID, token: &auth::Token, handle: &Handle) -> FutureResponse<List> {
     let mut params = HashMap::new();
     add_list_param(&mut params, &list);
@@ -41,6 +41,8 @@
 //! - `ListUpdate`: When updating a list's metadata, all the fields that can be updated are
 //!   optional, so the `update` function returns this builder struct so you don't have to provide
 //!   all the parameters if you don't need to.
+//! - `ListCheckFuture`: Returned by `is_member` and `is_subscribed`, to turn Twitter's "not in this
+//!   list" error into a `false` result.
 //!
 //! ## Functions
 //!
@@ -63,7 +65,7 @@
 //!   lists in the `ownerships`/`subscriptions` sets.
 //! - `memberships`
 //! - `members`/`is_member`
-//! - `subscribers`/`is_subscriber`
+//! - `subscribers`/`is_subscribed`
 //! - `show`
 //! - `statuses`
 
@@ -71,6 +73,7 @@ use std::collections::HashMap;
 
 use common::*;
 
+use futures::{Future, Poll};
 use rustc_serialize::json;
 use chrono;
 
@@ -106,7 +109,7 @@ pub use self::fun::*;
 /// let slug = ListID::from_slug("Twitter", "support");
 /// let id = ListID::from_id(99924643);
 /// ```
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum ListID<'a> {
     ///Referring via the list's owner and its "slug" or name.
     Slug(user::UserID<'a>, &'a str),
@@ -207,7 +210,8 @@ impl FromJson for List {
 ///
 /// As updating a list could modify each field independently, this operation is exposed as a builder
 /// struct. To update any field, call the method named after that field, then call `send` to send
-/// the update to Twitter.
+/// the update to Twitter. Only the fields you've set are sent, so anything you don't touch will be
+/// left as-is. Upon success, the future returned by `send` yields the updated list.
 ///
 /// # Example
 ///
@@ -281,3 +285,118 @@ impl<'a> ListUpdate<'a> {
         make_parsed_future(handle, req)
     }
 }
+
+/// `Future` which represents a call to `is_member` or `is_subscribed`.
+///
+/// Twitter answers these calls with the user in question if they're a member of or subscribed to
+/// the list, and with a "not found" error (code 109, `error::codes::NOT_IN_LIST`) if they're not.
+/// This future turns that error into `false`, so it only returns an error if something else went
+/// wrong, like the list not existing. Twitter still sends rate-limit headers with the error, so
+/// the `Response` carries them either way.
+#[must_use = "futures do nothing unless polled"]
+pub struct ListCheckFuture {
+    loader: FutureResponse<bool>,
+}
+
+impl ListCheckFuture {
+    #[doc(hidden)]
+    pub fn new(loader: FutureResponse<bool>) -> ListCheckFuture {
+        ListCheckFuture {
+            loader: loader,
+        }
+    }
+}
+
+impl Future for ListCheckFuture {
+    type Item = Response<bool>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.loader.poll()
+    }
+}
+
+/// Parses the response to `is_member` or `is_subscribed`, turning Twitter's "not in this list"
+/// error into `false`.
+fn parse_list_check(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
+    if let Ok(err) = json::decode::<error::TwitterErrors>(&full_resp) {
+        if err.has_code(error::codes::NOT_IN_LIST) {
+            return Ok(Response::map(rate_headers(headers), |_| false));
+        }
+
+        return Err(error::Error::TwitterError(err));
+    }
+
+    let user: WebResponse<user::TwitterUser> = make_response(full_resp, headers);
+
+    user.map(|user| Response::map(user, |_| true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListID, is_member};
+    use auth;
+    use common::mock;
+    use futures::{Future, Async};
+    use hyper::{Headers, StatusCode};
+    use tokio_core::reactor::Core;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    fn load_file(path: &str) -> String {
+        let mut file = File::open(path).unwrap();
+        let mut ret = String::new();
+        file.read_to_string(&mut ret).unwrap();
+        ret
+    }
+
+    fn rate_limited() -> Headers {
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Limit", "180");
+        headers.set_raw("X-Rate-Limit-Remaining", "179");
+        headers.set_raw("X-Rate-Limit-Reset", "1500000000");
+        headers
+    }
+
+    #[test]
+    fn list_checks() {
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+        let list = ListID::from_id(1);
+
+        mock::respond(&handle, StatusCode::Ok, rate_limited(),
+                      load_file("src/user/sample-user.json"));
+        mock::respond(&handle, StatusCode::NotFound, rate_limited(),
+                      r#"{"errors":[{"code":109,"message":"The specified user is not a member of this list."}]}"#);
+        mock::respond(&handle, StatusCode::NotFound, rate_limited(),
+                      r#"{"errors":[{"code":112,"message":"You must specify either a list ID or a slug and owner."}]}"#);
+
+        match is_member("rustlang", list.clone(), &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => {
+                assert!(resp.response);
+                assert_eq!(resp.rate_limit_remaining, 179);
+            }
+            Ok(Async::NotReady) => panic!("mocked is_member wasn't ready"),
+            Err(e) => panic!("mocked is_member returned an error: {}", e),
+        }
+
+        match is_member("rustlang", list.clone(), &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => {
+                assert!(!resp.response);
+                assert_eq!(resp.rate_limit, 180);
+                assert_eq!(resp.rate_limit_remaining, 179);
+                assert_eq!(resp.rate_limit_reset, 1500000000);
+            }
+            Ok(Async::NotReady) => panic!("mocked is_member wasn't ready"),
+            Err(e) => panic!("mocked is_member returned an error: {}", e),
+        }
+
+        match is_member("rustlang", list, &token, &handle).poll() {
+            Err(ref e) if e.has_twitter_code(112) => (),
+            Err(e) => panic!("mocked is_member returned the wrong error: {}", e),
+            Ok(_) => panic!("mocked is_member didn't return an error"),
+        }
+    }
+}
@@ -447,15 +447,7 @@ impl<'a> UploadFuture<'a> {
 
             let req = auth::post(links::media::UPLOAD, &self.token, Some(&params));
 
-            fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<()>, error::Error> {
-                if full_resp.is_empty() {
-                    Ok(rate_headers(headers))
-                } else {
-                    Err(InvalidResponse("Expected empty response", Some(full_resp)))
-                }
-            }
-
-            Some(make_future(&self.handle, req, parse_resp))
+            Some(make_future(&self.handle, req, parse_empty_response))
         } else {
             None
         }
@@ -495,15 +487,7 @@ impl<'a> UploadFuture<'a> {
 
         let req = auth::post_json(links::media::METADATA, &self.token, &body);
 
-        fn parse_resp(full_resp: String, headers: &Headers) -> Result<Response<()>, error::Error> {
-            if full_resp.is_empty() {
-                Ok(rate_headers(headers))
-            } else {
-                Err(InvalidResponse("Expected empty response", Some(full_resp)))
-            }
-        }
-
-        make_future(&self.handle, req, parse_resp)
+        make_future(&self.handle, req, parse_empty_response)
     }
 }
 
@@ -17,6 +17,11 @@ use super::PlaceQuery;
 
 /// Load the place with the given ID.
 ///
+/// This loads the full details for the place, like the ones given by `reverse_geocode` or the
+/// `search_*` functions: its `attributes`, its `bounding_box`, and the places it sits inside of
+/// (like the state a city is in) in `contained_within`. This is useful to look up more about a
+/// place you got from a search before tagging a tweet with it.
+///
 /// ## Examples
 ///
 /// ```rust,no_run
@@ -456,3 +456,42 @@ impl FromJson for SearchResult {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Place, PlaceType};
+    use common::*;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn parse_place() {
+        let mut file = File::open("src/place/sample-place.json").unwrap();
+        let mut input = String::new();
+        file.read_to_string(&mut input).unwrap();
+
+        let place = Place::from_str(&input).unwrap();
+
+        assert_eq!(place.id, "18810aa5b43e76c7");
+        assert_eq!(place.full_name, "Dallas, TX");
+        assert_eq!(place.country_code, "US");
+        match place.place_type {
+            PlaceType::City => (),
+            other => panic!("unexpected place type: {:?}", other),
+        }
+        assert_eq!(place.attributes.get("geotagCount").map(|s| &**s), Some("6"));
+        assert_eq!(place.bounding_box.len(), 5);
+        assert_eq!(place.bounding_box[0], (-96.977527, 32.620678));
+
+        let parents = place.contained_within.unwrap();
+        assert_eq!(parents.len(), 1);
+        assert_eq!(parents[0].name, "Texas");
+        match parents[0].place_type {
+            PlaceType::Admin => (),
+            other => panic!("unexpected place type: {:?}", other),
+        }
+        assert!(parents[0].attributes.is_empty());
+        assert!(parents[0].contained_within.is_none());
+    }
+}
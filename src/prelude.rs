@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A collection of the most commonly-used items in egg-mode, for glob-importing.
+//!
+//! Most programs that use egg-mode need the same handful of items: a `Token` to authenticate,
+//! `Response` to read the results (and rate-limit information) of their calls, and the `user` and
+//! `tweet` modules to do something with those. Importing this module pulls those in all at once:
+//!
+//! ```rust,no_run
+//! # extern crate egg_mode; extern crate tokio_core;
+//! # use tokio_core::reactor::{Core, Handle};
+//! use egg_mode::prelude::*;
+//!
+//! # fn main() {
+//! # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+//! let rustlang: Response<user::TwitterUser> =
+//!     core.run(user::show(UserID::parse("@rustlang"), &token, &handle)).unwrap();
+//!
+//! let post = tweet::DraftTweet::new(format!("Hello, @{}!", rustlang.screen_name));
+//! core.run(post.send(&token, &handle)).unwrap();
+//! # }
+//! ```
+//!
+//! This is kept deliberately small, so that it doesn't clash with names in your own code. Anything
+//! else can be imported from its own module as usual. Note that this doesn't re-export the
+//! `Future` or `Stream` traits; those still need to be imported from the `futures` crate.
+
+pub use auth::{KeyPair, Token};
+pub use common::{Response, FutureResponse};
+pub use user::{self, UserID};
+pub use tweet::{self, Tweet};
@@ -0,0 +1,117 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and methods for working with the authenticated user's saved searches.
+//!
+//! Twitter lets users save search queries to their account so they can run them again later. The
+//! functions in this module let you load, create, and delete these saved searches. Note that this
+//! only manages the queries themselves; to actually run one, hand its `query` to
+//! [`search::search`].
+//!
+//! [`search::search`]: ../search/fn.search.html
+//!
+//! ```rust,no_run
+//! # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+//! # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+//! # fn main() {
+//! # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+//! use egg_mode::saved_searches;
+//!
+//! let saved = core.run(saved_searches::create("rustlang", &token, &handle)).unwrap();
+//!
+//! for search in &core.run(saved_searches::list(&token, &handle)).unwrap().response {
+//!     println!("{} ({})", search.name, search.id);
+//! }
+//!
+//! core.run(saved_searches::destroy(saved.id, &token, &handle)).unwrap();
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use rustc_serialize::json;
+use chrono;
+
+use auth;
+use error;
+use error::Error::InvalidResponse;
+use links;
+use common::*;
+
+///Represents a search query that has been saved to the authenticated user's account.
+#[derive(Debug, Clone)]
+pub struct SavedSearch {
+    ///Numeric ID for this saved search.
+    pub id: u64,
+    ///UTC timestamp from when this search was saved.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    ///The display name of this saved search. This is usually the same as `query`.
+    pub name: String,
+    ///The search query that was saved.
+    pub query: String,
+}
+
+impl FromJson for SavedSearch {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("SavedSearch received json that wasn't an object",
+                                       Some(input.to_string())));
+        }
+
+        field_present!(input, id);
+        field_present!(input, created_at);
+        field_present!(input, name);
+        field_present!(input, query);
+
+        Ok(SavedSearch {
+            id: try!(field(input, "id")),
+            created_at: try!(field(input, "created_at")),
+            name: try!(field(input, "name")),
+            query: try!(field(input, "query")),
+        })
+    }
+}
+
+///Load the saved searches of the authenticated user.
+pub fn list(token: &auth::Token, handle: &Handle) -> FutureResponse<Vec<SavedSearch>> {
+    let req = auth::get(links::saved_searches::LIST, token, None);
+
+    make_parsed_future(handle, req)
+}
+
+///Load a single saved search by its numeric ID.
+///
+///The authenticated user must be the one who saved the search for this call to be successful.
+pub fn show(id: u64, token: &auth::Token, handle: &Handle) -> FutureResponse<SavedSearch> {
+    let url = format!("{}/{}.json", links::saved_searches::SHOW_STEM, id);
+
+    let req = auth::get(&url, token, None);
+
+    make_parsed_future(handle, req)
+}
+
+///Save the given search query to the authenticated user's account.
+///
+///Upon success, the future returned by this function yields the freshly-saved search.
+pub fn create(query: &str, token: &auth::Token, handle: &Handle) -> FutureResponse<SavedSearch> {
+    let mut params = HashMap::new();
+    add_param(&mut params, "query", query);
+
+    let req = auth::post(links::saved_searches::CREATE, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
+///Delete the saved search with the given ID.
+///
+///The authenticated user must be the one who saved the search for this call to be successful.
+///
+///Upon success, the future returned by this function yields the freshly-deleted search.
+pub fn destroy(id: u64, token: &auth::Token, handle: &Handle) -> FutureResponse<SavedSearch> {
+    let url = format!("{}/{}.json", links::saved_searches::DESTROY_STEM, id);
+
+    let req = auth::post(&url, token, None);
+
+    make_parsed_future(handle, req)
+}
@@ -28,7 +28,9 @@
 //! ```
 //!
 //! Once you have your `SearchResult`, you can navigate the search results by calling `older` and
-//! `newer` to get the next and previous pages, respsectively. In addition, you can see your
+//! `newer` to get the next and previous pages, respsectively. You can also call `next_page`, which
+//! follows the link to the next page that Twitter gives with each page of results, and returns
+//! `None` once there are no more results to load. In addition, you can see your
 //! original query in the search result struct as well, so you can categorize multiple searches by
 //! their query. While this is given as a regular field, note that modifying `query` will not
 //! change what is searched for when you call `older` or `newer`; the `SearchResult` keeps its
@@ -54,6 +56,7 @@ use std::fmt;
 
 use rustc_serialize::json;
 use futures::{Future, Poll, Async};
+use url::form_urlencoded;
 
 use auth;
 use error;
@@ -189,6 +192,7 @@ impl<'a> SearchBuilder<'a> {
         let mut params = HashMap::new();
 
         add_param(&mut params, "q", self.query);
+        add_param(&mut params, "tweet_mode", "extended");
 
         if let Some(lang) = self.lang {
             add_param(&mut params, "lang", lang);
@@ -268,6 +272,10 @@ pub struct SearchResult<'a> {
     pub max_id: u64,
     ///First tweet id in this page of results. This id can be used in `SearchBuilder::since_tweet`
     pub since_id: u64,
+    ///The query string Twitter gave to load the next page of results, like
+    ///`"?max_id=1234&q=rustlang&include_entities=1"`, or `None` if this is the last page. This is
+    ///what `next_page` uses to load that page.
+    pub next_results: Option<String>,
     params: Option<ParamList<'a>>,
 }
 
@@ -284,12 +292,71 @@ impl<'a> FromJson for SearchResult<'a> {
             query: try!(field(metadata, "query")),
             max_id: try!(field(metadata, "max_id")),
             since_id: try!(field(metadata, "since_id")),
+            next_results: try!(field(metadata, "next_results")),
             params: None,
         })
     }
 }
 
 impl<'a> SearchResult<'a> {
+    ///Load the next page of search results, as given by `next_results`, or return `None` if
+    ///there are no more results to load.
+    ///
+    ///The parameters in `next_results` are laid over the ones used to load this page, so any
+    ///settings that Twitter doesn't include in its link (like the extended tweet mode egg-mode asks
+    ///for) are kept for the next page.
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core; extern crate futures;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///let mut page = core.run(egg_mode::search::search("rustlang").call(&token, &handle)).unwrap();
+    ///
+    ///loop {
+    ///    for tweet in &page.statuses {
+    ///        println!("{}", tweet.text);
+    ///    }
+    ///
+    ///    page = match page.next_page(&token, &handle) {
+    ///        Some(next) => core.run(next).unwrap(),
+    ///        None => break,
+    ///    };
+    ///}
+    ///# }
+    ///```
+    pub fn next_page(&self, token: &auth::Token, handle: &Handle) -> Option<SearchFuture<'a>> {
+        let params = match self.next_page_params() {
+            Some(params) => params,
+            None => return None,
+        };
+
+        let req = auth::get(links::statuses::SEARCH, token, Some(&params));
+
+        Some(SearchFuture {
+            loader: make_parsed_future(handle, req),
+            params: Some(params),
+        })
+    }
+
+    ///Combines the parameters used to load this page with the ones given in `next_results`.
+    fn next_page_params(&self) -> Option<ParamList<'a>> {
+        let next = match self.next_results {
+            Some(ref next) => next.trim_left_matches('?'),
+            None => return None,
+        };
+
+        let mut params = self.params.as_ref().cloned().unwrap_or_default();
+        //the link to the next page replaces the bounds of this one
+        params.remove("since_id");
+
+        for (key, val) in form_urlencoded::parse(next.as_bytes()) {
+            add_param(&mut params, key.into_owned(), val.into_owned());
+        }
+
+        Some(params)
+    }
+
     ///Load the next page of search results for the same query.
     pub fn older(&self, token: &auth::Token, handle: &Handle) -> SearchFuture<'a> {
         let mut params = self.params.as_ref().cloned().unwrap_or_default();
@@ -328,3 +395,46 @@ impl<'a> SearchResult<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SearchResult;
+    use common::*;
+
+    fn load_page(next_results: Option<&str>) -> SearchResult<'static> {
+        let next = next_results.map_or("null".to_string(), |next| format!("\"{}\"", next));
+        let input = format!(r#"{{
+            "statuses": [],
+            "search_metadata": {{
+                "query": "rust+lang",
+                "max_id": 250126199840518145,
+                "since_id": 24012619984051000,
+                "next_results": {}
+            }}
+        }}"#, next);
+
+        SearchResult::from_str(&input).unwrap()
+    }
+
+    #[test]
+    fn next_page_params() {
+        let mut page = load_page(Some("?max_id=249279667666817023&q=rust%20lang&count=4"));
+
+        let mut params = ParamList::new();
+        add_param(&mut params, "q", "rust lang");
+        add_param(&mut params, "tweet_mode", "extended");
+        add_param(&mut params, "since_id", "1");
+        page.params = Some(params);
+
+        let next = page.next_page_params().unwrap();
+        assert_eq!(next.get("max_id").map(|v| &**v), Some("249279667666817023"));
+        assert_eq!(next.get("q").map(|v| &**v), Some("rust lang"));
+        assert_eq!(next.get("count").map(|v| &**v), Some("4"));
+        assert_eq!(next.get("tweet_mode").map(|v| &**v), Some("extended"));
+        assert!(next.get("since_id").is_none());
+
+        let last = load_page(None);
+        assert!(last.next_results.is_none());
+        assert!(last.next_page_params().is_none());
+    }
+}
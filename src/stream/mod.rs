@@ -3,15 +3,53 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 //! Access to the Streaming API.
+//!
+//! The Streaming API gives you a long-lived connection to Twitter, which sends you new tweets and
+//! other events as they happen. There are three streams available here: `user`, which mirrors the
+//! authenticated user's home timeline and notifications; `filter`, which gives you the public
+//! tweets matching a set of keywords, accounts, or locations; and `sample`, which gives you a
+//! random sample of all public tweets. `user` and `filter` return a `StreamBuilder` to set up the
+//! stream before you `start` it; `sample` returns a `TwitterStream` directly.
+//!
+//! ## Reconnecting
+//!
+//! A `TwitterStream` ends with an error when its connection is dropped. Twitter expects clients to
+//! wait before reconnecting, and will rate-limit (or ban) clients that reconnect too quickly. Its
+//! guidelines are:
+//!
+//! - For network errors (an `Error::NetError` or `Error::IOError`), back off linearly: start at
+//!   250 milliseconds and add 250 milliseconds for each failed attempt, up to 16 seconds.
+//! - For HTTP errors (an `Error::BadStatus`), back off exponentially: start at 5 seconds and
+//!   double the wait for each failed attempt, up to 320 seconds.
+//! - For an HTTP 420 ("Enhance Your Calm") status, which means you've connected too often, start
+//!   the exponential backoff at a full minute instead.
+//!
+//! Once a connection succeeds, the backoff can be reset. A stream opened with the same parameters
+//! as before will pick up from the current moment; tweets sent while disconnected are not
+//! replayed.
+//!
+//! Rather than doing this yourself, you can hand a `Backoff` to `TwitterStream::reconnect`, and
+//! the stream will wait and reconnect on its own, following the rules above. Each time it does,
+//! it yields a `StreamMessage::Reconnecting` with the error that dropped the connection and how
+//! long it's waiting before reconnecting. If Twitter rejects the stream's credentials (an HTTP 401
+//! status), the stream ends with that error instead, since reconnecting won't fix it.
+//!
+//! Twitter sends a blank line every 30 seconds to keep the connection alive, and recommends that
+//! clients treat a connection as stalled if nothing arrives for 90 seconds. To do this, set an
+//! `idle_timeout` on the stream; once it passes without any data, the stream drops the connection
+//! with an `Error::IOError` of the kind `TimedOut`, which a stream set to `reconnect` will treat
+//! like any other network error.
 
 use std::{self, io};
 use std::collections::HashMap;
+use std::time::Duration;
 
 use chrono;
 use futures::{Future, Stream, Poll, Async};
-use hyper::Body;
-use hyper::client::{Request, FutureResponse};
+use hyper::{Body, StatusCode};
+use hyper::client::Request;
 use rustc_serialize::json;
+use tokio_core::reactor::Timeout;
 
 use auth::{self, Token};
 use direct::DirectMessage;
@@ -115,6 +153,12 @@ pub enum StreamMessage {
         /// The last status ID to scrub information from.
         up_to_status_id: u64,
     },
+    /// Notice that more tweets matched a filter stream's parameters than Twitter could send.
+    ///
+    /// The enclosed value is the total number of matching tweets that have been left out since the
+    /// connection was opened. Twitter caps filter streams to a small fraction of all tweets, so
+    /// broad `track` phrases can easily hit this limit.
+    Limit(u64),
     /// Placeholder message used to indicate that a specific tweet has been withheld in certain
     /// countries.
     StatusWithheld {
@@ -147,6 +191,18 @@ pub enum StreamMessage {
     ///
     /// [stream-doc]: https://developer.twitter.com/en/docs/tweets/filter-realtime/guides/streaming-message-types
     Disconnect(u64, String),
+    /// Notice that the connection to Twitter was dropped, and that the stream will reconnect on
+    /// its own after the given wait.
+    ///
+    /// This is only sent by streams that have been set up to reconnect with
+    /// `TwitterStream::reconnect`. Since the new connection starts from the current moment, any
+    /// messages sent while the stream was disconnected are lost.
+    Reconnecting {
+        /// The error that dropped the connection.
+        error: error::Error,
+        /// How long the stream is waiting before it reconnects.
+        wait: Duration,
+    },
     /// An unhandled message payload.
     ///
     /// Twitter can add new streaming messages to the API, and egg-mode includes them here so that
@@ -243,6 +299,8 @@ impl FromJson for StreamMessage {
                 user_id: try!(field(scrub, "user_id")),
                 up_to_status_id: try!(field(scrub, "up_to_status_id")),
             })
+        } else if let Some(limit) = input.find("limit") {
+            Ok(StreamMessage::Limit(try!(field(limit, "track"))))
         } else if let Some(tweet) = input.find("status_withheld") {
             Ok(StreamMessage::StatusWithheld {
                 status_id: try!(field(tweet, "id")),
@@ -278,35 +336,290 @@ impl FromJson for StreamMessage {
     }
 }
 
+/// The delays to use when reconnecting a `TwitterStream`.
+///
+/// The defaults follow Twitter's guidelines, as described in the [module
+/// documentation][reconnect]. Each kind of error keeps its own count of failed attempts, and both
+/// counts are reset once a connection succeeds.
+///
+/// [reconnect]: index.html#reconnecting
+///
+/// ```rust
+/// use std::time::Duration;
+/// use egg_mode::stream::Backoff;
+///
+/// let backoff = Backoff::new()
+///     .network(Duration::from_millis(500), Duration::from_secs(30))
+///     .http(Duration::from_secs(10), Duration::from_secs(600));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    network_step: Duration,
+    network_max: Duration,
+    http_start: Duration,
+    http_max: Duration,
+    rate_limit_start: Duration,
+    network_attempts: u32,
+    http_attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff {
+            network_step: Duration::from_millis(250),
+            network_max: Duration::from_secs(16),
+            http_start: Duration::from_secs(5),
+            http_max: Duration::from_secs(320),
+            rate_limit_start: Duration::from_secs(60),
+            network_attempts: 0,
+            http_attempts: 0,
+        }
+    }
+}
+
+impl Backoff {
+    /// Creates a new `Backoff` with Twitter's recommended delays.
+    pub fn new() -> Backoff {
+        Backoff::default()
+    }
+
+    /// Sets the delays for network errors. The first reconnect waits for `step`, and each failed
+    /// attempt after that adds another `step` to the wait, up to `max`.
+    pub fn network(self, step: Duration, max: Duration) -> Backoff {
+        Backoff {
+            network_step: step,
+            network_max: max,
+            ..self
+        }
+    }
+
+    /// Sets the delays for HTTP errors. The first reconnect waits for `start`, and each failed
+    /// attempt after that doubles the wait, up to `max`.
+    pub fn http(self, start: Duration, max: Duration) -> Backoff {
+        Backoff {
+            http_start: start,
+            http_max: max,
+            ..self
+        }
+    }
+
+    /// Sets the first delay to use after an HTTP 420 ("Enhance Your Calm") status. As with other
+    /// HTTP errors, each failed attempt after that doubles the wait, up to the maximum given to
+    /// `http`.
+    pub fn rate_limit(self, start: Duration) -> Backoff {
+        Backoff {
+            rate_limit_start: start,
+            ..self
+        }
+    }
+
+    /// Counts a failed connection with the given error, and returns how long to wait before
+    /// reconnecting. Returns `None` if the stream shouldn't reconnect at all, either because the
+    /// error means the stream's credentials were rejected, or because it's not an error with the
+    /// connection.
+    pub fn next_delay(&mut self, err: &error::Error) -> Option<Duration> {
+        match *err {
+            error::Error::BadStatus(StatusCode::Unauthorized) => None,
+            error::Error::BadStatus(status) => {
+                let start = if status.as_u16() == 420 {
+                    self.rate_limit_start
+                } else {
+                    self.http_start
+                };
+
+                let delay = if self.http_attempts < 32 {
+                    start.checked_mul(1 << self.http_attempts)
+                } else {
+                    None
+                };
+                self.http_attempts = self.http_attempts.saturating_add(1);
+
+                Some(delay.map_or(self.http_max, |d| std::cmp::min(d, self.http_max)))
+            }
+            error::Error::NetError(_) | error::Error::IOError(_) |
+            error::Error::FutureAlreadyCompleted => {
+                self.network_attempts = self.network_attempts.saturating_add(1);
+
+                let delay = self.network_step.checked_mul(self.network_attempts);
+
+                Some(delay.map_or(self.network_max, |d| std::cmp::min(d, self.network_max)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Clears the counts of failed attempts, so the next reconnect starts from the shortest wait.
+    pub fn reset(&mut self) {
+        self.network_attempts = 0;
+        self.http_attempts = 0;
+    }
+}
+
+/// The default limit on how large a single message can grow before the stream gives up on it.
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
 /// A `Stream` that represents a connection to the Twitter Streaming API.
 #[must_use = "Streams are lazy and do nothing unless polled"]
 pub struct TwitterStream {
     buf: Vec<u8>,
     handle: Handle,
+    url: &'static str,
+    token: Token,
+    params: ParamList<'static>,
     request: Option<Request>,
-    response: Option<FutureResponse>,
+    response: Option<ResponseFuture>,
     body: Option<Body>,
+    backoff: Option<Backoff>,
+    wait: Option<Timeout>,
+    max_message_size: usize,
+    idle_timeout: Option<Duration>,
+    idle: Option<Timeout>,
+    error: Option<error::Error>,
 }
 
 impl TwitterStream {
-    fn new(handle: &Handle, request: Request) -> TwitterStream {
-        TwitterStream {
+    fn new(handle: &Handle, url: &'static str, token: &Token, params: ParamList<'static>)
+        -> TwitterStream
+    {
+        let mut stream = TwitterStream {
             buf: vec![],
             handle: handle.clone(),
-            request: Some(request),
+            url: url,
+            token: token.clone(),
+            params: params,
+            request: None,
             response: None,
             body: None,
+            backoff: None,
+            wait: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            idle_timeout: None,
+            idle: None,
+            error: None,
+        };
+
+        stream.request = Some(stream.make_request());
+        stream
+    }
+
+    /// Sets this stream to reconnect on its own when its connection is dropped, waiting between
+    /// attempts according to the given `Backoff`.
+    ///
+    /// See the [module documentation][reconnect] for details.
+    ///
+    /// [reconnect]: index.html#reconnecting
+    pub fn reconnect(self, backoff: Backoff) -> TwitterStream {
+        TwitterStream {
+            backoff: Some(backoff),
+            ..self
         }
     }
-}
 
-impl Stream for TwitterStream {
-    type Item = StreamMessage;
-    type Error = error::Error;
+    /// Sets the largest size, in bytes, that a single message can reach before the stream gives up
+    /// on it. By default, this is one megabyte.
+    ///
+    /// If a message grows past this size without ending, the stream drops the connection with an
+    /// `Error::IOError` of the kind `InvalidData`, rather than continuing to buffer it. This keeps
+    /// a broken or malicious connection from using up all your memory.
+    pub fn max_message_size(self, size: usize) -> TwitterStream {
+        TwitterStream {
+            max_message_size: size,
+            ..self
+        }
+    }
 
-    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+    /// Sets how long the stream can go without receiving anything before it considers the
+    /// connection stalled. By default, streams have no idle timeout.
+    ///
+    /// Since Twitter sends a keep-alive every 30 seconds, it recommends a timeout of 90 seconds.
+    /// See the [module documentation][reconnect] for details.
+    ///
+    /// [reconnect]: index.html#reconnecting
+    pub fn idle_timeout(self, timeout: Duration) -> TwitterStream {
+        TwitterStream {
+            idle_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Signs a fresh request for this stream's parameters.
+    fn make_request(&self) -> Request {
+        let params = if self.params.is_empty() { None } else { Some(&self.params) };
+
+        if self.url == links::stream::FILTER {
+            auth::post(self.url, &self.token, params)
+        } else {
+            auth::get(self.url, &self.token, params).request
+        }
+    }
+
+    /// Pulls the next complete message out of the buffer, if one has been fully received.
+    ///
+    /// Twitter separates messages with `\r\n`, but a single message can be split across several
+    /// chunks of the response (and a single chunk can hold several messages), so anything after
+    /// the last separator is left in the buffer until the rest of it arrives.
+    fn take_message(&mut self) -> Option<Result<StreamMessage, error::Error>> {
+        let pos = match self.buf.windows(2).position(|w| w == b"\r\n") {
+            Some(pos) => pos + 2,
+            None => return None,
+        };
+
+        let resp = if let Ok(msg_str) = std::str::from_utf8(&self.buf[..pos]) {
+            StreamMessage::from_str(msg_str)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               "stream did not contain valid UTF-8").into())
+        };
+
+        self.buf.drain(..pos);
+        Some(resp)
+    }
+
+    /// Checks that the partial message in the buffer hasn't grown past `max_message_size`,
+    /// clearing the buffer if it has.
+    fn check_message_size(&mut self) -> Result<(), error::Error> {
+        if self.buf.len() > self.max_message_size {
+            self.buf.clear();
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "stream message exceeded the maximum size").into());
+        }
+
+        Ok(())
+    }
+
+    /// Restarts the idle timer, if this stream has an idle timeout.
+    fn reset_idle(&mut self) -> Result<(), error::Error> {
+        if let Some(timeout) = self.idle_timeout {
+            self.idle = Some(try!(Timeout::new(timeout, &self.handle)));
+        }
+
+        Ok(())
+    }
+
+    /// Polls the idle timer, returning an error if it has gone off. This needs to be called
+    /// before the stream returns `NotReady`, so the timer can wake it up.
+    fn poll_idle(&mut self) -> Result<(), error::Error> {
+        if let Some(mut idle) = self.idle.take() {
+            match idle.poll() {
+                Ok(Async::NotReady) => self.idle = Some(idle),
+                Ok(Async::Ready(())) => {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                              "stream received nothing before its idle timeout")
+                                   .into());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Polls the current connection. Errors with the connection itself are returned as the outer
+    /// `Err`, and errors with a single message are returned inside the `Ready` value.
+    fn poll_connection(&mut self) -> Poll<Result<StreamMessage, error::Error>, error::Error> {
         if let Some(req) = self.request.take() {
             self.response = Some(try!(get_response(&self.handle, req)));
+            try!(self.reset_idle());
         }
 
         if let Some(mut resp) = self.response.take() {
@@ -314,6 +627,7 @@ impl Stream for TwitterStream {
                 Err(e) => return Err(e.into()),
                 Ok(Async::NotReady) => {
                     self.response = Some(resp);
+                    try!(self.poll_idle());
                     return Ok(Async::NotReady);
                 },
                 Ok(Async::Ready(resp)) => {
@@ -323,11 +637,21 @@ impl Stream for TwitterStream {
                         return Err(error::Error::BadStatus(status));
                     }
 
+                    if let Some(ref mut backoff) = self.backoff {
+                        backoff.reset();
+                    }
+
                     self.body = Some(resp.body());
                 },
             }
         }
 
+        if self.body.is_some() {
+            if let Some(msg) = self.take_message() {
+                return Ok(Async::Ready(msg));
+            }
+        }
+
         if let Some(mut body) = self.body.take() {
             loop {
                 match body.poll() {
@@ -337,6 +661,7 @@ impl Stream for TwitterStream {
                     },
                     Ok(Async::NotReady) => {
                         self.body = Some(body);
+                        try!(self.poll_idle());
                         return Ok(Async::NotReady);
                     },
                     Ok(Async::Ready(None)) => {
@@ -345,20 +670,14 @@ impl Stream for TwitterStream {
                     },
                     Ok(Async::Ready(Some(chunk))) => {
                         self.buf.extend(&*chunk);
+                        try!(self.reset_idle());
 
-                        if let Some(pos) = self.buf.windows(2).position(|w| w == b"\r\n") {
+                        if let Some(msg) = self.take_message() {
                             self.body = Some(body);
-                            let pos = pos + 2;
-                            let resp = if let Ok(msg_str) = std::str::from_utf8(&self.buf[..pos]) {
-                                StreamMessage::from_str(msg_str)
-                            } else {
-                                Err(io::Error::new(io::ErrorKind::InvalidData,
-                                                   "stream did not contain valid UTF-8").into())
-                            };
-
-                            self.buf.drain(..pos);
-                            return Ok(Async::Ready(Some(try!(resp))));
+                            return Ok(Async::Ready(msg));
                         }
+
+                        try!(self.check_message_size());
                     },
                 }
             }
@@ -368,6 +687,56 @@ impl Stream for TwitterStream {
     }
 }
 
+impl Stream for TwitterStream {
+    type Item = StreamMessage;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        //errors from the builder are returned before connecting, and skip the reconnect backoff
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        if let Some(mut wait) = self.wait.take() {
+            match wait.poll() {
+                Ok(Async::NotReady) => {
+                    self.wait = Some(wait);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(())) => self.request = Some(self.make_request()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let err = match self.poll_connection() {
+            Ok(Async::Ready(msg)) => return Ok(Async::Ready(Some(try!(msg)))),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Err(err) => err,
+        };
+
+        let delay = match self.backoff {
+            Some(ref mut backoff) => backoff.next_delay(&err),
+            None => None,
+        };
+
+        match delay {
+            Some(delay) => {
+                self.response = None;
+                self.body = None;
+                self.idle = None;
+                self.buf.clear();
+                self.wait = Some(try!(Timeout::new(delay, &self.handle)));
+
+                Ok(Async::Ready(Some(StreamMessage::Reconnecting {
+                    error: err,
+                    wait: delay,
+                })))
+            }
+            None => Err(err),
+        }
+    }
+}
+
 /// Represents the amount of filtering that can be done to streams on Twitter's side.
 ///
 /// According to Twitter's documentation, "When displaying a stream of Tweets to end users
@@ -412,12 +781,74 @@ impl FromJson for FilterLevel {
     }
 }
 
+/// A rectangular area on the map, used to filter a stream by location with
+/// `StreamBuilder::locations`.
+///
+/// Each corner is a `(longitude, latitude)` pair, in that order, the same way Twitter expects
+/// them. Longitudes must be between -180 and 180, latitudes must be between -90 and 90, and the
+/// southwest corner must be south and west of the northeast corner. (This means a box can't cross
+/// the 180th meridian; split it into two boxes instead.)
+///
+/// ```rust
+/// use egg_mode::stream::BoundingBox;
+///
+/// // San Francisco
+/// let sf = BoundingBox::new((-122.75, 36.8), (-121.75, 37.8));
+/// assert!(sf.is_valid());
+///
+/// // latitude and longitude swapped
+/// let oops = BoundingBox::new((36.8, -122.75), (37.8, -121.75));
+/// assert!(!oops.is_valid());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// The southwest corner of the box, as a `(longitude, latitude)` pair.
+    pub sw: (f64, f64),
+    /// The northeast corner of the box, as a `(longitude, latitude)` pair.
+    pub ne: (f64, f64),
+}
+
+impl BoundingBox {
+    /// Creates a new `BoundingBox` with the given southwest and northeast corners, each given as a
+    /// `(longitude, latitude)` pair.
+    pub fn new(sw: (f64, f64), ne: (f64, f64)) -> BoundingBox {
+        BoundingBox {
+            sw: sw,
+            ne: ne,
+        }
+    }
+
+    /// Returns whether this box can be sent to Twitter: both corners are real coordinates, and
+    /// the southwest corner is actually south and west of the northeast one.
+    pub fn is_valid(&self) -> bool {
+        fn valid_point(&(lon, lat): &(f64, f64)) -> bool {
+            lon >= -180.0 && lon <= 180.0 && lat >= -90.0 && lat <= 90.0
+        }
+
+        valid_point(&self.sw) && valid_point(&self.ne) &&
+            self.sw.0 <= self.ne.0 && self.sw.1 <= self.ne.1
+    }
+}
+
+/// `Display` impl to turn a `BoundingBox` into the form needed for the `locations` parameter:
+/// the southwest longitude and latitude, then the northeast longitude and latitude, separated by
+/// commas.
+impl ::std::fmt::Display for BoundingBox {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "{},{},{},{}", self.sw.0, self.sw.1, self.ne.0, self.ne.1)
+    }
+}
+
 /// Represents a `TwitterStream` before it is started.
 pub struct StreamBuilder {
     url: &'static str,
     with_follows: Option<bool>,
     all_replies: bool,
     filter_level: Option<FilterLevel>,
+    track: Vec<String>,
+    follow: Vec<u64>,
+    locations: Vec<BoundingBox>,
+    invalid_location: bool,
 }
 
 impl StreamBuilder {
@@ -427,9 +858,58 @@ impl StreamBuilder {
             with_follows: None,
             all_replies: false,
             filter_level: None,
+            track: vec![],
+            follow: vec![],
+            locations: vec![],
+            invalid_location: false,
         }
     }
 
+    /// For Filter Streams, adds the given keywords to the list of phrases to track.
+    ///
+    /// Each phrase can be one or more words separated by spaces. A tweet matches a phrase if it
+    /// contains all of its words, in any order and regardless of case. A tweet will be included in
+    /// the stream if it matches any of the tracked phrases.
+    ///
+    /// Twitter allows up to 400 tracked phrases per stream, each up to 60 bytes long.
+    pub fn track(mut self, keywords: &[&str]) -> StreamBuilder {
+        self.track.extend(keywords.iter().map(|k| k.to_string()));
+        self
+    }
+
+    /// For Filter Streams, adds the given user IDs to the list of accounts to follow.
+    ///
+    /// The stream will include tweets created by these accounts, retweets of their tweets, and
+    /// replies to their tweets. Twitter allows up to 5,000 followed accounts per stream.
+    pub fn follow(mut self, users: &[u64]) -> StreamBuilder {
+        self.follow.extend(users);
+        self
+    }
+
+    /// For Filter Streams, adds the given bounding boxes to the list of locations to watch.
+    ///
+    /// The stream will include tweets whose location falls inside any of the given boxes. See
+    /// [`BoundingBox`] for how to describe an area. Twitter allows up to 25 boxes per stream.
+    ///
+    /// [`BoundingBox`]: struct.BoundingBox.html
+    ///
+    /// Note that Twitter treats locations separately from `track` and `follow`: a tweet will be
+    /// included if it matches *any* of the filters, not all of them.
+    ///
+    /// If any of the given boxes aren't valid, according to `BoundingBox::is_valid`, the stream
+    /// returned by `start` will fail with `Error::InvalidArgument` the first time it's polled,
+    /// without connecting. Twitter doesn't reject a stream with a malformed box, it just never
+    /// matches anything, so this catches the mistake up front instead of leaving a stream that
+    /// silently stays quiet.
+    pub fn locations(mut self, boxes: &[BoundingBox]) -> StreamBuilder {
+        if boxes.iter().any(|bbox| !bbox.is_valid()) {
+            self.invalid_location = true;
+        }
+
+        self.locations.extend(boxes);
+        self
+    }
+
     /// For User Streams, sets whether to include posts from just the authenticated user or from
     /// the accounts they follow as well.
     ///
@@ -470,7 +950,18 @@ impl StreamBuilder {
     }
 
     /// Finalizes the stream parameters and returns the resulting `TwitterStream`.
+    ///
+    /// If any of the boxes given to `locations` weren't valid, the returned stream will fail with
+    /// `Error::InvalidArgument` the first time it's polled.
     pub fn start(self, handle: &Handle, token: &Token) -> TwitterStream {
+        if self.invalid_location {
+            let mut stream = TwitterStream::new(handle, self.url, token, HashMap::new());
+            stream.request = None;
+            stream.error = Some(error::Error::InvalidArgument("locations",
+                "bounding box has out-of-range or out-of-order corners"));
+            return stream;
+        }
+
         let mut params = HashMap::new();
 
         if let Some(with_follows) = self.with_follows {
@@ -489,13 +980,21 @@ impl StreamBuilder {
             add_param(&mut params, "filter_level", filter_level.to_string());
         }
 
-        let req = if self.url == links::stream::USER {
-            auth::get(self.url, token, Some(&params))
-        } else {
-            auth::post(self.url, token, Some(&params))
-        };
+        if !self.track.is_empty() {
+            add_param(&mut params, "track", self.track.join(","));
+        }
+
+        if !self.follow.is_empty() {
+            let ids = self.follow.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+            add_param(&mut params, "follow", ids.join(","));
+        }
 
-        TwitterStream::new(handle, req)
+        if !self.locations.is_empty() {
+            let coords = self.locations.iter().map(|b| b.to_string()).collect::<Vec<_>>();
+            add_param(&mut params, "locations", coords.join(","));
+        }
+
+        TwitterStream::new(handle, self.url, token, params)
     }
 }
 
@@ -505,12 +1004,44 @@ pub fn user() -> StreamBuilder {
 }
 
 /// Begins building a request to a filtered public stream.
+///
+/// Twitter requires filter streams to have at least one of `track`, `follow`, or `locations` set
+/// before they can be started.
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use futures::Stream;
+/// use egg_mode::stream::StreamMessage;
+///
+/// let stream = egg_mode::stream::filter()
+///     .track(&["rustlang", "rust programming"])
+///     .follow(&[165262228])
+///     .start(&handle, &token)
+///     .for_each(|msg| {
+///         if let StreamMessage::Tweet(tweet) = msg {
+///             println!("{}", tweet.text);
+///         }
+///         Ok(())
+///     });
+///
+/// core.run(stream).unwrap();
+/// # }
+/// ```
 pub fn filter() -> StreamBuilder {
     StreamBuilder::new(links::stream::FILTER)
 }
 
 /// Opens a `TwitterStream` returning "a small random sample of all public statuses".
 ///
+/// This connects to the `statuses/sample` endpoint, and gives a roughly 1% sample of all public
+/// tweets. If the connection drops, see the [module documentation][reconnect] for how long to wait
+/// before starting a new one.
+///
+/// [reconnect]: index.html#reconnecting
+///
 /// As sample streams don't have the same configuration options as user streams or filter streams,
 /// this directly returns a `TwitterStream`, rather than going through a [`StreamBuilder`]. To apply
 /// filter options on the public stream, start with [`filter`] and add parameters to the
@@ -519,7 +1050,218 @@ pub fn filter() -> StreamBuilder {
 /// [`StreamBuilder`]: struct.StreamBuilder.html
 /// [`filter`]: fn.filter.html
 pub fn sample(handle: &Handle, token: &Token) -> TwitterStream {
-    let req = auth::get(links::stream::SAMPLE, token, None);
+    TwitterStream::new(handle, links::stream::SAMPLE, token, HashMap::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, TwitterStream, StreamMessage};
+
+    use std::collections::HashMap;
+    use std::io;
+    use std::time::Duration;
+
+    use hyper::StatusCode;
+    use tokio_core::reactor::Core;
+
+    use auth::{KeyPair, Token};
+    use error::Error;
+    use links;
+
+    #[test]
+    fn split_messages() {
+        let core = Core::new().unwrap();
+        let token = Token::Bearer("bearer".to_string());
+        let mut stream = TwitterStream::new(&core.handle(), links::stream::SAMPLE, &token,
+                                            HashMap::new());
+
+        stream.buf.extend(b"\r\n{\"friends\":[1,2]}\r\n{\"friends\"");
 
-    TwitterStream::new(handle, req)
+        match stream.take_message() {
+            Some(Ok(StreamMessage::Ping)) => (),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match stream.take_message() {
+            Some(Ok(StreamMessage::FriendList(ids))) => assert_eq!(ids, vec![1, 2]),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        assert!(stream.take_message().is_none());
+
+        stream.buf.extend(b":[3]}\r\n");
+
+        match stream.take_message() {
+            Some(Ok(StreamMessage::FriendList(ids))) => assert_eq!(ids, vec![3]),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        assert!(stream.buf.is_empty());
+    }
+
+    #[test]
+    fn message_size_limit() {
+        let core = Core::new().unwrap();
+        let token = Token::Bearer("bearer".to_string());
+        let mut stream = TwitterStream::new(&core.handle(), links::stream::SAMPLE, &token,
+                                            HashMap::new())
+            .max_message_size(16);
+
+        stream.buf.extend(b"{\"friends\":[1,2]");
+        assert!(stream.check_message_size().is_ok());
+
+        stream.buf.extend(b",3,4,5]}");
+        match stream.check_message_size() {
+            Err(Error::IOError(ref err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(stream.buf.is_empty());
+    }
+
+    #[test]
+    fn backoff_delays() {
+        let mut backoff = Backoff::new();
+        let net = || Error::IOError(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+
+        assert_eq!(backoff.next_delay(&net()), Some(Duration::from_millis(250)));
+        assert_eq!(backoff.next_delay(&net()), Some(Duration::from_millis(500)));
+        for _ in 0..100 {
+            backoff.next_delay(&net());
+        }
+        assert_eq!(backoff.next_delay(&net()), Some(Duration::from_secs(16)));
+
+        let unavailable = Error::BadStatus(StatusCode::ServiceUnavailable);
+        assert_eq!(backoff.next_delay(&unavailable), Some(Duration::from_secs(5)));
+        assert_eq!(backoff.next_delay(&unavailable), Some(Duration::from_secs(10)));
+        for _ in 0..100 {
+            backoff.next_delay(&unavailable);
+        }
+        assert_eq!(backoff.next_delay(&unavailable), Some(Duration::from_secs(320)));
+
+        backoff.reset();
+        let calm = Error::BadStatus(StatusCode::Unregistered(420));
+        assert_eq!(backoff.next_delay(&calm), Some(Duration::from_secs(60)));
+        assert_eq!(backoff.next_delay(&calm), Some(Duration::from_secs(120)));
+        assert_eq!(backoff.next_delay(&net()), Some(Duration::from_millis(250)));
+
+        assert_eq!(backoff.next_delay(&Error::BadStatus(StatusCode::Unauthorized)), None);
+        assert_eq!(backoff.next_delay(&Error::MissingValue("id")), None);
+    }
+
+    #[test]
+    fn custom_backoff() {
+        let mut backoff = Backoff::new().network(Duration::from_secs(1), Duration::from_secs(2))
+                                        .http(Duration::from_secs(1), Duration::from_secs(3));
+        let net = Error::FutureAlreadyCompleted;
+        let http = Error::BadStatus(StatusCode::InternalServerError);
+
+        assert_eq!(backoff.next_delay(&net), Some(Duration::from_secs(1)));
+        assert_eq!(backoff.next_delay(&net), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.next_delay(&net), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.next_delay(&http), Some(Duration::from_secs(1)));
+        assert_eq!(backoff.next_delay(&http), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.next_delay(&http), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn access_streams_post_filters() {
+        let core = Core::new().unwrap();
+        let token = Token::Access {
+            consumer: KeyPair::new("consumer key", "consumer secret"),
+            access: KeyPair::new("access key", "access secret"),
+        };
+
+        let stream = super::filter().track(&["rustlang"]).start(&core.handle(), &token);
+        assert_eq!(*stream.make_request().method(), ::hyper::Method::Post);
+
+        let stream = super::user().start(&core.handle(), &token);
+        assert_eq!(*stream.make_request().method(), ::hyper::Method::Get);
+    }
+
+    #[test]
+    fn filter_locations() {
+        use super::BoundingBox;
+
+        let core = Core::new().unwrap();
+        let token = Token::Bearer("token".to_string());
+
+        let sf = BoundingBox::new((-122.75, 36.8), (-121.75, 37.8));
+        let nyc = BoundingBox::new((-74.0, 40.0), (-73.0, 41.0));
+        let stream = super::filter().locations(&[sf, nyc]).start(&core.handle(), &token);
+        assert_eq!(stream.params.get("locations").map(|l| &**l),
+                   Some("-122.75,36.8,-121.75,37.8,-74,40,-73,41"));
+
+        assert!(!BoundingBox::new((-190.0, 36.8), (-121.75, 37.8)).is_valid());
+        assert!(!BoundingBox::new((-122.75, 36.8), (-121.75, 95.0)).is_valid());
+        assert!(!BoundingBox::new((-121.75, 37.8), (-122.75, 36.8)).is_valid());
+        assert!(!BoundingBox::new((::std::f64::NAN, 36.8), (-121.75, 37.8)).is_valid());
+    }
+
+    #[test]
+    fn filter_bad_locations() {
+        use futures::Stream;
+
+        let core = Core::new().unwrap();
+        let token = Token::Bearer("token".to_string());
+
+        let sf = super::BoundingBox::new((-122.75, 36.8), (-121.75, 37.8));
+        let swapped = super::BoundingBox::new((36.8, -122.75), (37.8, -121.75));
+        let mut stream = super::filter().locations(&[sf, swapped]).start(&core.handle(), &token);
+
+        match stream.poll() {
+            Err(Error::InvalidArgument("locations", _)) => (),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("stream with a bad bounding box didn't fail"),
+        }
+
+        assert!(stream.poll().is_err());
+    }
+
+    #[test]
+    fn control_messages() {
+        use common::FromJson;
+
+        match StreamMessage::from_str(r#"{"delete":{"status":{"id":1234,"id_str":"1234","user_id":3,"user_id_str":"3"}}}"#).unwrap() {
+            StreamMessage::Delete { status_id, user_id } => {
+                assert_eq!(status_id, 1234);
+                assert_eq!(user_id, 3);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match StreamMessage::from_str(r#"{"scrub_geo":{"user_id":14090452,"user_id_str":"14090452","up_to_status_id":23260136625,"up_to_status_id_str":"23260136625"}}"#).unwrap() {
+            StreamMessage::ScrubGeo { user_id, up_to_status_id } => {
+                assert_eq!(user_id, 14090452);
+                assert_eq!(up_to_status_id, 23260136625);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match StreamMessage::from_str(r#"{"limit":{"track":1234,"timestamp_ms":"1519567716058"}}"#).unwrap() {
+            StreamMessage::Limit(skipped) => assert_eq!(skipped, 1234),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match StreamMessage::from_str(r#"{"status_withheld":{"id":1234567890,"user_id":123456,"withheld_in_countries":["DE","AR"]}}"#).unwrap() {
+            StreamMessage::StatusWithheld { status_id, user_id, withheld_in_countries } => {
+                assert_eq!(status_id, 1234567890);
+                assert_eq!(user_id, 123456);
+                assert_eq!(withheld_in_countries, vec!["DE", "AR"]);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match StreamMessage::from_str(r#"{"disconnect":{"code":4,"stream_name":"< A stream identifier >","reason":"duplicate stream"}}"#).unwrap() {
+            StreamMessage::Disconnect(code, reason) => {
+                assert_eq!(code, 4);
+                assert_eq!(reason, "duplicate stream");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        match StreamMessage::from_str(r#"{"brand_new_message":{"id":1}}"#).unwrap() {
+            StreamMessage::Unknown(json) => assert!(json.find("brand_new_message").is_some()),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
 }
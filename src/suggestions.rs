@@ -0,0 +1,159 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structs and methods for loading the accounts Twitter suggests to new users.
+//!
+//! Twitter keeps a curated set of accounts to suggest following, sorted into categories like
+//! "Sports" or "Music". The functions in this module let you load the list of categories, and the
+//! accounts within a given category. Each category is referred to by its `slug`, which is given
+//! alongside its name by `categories`.
+//!
+//! ```rust,no_run
+//! # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+//! # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+//! # fn main() {
+//! # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+//! use egg_mode::suggestions;
+//!
+//! let categories = core.run(suggestions::categories(None, &token, &handle)).unwrap();
+//!
+//! for category in &categories.response {
+//!     let users = core.run(suggestions::suggestions(&category.slug, None, &token, &handle))
+//!                     .unwrap();
+//!
+//!     println!("{} ({} accounts):", users.name, users.size);
+//!     for user in &users.users {
+//!         println!("    {} (@{})", user.name, user.screen_name);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use rustc_serialize::json;
+
+use auth;
+use error;
+use error::Error::InvalidResponse;
+use links;
+use user::TwitterUser;
+use common::*;
+
+///Represents a category of suggested accounts, as returned by `categories`.
+#[derive(Debug, Clone)]
+pub struct Category {
+    ///The display name of this category.
+    pub name: String,
+    ///The name used to refer to this category when calling `suggestions` or `members`.
+    pub slug: String,
+    ///The number of accounts in this category.
+    pub size: i32,
+}
+
+impl FromJson for Category {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("Category received json that wasn't an object",
+                                       Some(input.to_string())));
+        }
+
+        field_present!(input, name);
+        field_present!(input, slug);
+        field_present!(input, size);
+
+        Ok(Category {
+            name: try!(field(input, "name")),
+            slug: try!(field(input, "slug")),
+            size: try!(field(input, "size")),
+        })
+    }
+}
+
+///Represents a category of suggested accounts along with the accounts themselves, as returned by
+///`suggestions`.
+#[derive(Debug, Clone)]
+pub struct Suggestions {
+    ///The display name of this category.
+    pub name: String,
+    ///The name used to refer to this category when calling `suggestions` or `members`.
+    pub slug: String,
+    ///The number of accounts in this category.
+    pub size: i32,
+    ///The accounts in this category.
+    pub users: Vec<TwitterUser>,
+}
+
+impl FromJson for Suggestions {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("Suggestions received json that wasn't an object",
+                                       Some(input.to_string())));
+        }
+
+        field_present!(input, name);
+        field_present!(input, slug);
+        field_present!(input, size);
+        field_present!(input, users);
+
+        Ok(Suggestions {
+            name: try!(field(input, "name")),
+            slug: try!(field(input, "slug")),
+            size: try!(field(input, "size")),
+            users: try!(field(input, "users")),
+        })
+    }
+}
+
+///Load the list of categories of suggested accounts.
+///
+///If `lang` is given, Twitter will return the categories (and their names) for that language,
+///given as an ISO 639-1 code like `"en"` or `"es"`.
+pub fn categories(lang: Option<&str>, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<Vec<Category>>
+{
+    let mut params = HashMap::new();
+
+    if let Some(lang) = lang {
+        add_param(&mut params, "lang", lang);
+    }
+
+    let req = auth::get(links::suggestions::CATEGORIES, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
+///Load the suggested accounts in the given category.
+///
+///If `lang` is given, Twitter will return the category name for that language, given as an ISO
+///639-1 code like `"en"` or `"es"`.
+pub fn suggestions(slug: &str, lang: Option<&str>, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<Suggestions>
+{
+    let url = format!("{}/{}.json", links::suggestions::SUGGESTIONS_STEM, slug);
+
+    let mut params = HashMap::new();
+
+    if let Some(lang) = lang {
+        add_param(&mut params, "lang", lang);
+    }
+
+    let req = auth::get(&url, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
+///Load the suggested accounts in the given category, along with their most recent tweet.
+///
+///This returns the same accounts as `suggestions`, but without the category information, and
+///with the `status` field of each user filled in.
+pub fn members(slug: &str, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<Vec<TwitterUser>>
+{
+    let url = format!("{}/{}/members.json", links::suggestions::SUGGESTIONS_STEM, slug);
+
+    let req = auth::get(&url, token, None);
+
+    make_parsed_future(handle, req)
+}
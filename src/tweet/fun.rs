@@ -2,13 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
-use rustc_serialize::json;
+use mime;
 use auth;
 use cursor;
 use user::UserID;
-use error::Error::InvalidResponse;
 use links;
+use media;
 use common::*;
 
 use super::*;
@@ -16,21 +17,83 @@ use super::*;
 ///Lookup a single tweet by numeric ID.
 pub fn show(id: u64, token: &auth::Token, handle: &Handle)
     -> FutureResponse<Tweet>
+{
+    show_with(id, false, token, handle)
+}
+
+///Lookup a single tweet by numeric ID, optionally leaving out the full profile of its author.
+///
+///If `trim_user` is `true`, Twitter will only send the ID of the tweet's author, so the returned
+///tweet will have its `user` field set to `None` and its `user_id` filled in. With `trim_user` set
+///to `false`, this is the same as `show`.
+pub fn show_with(id: u64, trim_user: bool, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<Tweet>
 {
     let mut params = HashMap::new();
     add_param(&mut params, "id", id.to_string());
     add_param(&mut params, "include_my_retweet", "true");
     add_param(&mut params, "tweet_mode", "extended");
+    add_param(&mut params, "trim_user", trim_user.to_string());
 
     let req = auth::get(links::statuses::SHOW, token, Some(&params));
 
     make_parsed_future(handle, req)
 }
 
+///Load the HTML needed to embed the given tweet on a web page.
+///
+///Use the `options` parameter to change how the embedded tweet looks; `OEmbedOptions::default()`
+///will keep Twitter's default settings. If you have the tweet's URL instead of its ID, use
+///`oembed_url`.
+///
+///```rust,no_run
+///# extern crate egg_mode; extern crate tokio_core;
+///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+///# fn main() {
+///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+///use egg_mode::tweet::{self, OEmbedOptions};
+///
+///let opts = OEmbedOptions::new().hide_thread(true).omit_script(true);
+///let embed = core.run(tweet::oembed(782349500404862976, opts, &token, &handle)).unwrap();
+///
+///println!("{}", embed.html);
+///# }
+///```
+pub fn oembed(id: u64, options: OEmbedOptions, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<OEmbed>
+{
+    let mut params = HashMap::new();
+    add_param(&mut params, "id", id.to_string());
+    options.add_params(&mut params);
+
+    let req = auth::get(links::statuses::OEMBED, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
+///Load the HTML needed to embed the tweet at the given URL on a web page.
+///
+///This is the same as `oembed`, but takes the tweet's URL (like
+///`https://twitter.com/rustlang/status/782349500404862976`) instead of its ID.
+pub fn oembed_url(url: &str, options: OEmbedOptions, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<OEmbed>
+{
+    let mut params = HashMap::new();
+    add_param(&mut params, "url", url);
+    options.add_params(&mut params);
+
+    let req = auth::get(links::statuses::OEMBED, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
 ///Lookup the most recent 100 (or fewer) retweets of the given tweet.
 ///
 ///Use the `count` parameter to indicate how many retweets you would like to retrieve. If `count`
 ///is 0 or greater than 100, it will be defaulted to 100 before making the call.
+///
+///If you only need to know who retweeted the tweet, `retweeters_of` will load just the user IDs,
+///and can page through more than 100 of them.
 pub fn retweets_of(id: u64, count: u32, token: &auth::Token, handle: &Handle)
     -> FutureResponse<Vec<Tweet>>
 {
@@ -56,6 +119,9 @@ pub fn retweets_of(id: u64, count: u32, token: &auth::Token, handle: &Handle)
 ///set the page size. Calling `with_page_size` on the iterator returned by this function will not
 ///change the page size used by the network call. Setting `page_size` manually may result in an
 ///error from Twitter.
+///
+///The IDs returned here can be loaded into full user profiles with `user::lookup`. To get the
+///retweets themselves, see `retweets_of`.
 pub fn retweeters_of(id: u64, token: &auth::Token, handle: &Handle)
     -> cursor::CursorIter<'static, cursor::IDCursor>
 {
@@ -68,21 +134,42 @@ pub fn retweeters_of(id: u64, token: &auth::Token, handle: &Handle)
 ///
 ///This function differs from `lookup_map` in how it handles protected or nonexistent tweets.
 ///`lookup` gives a Vec of just the tweets it could load, leaving out any that it couldn't find.
+///
+///Twitter can only load 100 tweets per call, so if more IDs than that are given, they will be split
+///into several calls, and the results will be combined once they all finish. See [`BatchFuture`]
+///for which call's rate-limit information ends up in the final `Response`, and for what happens if
+///one of the calls fails. IDs that are given more than once are only sent to Twitter once.
+///
+///[`BatchFuture`]: ../struct.BatchFuture.html
 pub fn lookup<I: IntoIterator<Item=u64>>(ids: I, token: &auth::Token, handle: &Handle)
-    -> FutureResponse<Vec<Tweet>>
+    -> BatchFuture<Vec<Tweet>>
 {
-    let mut params = HashMap::new();
-    let id_param = ids.into_iter().fold(String::new(), |mut acc, x| {
-        if !acc.is_empty() { acc.push(','); }
-        acc.push_str(&x.to_string());
-        acc
-    });
-    add_param(&mut params, "id", id_param);
-    add_param(&mut params, "tweet_mode", "extended");
+    lookup_with(ids, false, token, handle)
+}
 
-    let req = auth::post(links::statuses::LOOKUP, token, Some(&params));
+///Lookup tweet information for the given list of tweet IDs, optionally leaving out the full
+///profile of each tweet's author.
+///
+///If `trim_user` is `true`, Twitter will only send the ID of each tweet's author, so the returned
+///tweets will have their `user` field set to `None` and their `user_id` filled in. With
+///`trim_user` set to `false`, this is the same as `lookup`.
+pub fn lookup_with<I: IntoIterator<Item=u64>>(ids: I, trim_user: bool, token: &auth::Token,
+                                              handle: &Handle)
+    -> BatchFuture<Vec<Tweet>>
+{
+    let ids = dedup_in_order(ids);
+    let loaders = ids.chunks(100).map(|chunk| {
+        let mut params = HashMap::new();
+        add_param(&mut params, "id", join_ids(chunk));
+        add_param(&mut params, "tweet_mode", "extended");
+        add_param(&mut params, "trim_user", trim_user.to_string());
 
-    make_parsed_future(handle, req)
+        let req = auth::get_or_post(links::statuses::LOOKUP, token, Some(&params));
+
+        make_parsed_future(handle, req)
+    }).collect();
+
+    make_batch_future(loaders)
 }
 
 ///Lookup tweet information for the given list of tweet IDs, and return a map indicating which IDs
@@ -92,46 +179,48 @@ pub fn lookup<I: IntoIterator<Item=u64>>(ids: I, token: &auth::Token, handle: &H
 ///`lookup_map` gives a map containing every ID in the input slice; tweets that don't exist or
 ///can't be read by the authenticated user store `None` in the map, whereas tweets that could be
 ///loaded store `Some` and the requested status.
+///
+///Note that Twitter doesn't say *why* a tweet couldn't be loaded: tweets that were deleted, that
+///come from a protected account the authenticated user doesn't follow, or that are withheld in
+///the user's country all come back the same way, so they all store `None`. If you need to tell
+///these apart, try loading the missing tweets with `show`, which returns an error explaining why
+///(see the `error::codes` module).
+///
+///As with `lookup`, if more than 100 IDs are given, they will be split into several calls, and
+///the results will be combined into one map once they all finish, as described in
+///[`BatchFuture`].
+///
+///[`BatchFuture`]: ../struct.BatchFuture.html
 pub fn lookup_map<I: IntoIterator<Item=u64>>(ids: I, token: &auth::Token, handle: &Handle)
-    -> FutureResponse<HashMap<u64, Option<Tweet>>>
+    -> BatchFuture<HashMap<u64, Option<Tweet>>>
 {
-    let mut params = HashMap::new();
-    let id_param = ids.into_iter().fold(String::new(), |mut acc, x| {
-        if !acc.is_empty() { acc.push(','); }
-        acc.push_str(&x.to_string());
-        acc
-    });
-    add_param(&mut params, "id", id_param);
-    add_param(&mut params, "map", "true");
-    add_param(&mut params, "tweet_mode", "extended");
+    lookup_map_with(ids, false, token, handle)
+}
 
-    let req = auth::post(links::statuses::LOOKUP, token, Some(&params));
-
-    fn parse_map(full_resp: String, headers: &Headers)
-        -> Result<Response<HashMap<u64, Option<Tweet>>>, error::Error>
-    {
-        let parsed: Response<json::Json> = try!(make_response(full_resp, headers));
-        let mut map = HashMap::new();
-
-        for (key, val) in try!(parsed.response
-                                     .find("id")
-                                     .and_then(|v| v.as_object())
-                                     .ok_or_else(|| InvalidResponse("unexpected response for lookup_map",
-                                                                    Some(parsed.response.to_string())))) {
-            let id = try!(key.parse::<u64>().or(Err(InvalidResponse("could not parse id as integer",
-                                                                    Some(key.to_string())))));
-            if val.is_null() {
-                map.insert(id, None);
-            } else {
-                let tweet = try!(Tweet::from_json(&val));
-                map.insert(id, Some(tweet));
-            }
-        }
-
-        Ok(Response::map(parsed, |_| map))
-    }
+///Lookup tweet information for the given list of tweet IDs, optionally leaving out the full
+///profile of each tweet's author, and return a map indicating which IDs couldn't be found.
+///
+///If `trim_user` is `true`, Twitter will only send the ID of each tweet's author, so the returned
+///tweets will have their `user` field set to `None` and their `user_id` filled in. With
+///`trim_user` set to `false`, this is the same as `lookup_map`.
+pub fn lookup_map_with<I: IntoIterator<Item=u64>>(ids: I, trim_user: bool, token: &auth::Token,
+                                                  handle: &Handle)
+    -> BatchFuture<HashMap<u64, Option<Tweet>>>
+{
+    let ids = dedup_in_order(ids);
+    let loaders = ids.chunks(100).map(|chunk| {
+        let mut params = HashMap::new();
+        add_param(&mut params, "id", join_ids(chunk));
+        add_param(&mut params, "map", "true");
+        add_param(&mut params, "tweet_mode", "extended");
+        add_param(&mut params, "trim_user", trim_user.to_string());
 
-    make_future(handle, req, parse_map)
+        let req = auth::get_or_post(links::statuses::LOOKUP, token, Some(&params));
+
+        make_future(handle, req, parse_tweet_map)
+    }).collect();
+
+    make_batch_future(loaders)
 }
 
 ///Make a `Timeline` struct for navigating the collection of tweets posted by the authenticated
@@ -186,15 +275,37 @@ pub fn retweets_of_me(token: &auth::Token, handle: &Handle) -> Timeline<'static>
     Timeline::new(links::statuses::RETWEETS_OF_ME, None, token, handle)
 }
 
+///Make a `Timeline` struct for navigating the collection of tweets posted by the authenticated
+///user that have been retweeted by others, with the given settings for entities.
+///
+///Setting `include_entities` to `false` leaves out the `entities` of each tweet, and setting
+///`include_user_entities` to `false` leaves out the `entities` of each tweet's `user`. With both
+///set to `true`, this is the same as `retweets_of_me`.
+///
+///This method has a default page size of 20 tweets, with a maximum of 100.
+pub fn retweets_of_me_with(include_entities: bool, include_user_entities: bool,
+                           token: &auth::Token, handle: &Handle)
+    -> Timeline<'static>
+{
+    let mut params = HashMap::new();
+    add_param(&mut params, "include_entities", include_entities.to_string());
+    add_param(&mut params, "include_user_entities", include_user_entities.to_string());
+
+    Timeline::new(links::statuses::RETWEETS_OF_ME, Some(params), token, handle)
+}
+
 ///Make a `Timeline` struct for navigating the collection of tweets liked by the given user.
 ///
+///Twitter's API still refers to likes by their old name, "favorites", so this is the method to
+///use if you're looking for `favorites/list`. To like or unlike a tweet, see `like` and `unlike`.
+///
 ///This method has a default page size of 20 tweets, with a maximum of 200.
 pub fn liked_by<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
     -> Timeline<'a>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
-    Timeline::new(links::statuses::LIKES_OF, Some(params), token, handle)
+    Timeline::new(links::favorites::LIST, Some(params), token, handle)
 }
 
 ///Retweet the given status as the authenticated user.
@@ -237,7 +348,7 @@ pub fn like(id: u64, token: &auth::Token, handle: &Handle) -> FutureResponse<Twe
     add_param(&mut params, "id", id.to_string());
     add_param(&mut params, "tweet_mode", "extended");
 
-    let req = auth::post(links::statuses::LIKE, token, Some(&params));
+    let req = auth::post(links::favorites::CREATE, token, Some(&params));
 
     make_parsed_future(handle, req)
 }
@@ -250,7 +361,7 @@ pub fn unlike(id: u64, token: &auth::Token, handle: &Handle) -> FutureResponse<T
     add_param(&mut params, "id", id.to_string());
     add_param(&mut params, "tweet_mode", "extended");
 
-    let req = auth::post(links::statuses::UNLIKE, token, Some(&params));
+    let req = auth::post(links::favorites::DESTROY, token, Some(&params));
 
     make_parsed_future(handle, req)
 }
@@ -268,3 +379,37 @@ pub fn delete(id: u64, token: &auth::Token, handle: &Handle) -> FutureResponse<T
 
     make_parsed_future(handle, req)
 }
+
+///Upload the given image and post a new tweet with it attached, as the authenticated user.
+///
+///This is a shortcut for the most common way to post media: it uploads the image with
+///[`UploadBuilder`], then posts a [`DraftTweet`] with the given text and the new media ID. On
+///success, the future returned by this function yields the new tweet and the handle to the
+///uploaded image. For more control over either step (like setting alt text, replying to another
+///tweet, or retrying a failed upload), use `UploadBuilder` and `DraftTweet` directly.
+///
+///[`UploadBuilder`]: ../media/struct.UploadBuilder.html
+///[`DraftTweet`]: struct.DraftTweet.html
+///
+///```rust,no_run
+///# extern crate egg_mode; extern crate tokio_core;
+///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+///# fn main() {
+///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+///use egg_mode::media::media_types;
+///
+///let image = vec![]; //pretend we loaded an image file into this
+///let (tweet, media) = core.run(egg_mode::tweet::post_with_image("Look at this cat!", image,
+///                                                                 media_types::image_png(),
+///                                                                 &token, &handle)).unwrap();
+///# }
+///```
+pub fn post_with_image<'a, S, V>(text: S, image: V, media_type: mime::Mime, token: &auth::Token,
+                                 handle: &Handle)
+    -> PostWithImageFuture<'a>
+    where S: Into<String>, V: Into<Cow<'a, [u8]>>
+{
+    let upload = media::UploadBuilder::new(image, media_type).call(token, handle);
+
+    PostWithImageFuture::new(text.into(), upload, token, handle)
+}
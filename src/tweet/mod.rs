@@ -21,6 +21,8 @@
 //!   coordinate are available.
 //! - `Timeline`: Returned by several functions in this module, this is how you cursor through a
 //!   collection of tweets. See the struct-level documentation for details.
+//! - `OEmbed`/`OEmbedOptions`/`OEmbedAlign`: Returned by and given to `oembed`, these hold the
+//!   HTML needed to embed a tweet on a web page, and the settings for how it should look.
 //!
 //! ## Functions
 //!
@@ -30,6 +32,7 @@
 //! authenticated user's account.
 //!
 //! - `delete` (for creating a tweet, see `DraftTweet`)
+//! - `post_with_image`, a shortcut for uploading an image and posting a tweet with it
 //! - `like`/`unlike`
 //! - `retweet`/`unretweet`
 //!
@@ -38,11 +41,12 @@
 //! These functions either perform some direct lookup of specific tweets, or provide some metadata
 //! about the given tweet in a direct (non-`Timeline`) fashion.
 //!
-//! - `show`
-//! - `lookup`/`lookup_map` (for the differences between these functions, see their respective
-//!   documentations.)
+//! - `show`/`show_with`
+//! - `lookup`/`lookup_map`/`lookup_with`/`lookup_map_with` (for the differences between these
+//!   functions, see their respective documentations.)
 //! - `retweeters_of`
 //! - `retweets_of`
+//! - `oembed`/`oembed_url`
 //!
 //! ### `Timeline` cursors
 //!
@@ -50,16 +54,16 @@
 //! documentation for `Timeline` to learn how to navigate these return values. This correspond to a
 //! user's own view of Twitter, or with feeds you might see attached to a user's profile page.
 //!
-//! - `home_timeline`/`mentions_timeline`/`retweets_of_me`
+//! - `home_timeline`/`mentions_timeline`/`retweets_of_me`/`retweets_of_me_with`
 //! - `user_timeline`/`liked_by`
 
 use std::borrow::Cow;
+use std::cmp;
 use std::collections::HashMap;
 
 use rustc_serialize::json;
 use chrono;
 use regex::Regex;
-use hyper::client::Request;
 use futures::{Future, Poll, Async};
 
 use auth;
@@ -68,6 +72,7 @@ use user;
 use error;
 use error::Error::InvalidResponse;
 use entities;
+use media;
 use place;
 use stream::FilterLevel;
 use common::*;
@@ -88,7 +93,7 @@ pub use self::fun::*;
 ///* `text`
 ///* `id`
 ///* `created_at`
-///* `user`
+///* `user`/`user_id`
 ///* `source`
 ///* `favorite_count`/`retweet_count`
 ///* `lang`, though third-party clients usually don't surface this at a user-interface level.
@@ -122,8 +127,8 @@ pub use self::fun::*;
 ///tweet" that references another tweet by linking to it. These fields allow you to reference the
 ///parent tweet without having to make another call to `show`.
 ///
-///* `retweeted_status`
-///* `quoted_status`/`quoted_status_id`
+///* `retweeted_status`, also available with `is_retweet` and `retweeted_tweet`
+///* `quoted_status`/`quoted_status_id`, also available with `quoted`
 ///
 ///## Media
 ///
@@ -135,7 +140,8 @@ pub use self::fun::*;
 ///[`MediaEntity`]: ../entities/struct.MediaEntity.html
 ///
 ///* `entities` (note that this also contains information about hyperlinks, user mentions, and
-///  hashtags in addition to a picture/thumbnail)
+///  hashtags in addition to a picture/thumbnail). This will be empty if the tweet was loaded with
+///  entities turned off, like with `retweets_of_me_with`.
 ///* `extended_entities`: This field is only present for tweets with attached media, and houses
 ///  more complete media information, in the case of a photo set, video, or GIF. For videos and
 ///  GIFs, note that `entities` will only contain a thumbnail, and the actual video links will be
@@ -169,6 +175,8 @@ pub struct Tweet {
     ///video.
     pub extended_entities: Option<ExtendedTweetEntities>,
     ///"Approximately" how many times this tweet has been liked by users.
+    ///
+    ///Some payloads leave this out, in which case it's set to 0.
     pub favorite_count: i32,
     ///Indicates whether the authenticated user has liked this tweet.
     pub favorited: Option<bool>,
@@ -189,8 +197,11 @@ pub struct Tweet {
     ///When present, the `Place` that this tweet is associated with (but not necessarily where it
     ///originated from).
     pub place: Option<place::Place>,
-    ///If the tweet has a link, indicates whether the link may contain content that could be
-    ///identified as sensitive.
+    ///If the tweet has a link or attached media, indicates whether it may contain content that
+    ///could be identified as sensitive, so you can hide or blur it by default.
+    ///
+    ///This is set by the author when posting (see `DraftTweet::possibly_sensitive`), or by
+    ///Twitter itself. It's not present on tweets without links or media.
     pub possibly_sensitive: Option<bool>,
     ///If this tweet is quoting another by link, contains the ID of the quoted tweet.
     pub quoted_status_id: Option<u64>,
@@ -200,6 +211,8 @@ pub struct Tweet {
     //Tweet. Currently used by Twitter’s Promoted Products."
     //pub scopes: Option<Scopes>,
     ///The number of times this tweet has been retweeted (with native retweets).
+    ///
+    ///Some payloads leave this out, in which case it's set to 0.
     pub retweet_count: i32,
     ///Indicates whether the authenticated user has retweeted this tweet.
     pub retweeted: Option<bool>,
@@ -219,8 +232,14 @@ pub struct Tweet {
     ///full text is longer than 140 characters.
     pub truncated: bool,
     ///The user who posted this tweet. This field will be absent on tweets included as part of a
-    ///`TwitterUser`.
+    ///`TwitterUser`, or when the tweet was loaded with `trim_user` set.
     pub user: Option<Box<user::TwitterUser>>,
+    ///The numeric ID of the user who posted this tweet.
+    ///
+    ///Unlike `user`, this is still filled in when the tweet was loaded with `trim_user` set, since
+    ///Twitter still sends the author's ID in that case. Like `user`, it's absent on tweets included
+    ///as part of a `TwitterUser`.
+    pub user_id: Option<u64>,
     ///If present and `true`, indicates that this tweet has been withheld due to a DMCA complaint.
     pub withheld_copyright: bool,
     ///If present, contains two-letter country codes indicating where this tweet is being withheld.
@@ -230,8 +249,9 @@ pub struct Tweet {
     ///- `XX`: Withheld in all countries
     ///- `XY`: Withheld due to DMCA complaint.
     pub withheld_in_countries: Option<Vec<String>>,
-    ///If present, indicates whether the content being withheld is the `status` or the `user`.
-    pub withheld_scope: Option<String>,
+    ///If present, indicates whether the content being withheld is this tweet or its author's whole
+    ///account.
+    pub withheld_scope: Option<user::WithheldScope>,
 }
 
 impl FromJson for Tweet {
@@ -251,10 +271,22 @@ impl FromJson for Tweet {
             None
         };
 
+        //with `trim_user` set, Twitter replaces the user object with one that only has its ID
+        let user_json = input.find("user");
+        let user = match user_json {
+            Some(author) if author.find("screen_name").is_some() => {
+                Some(Box::new(try!(user::TwitterUser::from_json(author))))
+            },
+            _ => None,
+        };
+        let user_id: Option<u64> = match user_json {
+            Some(author) => try!(field(author, "id")),
+            None => None,
+        };
+
         field_present!(input, created_at);
         field_present!(input, id);
         field_present!(input, lang);
-        field_present!(input, retweet_count);
         field_present!(input, source);
         field_present!(input, truncated);
 
@@ -269,11 +301,10 @@ impl FromJson for Tweet {
             entities = try!(field(ext, "entities"));
             extended_entities = try!(field(ext, "extended_entities"));
         } else {
-            field_present!(input, entities);
-
             text = try!(field(input, "full_text").or(field(input, "text")));
             display_text_range = try!(field(input, "display_text_range"));
-            entities = try!(field(input, "entities"));
+            //twitter leaves out entities entirely when asked to with include_entities=false
+            entities = try!(field::<Option<TweetEntities>>(input, "entities")).unwrap_or_default();
             extended_entities = try!(field(input, "extended_entities"));
         }
 
@@ -326,13 +357,14 @@ impl FromJson for Tweet {
             quoted_status_id: try!(field(input, "quoted_status_id")),
             quoted_status: try!(field(input, "quoted_status")),
             //scopes: Option<Scopes>,
-            retweet_count: try!(field(input, "retweet_count")),
+            retweet_count: field(input, "retweet_count").unwrap_or(0),
             retweeted: try!(field(input, "retweeted")),
             retweeted_status: try!(field(input, "retweeted_status")),
             source: try!(field(input, "source")),
             text: text,
             truncated: try!(field(input, "truncated")),
-            user: try!(field(input, "user")),
+            user: user,
+            user_id: user_id,
             withheld_copyright: field(input, "withheld_copyright").unwrap_or(false),
             withheld_in_countries: try!(field(input, "withheld_in_countries")),
             withheld_scope: try!(field(input, "withheld_scope")),
@@ -340,6 +372,151 @@ impl FromJson for Tweet {
     }
 }
 
+impl Tweet {
+    ///Returns the user who posted this tweet, if Twitter included them.
+    ///
+    ///This is a shortcut for `user.as_ref()` that also unwraps the `Box`. As with the `user` field,
+    ///this is `None` for tweets included as part of a `TwitterUser`.
+    pub fn author(&self) -> Option<&user::TwitterUser> {
+        self.user.as_ref().map(|user| &**user)
+    }
+
+    ///Returns whether this tweet is a native retweet of another tweet.
+    pub fn is_retweet(&self) -> bool {
+        self.retweeted_status.is_some()
+    }
+
+    ///If this tweet is a native retweet, returns the original tweet that was retweeted.
+    ///
+    ///This is a shortcut for `retweeted_status.as_ref()` that also unwraps the `Box`. The retweet
+    ///itself has its own ID and author, but counts like `favorite_count` and `retweet_count` are
+    ///tracked on the original tweet, so this is the one to look at when displaying those:
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///let tweet = core.run(egg_mode::tweet::show(782349500404862976, &token, &handle)).unwrap();
+    ///let original = tweet.retweeted_tweet().unwrap_or(&tweet);
+    ///println!("{} likes, {} retweets", original.favorite_count, original.retweet_count);
+    ///# }
+    ///```
+    ///
+    ///(This isn't named `retweeted`, since that's already the field that says whether the
+    ///authenticated user has retweeted this tweet.)
+    pub fn retweeted_tweet(&self) -> Option<&Tweet> {
+        self.retweeted_status.as_ref().map(|tweet| &**tweet)
+    }
+
+    ///If this tweet is quoting another, returns the quoted tweet.
+    ///
+    ///This is a shortcut for `quoted_status.as_ref()` that also unwraps the `Box`. Note that
+    ///Twitter only includes one level of quoted tweets: if the quoted tweet is itself a quote,
+    ///its own `quoted_status` will be empty, though its `quoted_status_id` will still be set. The
+    ///quoted tweet is also left out if it's been deleted or can't be seen by the authenticated
+    ///user, in which case only `quoted_status_id` is available.
+    pub fn quoted(&self) -> Option<&Tweet> {
+        self.quoted_status.as_ref().map(|tweet| &**tweet)
+    }
+
+    ///Compares two tweets by when they were posted, oldest first, for sorting tweets
+    ///chronologically.
+    ///
+    ///This compares the parsed `created_at` timestamps rather than Twitter's date text (which
+    ///starts with the day of the week, so it doesn't sort). Tweets posted in the same second are
+    ///ordered by ID, which Twitter hands out in increasing order, so only a tweet compared with
+    ///itself is `Equal`. Hand it to `sort_by`, swapping the arguments to sort newest first:
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///use egg_mode::tweet::Tweet;
+    ///
+    ///let ids = vec![782349500404862976, 783051510464708608];
+    ///let mut tweets = core.run(egg_mode::tweet::lookup(ids, &token, &handle)).unwrap().response;
+    ///
+    ///tweets.sort_by(Tweet::cmp_created_at);
+    ///tweets.sort_by(|a, b| Tweet::cmp_created_at(b, a));
+    ///# }
+    ///```
+    ///
+    ///(This isn't an `Ord` implementation, since `Ord` needs `Eq`, and tweets with the same time
+    ///and ID can still differ in fields like `favorite_count`.)
+    pub fn cmp_created_at(a: &Tweet, b: &Tweet) -> cmp::Ordering {
+        (a.created_at, a.id).cmp(&(b.created_at, b.id))
+    }
+
+    ///Returns the accounts mentioned in this tweet, in the order they're first mentioned.
+    ///
+    ///This pulls the user IDs out of `entities.user_mentions`, leaving out repeat mentions of the
+    ///same account, so they can be handed straight to functions like `user::lookup` or
+    ///`user::relation_lookup`:
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///let tweet = core.run(egg_mode::tweet::show(782349500404862976, &token, &handle)).unwrap();
+    ///let mentioned = core.run(egg_mode::user::lookup(tweet.mentions(), &token, &handle)).unwrap();
+    ///# }
+    ///```
+    pub fn mentions(&self) -> Vec<user::UserID<'static>> {
+        let mut ids: Vec<u64> = Vec::with_capacity(self.entities.user_mentions.len());
+
+        for mention in &self.entities.user_mentions {
+            if !ids.contains(&mention.id) {
+                ids.push(mention.id);
+            }
+        }
+
+        ids.into_iter().map(user::UserID::ID).collect()
+    }
+
+    ///Returns the part of `text` that Twitter's own clients display.
+    ///
+    ///With extended tweets, `text` can start with the @mentions that make up a reply and end with
+    ///the links to attached media or a quoted tweet, neither of which Twitter shows as part of the
+    ///tweet. This slices `text` down to `display_text_range`, or returns the whole of `text` if that
+    ///isn't present. (Twitter gives `display_text_range` in codepoints, but egg-mode converts it to
+    ///byte offsets while loading the tweet, so it can be used to slice `text` directly.)
+    pub fn displayable_text(&self) -> &str {
+        match self.display_text_range {
+            Some((start, end)) => self.text.get(start..end).unwrap_or(&self.text),
+            None => &self.text,
+        }
+    }
+}
+
+///Parses the response to `statuses/lookup` with `map=true`, which gives an object keyed by the
+///requested IDs, with `null` for each tweet that couldn't be loaded.
+fn parse_tweet_map(full_resp: String, headers: &Headers)
+    -> Result<Response<HashMap<u64, Option<Tweet>>>, error::Error>
+{
+    let parsed: Response<json::Json> = try!(make_response(full_resp, headers));
+    let mut map = HashMap::new();
+
+    for (key, val) in try!(parsed.response
+                                 .find("id")
+                                 .and_then(|v| v.as_object())
+                                 .ok_or_else(|| InvalidResponse("unexpected response for lookup_map",
+                                                                Some(parsed.response.to_string())))) {
+        let id = try!(key.parse::<u64>().or(Err(InvalidResponse("could not parse id as integer",
+                                                                Some(key.to_string())))));
+        if val.is_null() {
+            map.insert(id, None);
+        } else {
+            let tweet = try!(Tweet::from_json(&val));
+            map.insert(id, Some(tweet));
+        }
+    }
+
+    Ok(Response::map(parsed, |_| map))
+}
+
 fn current_user_retweet(input: &json::Json, field: &'static str) -> Result<Option<u64>, error::Error> {
     if let Some(obj) = input.find(field).and_then(|f| f.as_object()) {
         match obj.get("id").and_then(|o| o.as_u64()) {
@@ -359,11 +536,15 @@ fn current_user_retweet(input: &json::Json, field: &'static str) -> Result<Optio
 ///
 ///Note that if you're going to reconstruct a link from this, the source URL has `rel="nofollow"`
 ///in the anchor tag.
+///
+///Occasionally, Twitter sends the source as plain text instead of a link. In that case, the whole
+///text is used as the `name`, and `url` is left empty.
 #[derive(Debug, Clone)]
 pub struct TweetSource {
     ///The name of the app, given by its developer.
     pub name: String,
-    ///The URL for the app, given by its developer.
+    ///The URL for the app, given by its developer. This is empty if Twitter didn't send a link for
+    ///the app.
     pub url: String,
 }
 
@@ -385,6 +566,13 @@ impl FromJson for TweetSource {
             static ref RE_NAME: Regex = Regex::new(">(.*)</a>").unwrap();
         }
 
+        if !full.contains("<a") {
+            return Ok(TweetSource {
+                name: full.trim().to_string(),
+                url: String::new(),
+            });
+        }
+
         let url = if let Some(cap) = RE_URL.captures(full) {
             cap.expand("$1")
         } else {
@@ -407,12 +595,13 @@ impl FromJson for TweetSource {
 ///Container for URL, hashtag, mention, and media information associated with a tweet.
 ///
 ///If a tweet has no hashtags, financial symbols ("cashtags"), links, or mentions, those respective
-///Vecs will be empty. If there is no media attached to the tweet, that field will be `None`.
+///Vecs will be empty. If there is no media attached to the tweet, that field will be `None`. If the
+///tweet was loaded without entities, this will be empty as well.
 ///
 ///Note that for media attached to a tweet, this struct will only contain the first image of a
 ///photo set, or a thumbnail of a video or GIF. Full media information is available in the tweet's
 ///`extended_entities` field.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TweetEntities {
     ///Collection of hashtags parsed from the tweet.
     pub hashtags: Vec<entities::HashtagEntity>,
@@ -466,6 +655,194 @@ impl FromJson for ExtendedTweetEntities {
     }
 }
 
+///The HTML needed to embed a tweet on a web page, as returned by `oembed`.
+///
+///This follows the [oEmbed] format, so the `html` field can be placed directly into a page to show
+///the tweet the same way Twitter's own embedded tweets look. Unless you set `omit_script` when
+///making the call, `html` will also contain a `<script>` tag to load Twitter's widget code.
+///
+///[oEmbed]: https://oembed.com/
+#[derive(Debug, Clone)]
+pub struct OEmbed {
+    ///The URL of the tweet being embedded.
+    pub url: String,
+    ///The display name of the tweet's author.
+    pub author_name: String,
+    ///The URL of the profile of the tweet's author.
+    pub author_url: String,
+    ///The HTML snippet to embed in a page.
+    pub html: String,
+    ///The width of the embedded tweet in pixels, if Twitter gave one.
+    pub width: Option<i32>,
+    ///The height of the embedded tweet in pixels, if Twitter gave one. Twitter usually doesn't,
+    ///since that depends on the tweet's text and media.
+    pub height: Option<i32>,
+    ///How long, in seconds, this response may be cached for, if Twitter gave a limit.
+    pub cache_age: Option<u64>,
+    ///The name of the site providing the embed. This is usually "Twitter".
+    pub provider_name: String,
+    ///The URL of the site providing the embed.
+    pub provider_url: String,
+    ///The version of the oEmbed format this response uses.
+    pub version: String,
+}
+
+impl FromJson for OEmbed {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("OEmbed received json that wasn't an object", Some(input.to_string())));
+        }
+
+        field_present!(input, url);
+        field_present!(input, author_name);
+        field_present!(input, author_url);
+        field_present!(input, html);
+        field_present!(input, provider_name);
+        field_present!(input, provider_url);
+        field_present!(input, version);
+
+        //twitter sends cache_age as a string, but the oEmbed spec lets it be a number too
+        let cache_age = input.find("cache_age").and_then(|age| {
+            age.as_string().and_then(|age| age.parse().ok()).or_else(|| age.as_u64())
+        });
+
+        Ok(OEmbed {
+            url: try!(field(input, "url")),
+            author_name: try!(field(input, "author_name")),
+            author_url: try!(field(input, "author_url")),
+            html: try!(field(input, "html")),
+            width: try!(field(input, "width")),
+            height: try!(field(input, "height")),
+            cache_age: cache_age,
+            provider_name: try!(field(input, "provider_name")),
+            provider_url: try!(field(input, "provider_url")),
+            version: try!(field(input, "version")),
+        })
+    }
+}
+
+///How an embedded tweet should be floated on the page, for `OEmbedOptions::align`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OEmbedAlign {
+    ///Float the tweet to the left of the surrounding content.
+    Left,
+    ///Float the tweet to the right of the surrounding content.
+    Right,
+    ///Center the tweet on the page.
+    Center,
+    ///Don't float the tweet. This is Twitter's default.
+    None,
+}
+
+impl OEmbedAlign {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            OEmbedAlign::Left => "left",
+            OEmbedAlign::Right => "right",
+            OEmbedAlign::Center => "center",
+            OEmbedAlign::None => "none",
+        }
+    }
+}
+
+///Settings for how an embedded tweet should be displayed, for `oembed` and `oembed_url`.
+///
+///Every setting is optional; any that are left as `None` will use Twitter's defaults.
+///
+///```rust
+///use egg_mode::tweet::{OEmbedOptions, OEmbedAlign};
+///
+///let opts = OEmbedOptions::new().max_width(400).hide_thread(true).align(OEmbedAlign::Center);
+///```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OEmbedOptions {
+    ///The widest the embedded tweet can be, in pixels. Twitter only accepts values between 220 and
+    ///550.
+    pub max_width: Option<u32>,
+    ///Whether to leave out any photos, videos, or link previews attached to the tweet.
+    pub hide_media: Option<bool>,
+    ///Whether to leave out the tweet this one is replying to, if any.
+    pub hide_thread: Option<bool>,
+    ///Whether to leave out the `<script>` tag that loads Twitter's widget code. Set this if you're
+    ///embedding several tweets on one page and are loading that code yourself.
+    pub omit_script: Option<bool>,
+    ///How the embedded tweet should be floated on the page.
+    pub align: Option<OEmbedAlign>,
+}
+
+impl OEmbedOptions {
+    ///Creates a new `OEmbedOptions` that keeps Twitter's default settings.
+    pub fn new() -> OEmbedOptions {
+        OEmbedOptions::default()
+    }
+
+    ///Sets the widest the embedded tweet can be, in pixels.
+    ///
+    ///Twitter only accepts values between 220 and 550, so the given width will be clamped to that
+    ///range.
+    pub fn max_width(self, max_width: u32) -> OEmbedOptions {
+        OEmbedOptions {
+            max_width: Some(::std::cmp::max(220, ::std::cmp::min(max_width, 550))),
+            ..self
+        }
+    }
+
+    ///Sets whether to leave out any media attached to the tweet.
+    pub fn hide_media(self, hide_media: bool) -> OEmbedOptions {
+        OEmbedOptions {
+            hide_media: Some(hide_media),
+            ..self
+        }
+    }
+
+    ///Sets whether to leave out the tweet this one is replying to.
+    pub fn hide_thread(self, hide_thread: bool) -> OEmbedOptions {
+        OEmbedOptions {
+            hide_thread: Some(hide_thread),
+            ..self
+        }
+    }
+
+    ///Sets whether to leave out the `<script>` tag from the embed HTML.
+    pub fn omit_script(self, omit_script: bool) -> OEmbedOptions {
+        OEmbedOptions {
+            omit_script: Some(omit_script),
+            ..self
+        }
+    }
+
+    ///Sets how the embedded tweet should be floated on the page.
+    pub fn align(self, align: OEmbedAlign) -> OEmbedOptions {
+        OEmbedOptions {
+            align: Some(align),
+            ..self
+        }
+    }
+
+    ///Adds the parameters for these settings to the given `ParamList`.
+    fn add_params(&self, params: &mut ParamList) {
+        if let Some(max_width) = self.max_width {
+            add_param(params, "maxwidth", max_width.to_string());
+        }
+
+        if let Some(hide_media) = self.hide_media {
+            add_param(params, "hide_media", hide_media.to_string());
+        }
+
+        if let Some(hide_thread) = self.hide_thread {
+            add_param(params, "hide_thread", hide_thread.to_string());
+        }
+
+        if let Some(omit_script) = self.omit_script {
+            add_param(params, "omit_script", omit_script.to_string());
+        }
+
+        if let Some(align) = self.align {
+            add_param(params, "align", align.as_str());
+        }
+    }
+}
+
 /// Helper struct to navigate collections of tweets by requesting tweets older or newer than certain
 /// IDs.
 ///
@@ -542,8 +919,31 @@ impl FromJson for ExtendedTweetEntities {
 /// available that were posted after the given ID". The earlier invocations of `older` with `None`
 /// do not place a bound on the tweets it loads. `newer` operates in a similar fashion with its
 /// argument, saying "newer than what I just returned, but not newer than this given ID". When
-/// called like this, it's possible for these methods to return nothing, which will also clear the
-/// `Timeline`'s tracked IDs.
+/// called like this, it's possible for these methods to return nothing, in which case the
+/// `Timeline`'s tracked IDs are left as they were.
+///
+/// If you're saving the newest tweet ID between runs of your program, you can also hand it to a
+/// fresh `Timeline` with `with_since_id`, so that `newer` only loads the tweets you haven't seen
+/// yet:
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// # let saved_id = 0;
+/// let timeline = egg_mode::tweet::home_timeline(&token, &handle)
+///                                .with_since_id(saved_id);
+///
+/// let (timeline, new_posts) = core.run(timeline.newer(None)).unwrap();
+///
+/// //if nothing was posted since then, this is still `saved_id`
+/// let saved_id = timeline.max_id.unwrap();
+/// # }
+/// ```
+///
+/// Likewise, `with_max_id` sets up a `Timeline` so that `older` starts loading from a given tweet
+/// ID.
 ///
 /// If you want to manually pull tweets between certain IDs, the baseline `call` function can do
 /// that for you. Keep in mind, though, that `call` doesn't update the `min_id` or `max_id` fields,
@@ -557,6 +957,8 @@ pub struct Timeline<'a> {
     handle: Handle,
     ///Optional set of params to include prior to adding timeline navigation parameters.
     params_base: Option<ParamList<'a>>,
+    ///Whether to ask Twitter to leave out everything but the ID of each tweet's author.
+    trim_user: bool,
     ///The maximum number of tweets to return in a single call. Twitter doesn't guarantee returning
     ///exactly this number, as suspended or deleted content is removed after retrieving the initial
     ///collection of tweets.
@@ -617,11 +1019,15 @@ impl<'a> Timeline<'a> {
     }
 
     ///Helper function to construct a `Request` from the current state.
-    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> Request {
+    fn request(&self, since_id: Option<u64>, max_id: Option<u64>) -> auth::SignedRequest {
         let mut params = self.params_base.as_ref().cloned().unwrap_or_default();
         add_param(&mut params, "count", self.count.to_string());
         add_param(&mut params, "tweet_mode", "extended");
 
+        if self.trim_user {
+            add_param(&mut params, "trim_user", "true");
+        }
+
         if let Some(id) = since_id {
             add_param(&mut params, "since_id", id.to_string());
         }
@@ -641,10 +1047,114 @@ impl<'a> Timeline<'a> {
         }
     }
 
+    ///Helper builder function to ask Twitter to leave out the full author profile from each tweet.
+    ///
+    ///With this set, the tweets loaded by this `Timeline` will have their `user` field set to
+    ///`None`, but their `user_id` will still be filled in. This saves bandwidth if you already have
+    ///the users you need.
+    pub fn with_trim_user(self, trim_user: bool) -> Self {
+        Timeline {
+            trim_user: trim_user,
+            ..self
+        }
+    }
+
+    ///Helper builder function to start the timeline after the given tweet ID, so that `newer`
+    ///will load the tweets posted after it.
+    ///
+    ///This sets `max_id`, as if the given tweet was the newest one loaded so far.
+    pub fn with_since_id(self, since_id: u64) -> Self {
+        Timeline {
+            max_id: Some(since_id),
+            ..self
+        }
+    }
+
+    ///Helper builder function to start the timeline at the given tweet ID, so that `older` will
+    ///load the given tweet and the ones posted before it.
+    ///
+    ///This sets `min_id` to the ID just after the given one, as if that tweet was the oldest one
+    ///loaded so far.
+    pub fn with_max_id(self, max_id: u64) -> Self {
+        Timeline {
+            min_id: Some(max_id.saturating_add(1)),
+            ..self
+        }
+    }
+
+    ///Helper builder function to start the timeline at the given time, so that `newer` will load
+    ///the tweets posted after it.
+    ///
+    ///Twitter's timelines can only be bounded by tweet ID, so this uses `id_for_time` to estimate
+    ///the ID of the first tweet posted at `since`, and sets `max_id` just before it, as with
+    ///`with_since_id`. Since this is an estimate, you may get back a few tweets posted just before
+    ///the given time; if you need an exact cutoff, check each tweet's `created_at` as well.
+    pub fn with_since_time(self, since: chrono::DateTime<chrono::Utc>) -> Self {
+        Timeline {
+            max_id: Some(id_for_time(since).saturating_sub(1)),
+            ..self
+        }
+    }
+
+    ///Helper builder function to end the timeline at the given time, so that `older` will load
+    ///the tweets posted before it.
+    ///
+    ///Twitter's timelines can only be bounded by tweet ID, so this uses `id_for_time` to estimate
+    ///the ID of the first tweet posted at `until`, and sets `min_id` to it, as with
+    ///`with_max_id`. Since this is an estimate, you may get back a few tweets posted just after the
+    ///given time; if you need an exact cutoff, check each tweet's `created_at` as well.
+    pub fn with_until_time(self, until: chrono::DateTime<chrono::Utc>) -> Self {
+        Timeline {
+            //`older` asks for IDs below `min_id`, so keep it above zero even for very old times
+            min_id: Some(cmp::max(id_for_time(until), 1)),
+            ..self
+        }
+    }
+
+    ///Returns a snapshot of where this `Timeline` is, so it can be saved and picked up again later
+    ///with `resume`.
+    pub fn state(&self) -> TimelineState {
+        TimelineState {
+            count: self.count,
+            max_id: self.max_id,
+            min_id: self.min_id,
+        }
+    }
+
+    ///Picks up this `Timeline` from the given saved state, as returned by `state`.
+    ///
+    ///This sets `count`, `max_id`, and `min_id` from the state, so that `older` and `newer` carry
+    ///on from where the saved `Timeline` left off. The state should come from a `Timeline` for the
+    ///same call as this one.
+    ///
+    ///```rust,no_run
+    ///# extern crate egg_mode; extern crate tokio_core; extern crate futures;
+    ///# use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    ///# fn main() {
+    ///# let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    ///# let saved_state: egg_mode::tweet::TimelineState = unimplemented!();
+    ///let timeline = egg_mode::tweet::home_timeline(&token, &handle).resume(saved_state);
+    ///let (timeline, new_tweets) = core.run(timeline.newer(None)).unwrap();
+    ///# }
+    ///```
+    pub fn resume(self, state: TimelineState) -> Self {
+        Timeline {
+            count: state.count,
+            max_id: state.max_id,
+            min_id: state.min_id,
+            ..self
+        }
+    }
+
     ///With the returned slice of Tweets, set the min_id and max_id on self.
+    ///
+    ///If the slice is empty, the IDs are left as they were, so that the same call can be repeated
+    ///later.
     fn map_ids(&mut self, resp: &[Tweet]) {
-        self.max_id = resp.first().map(|status| status.id);
-        self.min_id = resp.last().map(|status| status.id);
+        if let (Some(first), Some(last)) = (resp.first(), resp.last()) {
+            self.max_id = Some(first.id);
+            self.min_id = Some(last.id);
+        }
     }
 
     ///Create an instance of `Timeline` with the given link and tokens.
@@ -656,6 +1166,7 @@ impl<'a> Timeline<'a> {
             token: token.clone(),
             handle: handle.clone(),
             params_base: params_base,
+            trim_user: false,
             count: 20,
             max_id: None,
             min_id: None,
@@ -663,6 +1174,107 @@ impl<'a> Timeline<'a> {
     }
 }
 
+///The time of Twitter's ID epoch, in milliseconds since the Unix epoch.
+const TWEPOCH_MILLIS: i64 = 1288834974657;
+
+///Estimates the smallest tweet ID that could have been given to a tweet posted at the given time.
+///
+///Tweet IDs (and the IDs of most other things on Twitter) are "snowflake" IDs, whose upper bits
+///hold the millisecond they were created, so every tweet posted at or after `time` will have an ID
+///at least as large as the one returned here, and every tweet posted before it will have a
+///smaller one. This makes it possible to use a time as a bound for calls that only take tweet IDs,
+///like `Timeline::with_since_id` or `search::SearchBuilder::since_tweet`. `Timeline` also has the
+///shortcuts `with_since_time` and `with_until_time` for this.
+///
+///Keep in mind that this is only an approximation: the time in the ID is when Twitter's servers
+///handed out the ID, which can be a little off from the tweet's `created_at`. Tweets posted before
+///snowflake IDs were introduced, in November 2010, don't follow this scheme at all; for times
+///before then, this returns 0. The other way, snowflake IDs run out of room for the time sometime
+///in 2150; for times after that, this returns `u64::MAX`.
+pub fn id_for_time(time: chrono::DateTime<chrono::Utc>) -> u64 {
+    let millis = time.timestamp().checked_mul(1000)
+                     .and_then(|ms| ms.checked_add(time.timestamp_subsec_millis() as i64));
+    let millis = match millis {
+        Some(millis) => millis,
+        None => return u64::max_value(),
+    };
+
+    if millis <= TWEPOCH_MILLIS {
+        return 0;
+    }
+
+    //the shift would silently drop the top bits of anything larger than this
+    let elapsed = (millis - TWEPOCH_MILLIS) as u64;
+    if elapsed > u64::max_value() >> 22 {
+        u64::max_value()
+    } else {
+        elapsed << 22
+    }
+}
+
+///The longest text a tweet can have, as counted by `text_length`.
+const MAX_TEXT_LENGTH: usize = 280;
+
+///The length every link counts as in a tweet, since Twitter shortens them all with t.co.
+const SHORT_URL_LENGTH: usize = 23;
+
+///Counts the length of the given tweet text the way Twitter does, to check it against the
+///280-character limit.
+///
+///Twitter doesn't simply count the characters in a tweet. Every link is shortened with t.co, so it
+///counts as 23 characters no matter how long it is, and characters outside of the Latin, Greek,
+///Cyrillic, and similar scripts (including Chinese, Japanese, and Korean text, and emoji) count as
+///2 characters each. `DraftTweet::send` uses this to reject text that's too long before calling
+///Twitter.
+///
+///Keep in mind that this is an approximation of Twitter's rules: links are detected by looking for
+///`http://`, `https://`, or `www.`, so bare domains like `example.com` are counted as regular
+///text.
+///
+///```rust
+///use egg_mode::tweet::text_length;
+///
+///assert_eq!(text_length("hello"), 5);
+///assert_eq!(text_length("read this: https://example.com/a/really/long/link/to/somewhere"), 34);
+///assert_eq!(text_length("こんにちは"), 10);
+///```
+pub fn text_length(text: &str) -> usize {
+    lazy_static! {
+        static ref RE_URL: Regex = Regex::new(r"(?i)\b(?:https?://|www\.)\S+").unwrap();
+    }
+
+    fn weighted(text: &str) -> usize {
+        text.chars().map(|c| match c as u32 {
+            0...4351 | 8192...8205 | 8208...8223 | 8242...8247 => 1,
+            _ => 2,
+        }).sum()
+    }
+
+    let mut length = 0;
+    let mut last = 0;
+
+    for (start, end) in RE_URL.find_iter(text) {
+        length += weighted(&text[last..start]) + SHORT_URL_LENGTH;
+        last = end;
+    }
+
+    length + weighted(&text[last..])
+}
+
+/// A saved position in a `Timeline`, returned by `Timeline::state`.
+///
+/// This implements serde's `Serialize` and `Deserialize`, so it can be saved to disk and loaded
+/// back in after a restart, to resume a timeline with `Timeline::resume`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineState {
+    /// The number of tweets to load in a single call.
+    pub count: i32,
+    /// The largest/most recent tweet ID the saved `Timeline` had loaded.
+    pub max_id: Option<u64>,
+    /// The smallest/oldest tweet ID the saved `Timeline` had loaded.
+    pub min_id: Option<u64>,
+}
+
 /// `Future` which represents loading from a `Timeline`.
 ///
 /// When this future completes, it will either return the tweets given by Twitter (after having
@@ -854,6 +1466,28 @@ impl<'a> DraftTweet<'a> {
         }
     }
 
+    ///Makes this draft quote the given tweet, by attaching the tweet's URL with `attachment_url`.
+    ///
+    ///Twitter needs the screen name of the quoted tweet's author to build the URL, along with its
+    ///ID. A leading `@` on the screen name is ignored. Since the URL is sent as an attachment, it
+    ///doesn't count against the draft's character limit, and doesn't need to be in its text.
+    ///
+    ///```rust
+    ///use egg_mode::tweet::DraftTweet;
+    ///
+    ///let draft = DraftTweet::new("this is what i was talking about")
+    ///                       .quote(782349500404862976, "@0xabad1dea");
+    ///
+    ///assert_eq!(draft.attachment_url.unwrap(),
+    ///           "https://twitter.com/0xabad1dea/status/782349500404862976");
+    ///```
+    pub fn quote(self, quoted_id: u64, quoted_user: &str) -> Self {
+        let screen_name = quoted_user.trim_left_matches('@');
+        let url = format!("https://twitter.com/{}/status/{}", screen_name, quoted_id);
+
+        self.attachment_url(url)
+    }
+
     ///Attach a lat/lon coordinate to this tweet, and mark whether a pin should be placed on the
     ///exact coordinate when the tweet is displayed.
     ///
@@ -862,6 +1496,12 @@ impl<'a> DraftTweet<'a> {
     ///the eventual tweet.
     ///
     ///Location fields will be ignored unless the user has enabled geolocation from their profile.
+    ///Twitter doesn't return an error in that case; the tweet is just posted without a location.
+    ///To check ahead of time, look at the `geo_enabled` field of the authenticated user (from
+    ///`auth::verify_tokens`).
+    ///
+    ///When the posted tweet comes back, the coordinate will be in its `coordinates` field, in the
+    ///same (latitude, longitude) order given here, and the Place Twitter picked will be in `place`.
     pub fn coordinates(self, latitude: f64, longitude: f64, display: bool) -> Self {
         DraftTweet {
             coordinates: Some((latitude, longitude)),
@@ -900,6 +1540,9 @@ impl<'a> DraftTweet<'a> {
 
     ///Marks the media attached with `media_ids` as being sensitive, so it can be hidden by
     ///default.
+    ///
+    ///This also applies to quote tweets made with `quote`. When the tweet is loaded back in, this
+    ///setting will be in its `possibly_sensitive` field.
     pub fn possibly_sensitive(self, sensitive: bool) -> Self {
         DraftTweet {
             possibly_sensitive: Some(sensitive),
@@ -908,7 +1551,15 @@ impl<'a> DraftTweet<'a> {
     }
 
     ///Send the assembled tweet as the authenticated user.
+    ///
+    ///If the text is longer than Twitter allows, as counted by `text_length`, the future returned
+    ///by this function will fail with `Error::TweetTooLong` without calling Twitter.
     pub fn send(&self, token: &auth::Token, handle: &Handle) -> FutureResponse<Tweet> {
+        let length = text_length(&self.text);
+        if length > MAX_TEXT_LENGTH {
+            return make_failed_future(handle, error::Error::TweetTooLong(length));
+        }
+
         let mut params = HashMap::new();
         add_param(&mut params, "status", self.text.clone());
 
@@ -956,15 +1607,91 @@ impl<'a> DraftTweet<'a> {
             add_param(&mut params, "possibly_sensitive", sensitive.to_string());
         }
 
+        add_param(&mut params, "tweet_mode", "extended");
+
         let req = auth::post(links::statuses::UPDATE, token, Some(&params));
         make_parsed_future(handle, req)
     }
 }
 
+/// `Future` which represents uploading an image and then posting a tweet with it, as returned by
+/// `post_with_image`.
+///
+/// When this future completes, it will return the newly-posted tweet, along with the handle to the
+/// uploaded image so its ID can be reused on another tweet while it's still valid. If either step
+/// fails, the future returns that error. An error from the upload is returned as the `error`
+/// inside the [`UploadError`], so if you need to retry a failed upload, use [`UploadBuilder`] and
+/// [`DraftTweet`] separately instead.
+///
+/// [`UploadError`]: ../media/struct.UploadError.html
+/// [`UploadBuilder`]: ../media/struct.UploadBuilder.html
+/// [`DraftTweet`]: struct.DraftTweet.html
+#[must_use = "futures do nothing unless polled"]
+pub struct PostWithImageFuture<'a> {
+    text: String,
+    token: auth::Token,
+    handle: Handle,
+    upload: Option<media::UploadFuture<'a>>,
+    post: Option<(media::MediaHandle, FutureResponse<Tweet>)>,
+}
+
+impl<'a> PostWithImageFuture<'a> {
+    #[doc(hidden)]
+    pub fn new(text: String, upload: media::UploadFuture<'a>, token: &auth::Token, handle: &Handle)
+        -> PostWithImageFuture<'a>
+    {
+        PostWithImageFuture {
+            text: text,
+            token: token.clone(),
+            handle: handle.clone(),
+            upload: Some(upload),
+            post: None,
+        }
+    }
+}
+
+impl<'a> Future for PostWithImageFuture<'a> {
+    type Item = (Response<Tweet>, media::MediaHandle);
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(mut upload) = self.upload.take() {
+            match upload.poll() {
+                Err(e) => return Err(e.error),
+                Ok(Async::NotReady) => {
+                    self.upload = Some(upload);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(media)) => {
+                    let draft = DraftTweet::new(self.text.clone()).media_ids(&[media.id]);
+                    self.post = Some((media, draft.send(&self.token, &self.handle)));
+                }
+            }
+        }
+
+        if let Some((media, mut post)) = self.post.take() {
+            match post.poll() {
+                Err(e) => Err(e),
+                Ok(Async::NotReady) => {
+                    self.post = Some((media, post));
+                    Ok(Async::NotReady)
+                }
+                Ok(Async::Ready(tweet)) => Ok(Async::Ready((tweet, media))),
+            }
+        } else {
+            Err(error::Error::FutureAlreadyCompleted)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use common::FromJson;
-    use super::Tweet;
+    use super::{Tweet, Timeline, OEmbed};
+    use auth;
+    use links;
+
+    use tokio_core::reactor::Core;
 
     use chrono::{Weekday, Datelike, Timelike};
 
@@ -981,6 +1708,28 @@ mod tests {
         Tweet::from_str(&sample_str).unwrap()
     }
 
+    #[test]
+    fn sort_by_created_at() {
+        use chrono::Duration;
+
+        let first = load_tweet("src/tweet/sample-extended-onepic.json");
+
+        let mut second = first.clone();
+        second.id += 1;
+
+        let mut third = first.clone();
+        third.id -= 1;
+        third.created_at = first.created_at + Duration::days(1);
+
+        let mut tweets = vec![third.clone(), second.clone(), first.clone()];
+        tweets.sort_by(Tweet::cmp_created_at);
+        assert_eq!(tweets.iter().map(|t| t.id).collect::<Vec<_>>(),
+                   vec![first.id, second.id, third.id]);
+
+        tweets.sort_by(|a, b| Tweet::cmp_created_at(b, a));
+        assert_eq!(tweets[0].id, third.id);
+    }
+
     #[test]
     fn parse_basic() {
         let sample = load_tweet("src/tweet/sample-extended-onepic.json");
@@ -992,6 +1741,7 @@ mod tests {
         assert_eq!(sample.id, 782349500404862976);
         assert_eq!(sample.source.name, "Tweetbot for iΟS"); //note that's an omicron, not an O
         assert_eq!(sample.source.url, "http://tapbots.com/tweetbot");
+        assert_eq!(sample.possibly_sensitive, Some(false));
         assert_eq!(sample.created_at.weekday(), Weekday::Sat);
         assert_eq!(sample.created_at.year(), 2016);
         assert_eq!(sample.created_at.month(), 10);
@@ -1021,6 +1771,66 @@ mod tests {
         assert_eq!(sample.truncated, false);
     }
 
+    #[test]
+    fn tweet_mentions() {
+        use user::UserID;
+
+        let mut sample = load_tweet("src/tweet/sample-extended-onepic.json");
+        assert_eq!(sample.mentions(), vec![UserID::ID(821887280)]);
+
+        //mentioning the same account twice only lists it once
+        let repeat = sample.entities.user_mentions[0].clone();
+        sample.entities.user_mentions.push(repeat);
+        assert_eq!(sample.mentions(), vec![UserID::ID(821887280)]);
+
+        assert!(load_tweet("src/tweet/sample-reply.json").mentions().is_empty());
+    }
+
+    #[test]
+    fn tweet_author() {
+        let mut sample = load_tweet("src/tweet/sample-reply.json");
+        let user_id = sample.user.as_ref().unwrap().id;
+        assert_eq!(sample.author().map(|u| u.id), Some(user_id));
+
+        sample.user = None;
+        assert!(sample.author().is_none());
+    }
+
+    #[test]
+    fn trimmed_user() {
+        use rustc_serialize::json::Json;
+
+        let sample = load_tweet("src/tweet/sample-reply.json");
+        let user_id = sample.user.as_ref().unwrap().id;
+        assert_eq!(sample.user_id, Some(user_id));
+
+        let mut trimmed = {
+            let mut file = File::open("src/tweet/sample-reply.json").unwrap();
+            let mut ret = String::new();
+            file.read_to_string(&mut ret).unwrap();
+            Json::from_str(&ret).unwrap()
+        };
+        if let Json::Object(ref mut obj) = trimmed {
+            let user = format!(r#"{{"id": {0}, "id_str": "{0}"}}"#, user_id);
+            obj.insert("user".to_string(), Json::from_str(&user).unwrap());
+        }
+
+        let trimmed = Tweet::from_json(&trimmed).unwrap();
+        assert!(trimmed.user.is_none());
+        assert_eq!(trimmed.user_id, Some(user_id));
+    }
+
+    #[test]
+    fn displayable_text() {
+        let sample = load_tweet("src/tweet/sample-extended-onepic.json");
+        assert_eq!(sample.displayable_text(),
+                   ".@Serrayak said he’d use what-ev-er I came up with as his Halloween avatar so I’m just making sure you all know he said that");
+
+        let mut sample = load_tweet("src/tweet/sample-reply.json");
+        sample.display_text_range = None;
+        assert_eq!(sample.displayable_text(), sample.text);
+    }
+
     #[test]
     fn parse_reply() {
         let sample = load_tweet("src/tweet/sample-reply.json");
@@ -1030,22 +1840,270 @@ mod tests {
         assert_eq!(sample.in_reply_to_status_id, Some(782643731665080322));
     }
 
+    #[test]
+    fn parse_lookup_map() {
+        use super::parse_tweet_map;
+        use common::Headers;
+
+        let mut file = File::open("src/tweet/sample-lookup-map.json").unwrap();
+        let mut input = String::new();
+        file.read_to_string(&mut input).unwrap();
+
+        let map = parse_tweet_map(input, &Headers::new()).unwrap().response;
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&782644334671691776].as_ref().map(|t| t.id), Some(782644334671691776));
+        assert!(map[&782349500404862977].is_none());
+        assert!(map[&20].is_none());
+
+        assert!(parse_tweet_map(r#"[{"id": 20}]"#.to_string(), &Headers::new()).is_err());
+    }
+
+    #[test]
+    fn parse_missing_counts() {
+        let sample = load_tweet("src/tweet/sample-no-counts.json");
+
+        assert_eq!(sample.id, 782644334671691776);
+        assert_eq!(sample.favorite_count, 0);
+        assert_eq!(sample.retweet_count, 0);
+    }
+
+    #[test]
+    fn parse_missing_entities() {
+        let sample = load_tweet("src/tweet/sample-no-entities.json");
+
+        assert_eq!(sample.id, 782349500404862976);
+        assert!(sample.entities.hashtags.is_empty());
+        assert!(sample.entities.urls.is_empty());
+        assert!(sample.entities.user_mentions.is_empty());
+        assert!(sample.entities.media.is_none());
+        assert!(sample.extended_entities.is_none());
+        assert_eq!(sample.user.unwrap().screen_name, "0xabad1dea");
+    }
+
+    #[test]
+    fn parse_withheld() {
+        use user::WithheldScope;
+
+        let sample = load_tweet("src/tweet/sample-withheld.json");
+
+        assert_eq!(sample.id, 782644334671691776);
+        assert!(!sample.withheld_copyright);
+        assert_eq!(sample.withheld_in_countries, Some(vec!["DE".to_string(), "FR".to_string()]));
+        assert_eq!(sample.withheld_scope, Some(WithheldScope::Status));
+
+        let plain = load_tweet("src/tweet/sample-no-counts.json");
+        assert!(plain.withheld_in_countries.is_none());
+        assert!(plain.withheld_scope.is_none());
+    }
+
+    #[test]
+    fn parse_source() {
+        use rustc_serialize::json::Json;
+        use super::TweetSource;
+
+        let link = Json::String(r#"<a href="http://twitter.com/download/iphone" rel="nofollow">Twitter for iPhone</a>"#.to_string());
+        let source = TweetSource::from_json(&link).unwrap();
+        assert_eq!(source.name, "Twitter for iPhone");
+        assert_eq!(source.url, "http://twitter.com/download/iphone");
+
+        let plain = Json::String("Some Bot ".to_string());
+        let source = TweetSource::from_json(&plain).unwrap();
+        assert_eq!(source.name, "Some Bot");
+        assert_eq!(source.url, "");
+
+        assert!(TweetSource::from_json(&Json::String("<a>No Link</a>".to_string())).is_err());
+    }
+
     #[test]
     fn parse_quote() {
         let sample = load_tweet("src/tweet/sample-quote.json");
 
         assert_eq!(sample.quoted_status_id, Some(783004145485840384));
         assert!(sample.quoted_status.is_some());
+        assert_eq!(sample.quoted().map(|q| q.id), Some(783004145485840384));
         assert_eq!(sample.quoted_status.unwrap().text,
                    "@chalkboardsband hot damn i should call up my friends in austin, i might actually be able to make one of these now :D");
     }
 
+    #[test]
+    fn parse_retweeted_quote() {
+        let sample = load_tweet("src/tweet/sample-retweet-quote.json");
+
+        //a retweet of a quote tweet carries the quoted tweet both on the retweet and the original
+        let original = sample.retweeted_tweet().unwrap();
+        assert_eq!(original.id, 783021240093118465);
+        assert_eq!(original.quoted().unwrap().id, 783004145485840384);
+        assert_eq!(sample.quoted().unwrap().id, 783004145485840384);
+        assert_eq!(sample.quoted_status_id, Some(783004145485840384));
+
+        //twitter only sends one level of quotes
+        assert!(original.quoted().unwrap().quoted().is_none());
+    }
+
     #[test]
     fn parse_retweet() {
         let sample = load_tweet("src/tweet/sample-retweet.json");
 
+        assert!(sample.is_retweet());
+        assert!(!sample.retweeted_tweet().unwrap().is_retweet());
+        assert_ne!(sample.retweeted_tweet().unwrap().id, sample.id);
         assert!(sample.retweeted_status.is_some());
         assert_eq!(sample.retweeted_status.unwrap().text,
                    "it's working: follow @andrewhuangbot for a random lyric of mine every hour. we'll call this version 0.1.0. wanna get line breaks in there");
     }
+
+    #[test]
+    fn timeline_keeps_ids_on_empty_page() {
+        let core = Core::new().unwrap();
+        let token = auth::Token::Access {
+            consumer: auth::KeyPair::new("consumer", "secret"),
+            access: auth::KeyPair::new("access", "secret"),
+        };
+
+        let handle = core.handle();
+        let mut timeline = Timeline::new(links::statuses::HOME_TIMELINE, None, &token, &handle)
+            .with_since_id(782349500404862976);
+        assert_eq!(timeline.max_id, Some(782349500404862976));
+        assert_eq!(timeline.min_id, None);
+
+        timeline.map_ids(&[]);
+        assert_eq!(timeline.max_id, Some(782349500404862976));
+
+        let sample = load_tweet("src/tweet/sample-reply.json");
+        let id = sample.id;
+        timeline.map_ids(&[sample]);
+        assert_eq!(timeline.max_id, Some(id));
+        assert_eq!(timeline.min_id, Some(id));
+
+        let timeline = timeline.with_max_id(782349500404862976);
+        assert_eq!(timeline.min_id, Some(782349500404862977));
+
+        //id_for_time gives u64::MAX for far-future times, so this shouldn't overflow
+        let timeline = timeline.with_max_id(u64::max_value());
+        assert_eq!(timeline.min_id, Some(u64::max_value()));
+    }
+
+    #[test]
+    fn timeline_state() {
+        use super::TimelineState;
+
+        let core = Core::new().unwrap();
+        let token = auth::Token::Bearer("bearer".to_string());
+        let handle = core.handle();
+
+        let timeline = Timeline::new(links::statuses::HOME_TIMELINE, None, &token, &handle)
+            .with_page_size(50)
+            .with_since_id(782349500404862976);
+
+        let saved = ::serde_json::to_string(&timeline.state()).unwrap();
+        let state: TimelineState = ::serde_json::from_str(&saved).unwrap();
+        assert_eq!(state, TimelineState { count: 50, max_id: Some(782349500404862976), min_id: None });
+
+        let resumed = Timeline::new(links::statuses::HOME_TIMELINE, None, &token, &handle)
+            .resume(state);
+        assert_eq!(resumed.count, 50);
+        assert_eq!(resumed.max_id, Some(782349500404862976));
+        assert_eq!(resumed.min_id, None);
+    }
+
+    #[test]
+    fn time_ids() {
+        use super::id_for_time;
+        use chrono::{Duration, TimeZone, Utc};
+
+        let sample = load_tweet("src/tweet/sample-extended-onepic.json");
+        //created_at only has whole seconds, so the tweet's ID falls somewhere in that second
+        assert!(id_for_time(sample.created_at) <= sample.id);
+        assert!(id_for_time(sample.created_at + Duration::seconds(1)) > sample.id);
+
+        assert_eq!(id_for_time(Utc.ymd(2010, 1, 1).and_hms(0, 0, 0)), 0);
+        assert_eq!(id_for_time(Utc.ymd(2151, 1, 1).and_hms(0, 0, 0)), u64::max_value());
+        assert_eq!(id_for_time(::chrono::MAX_DATE.and_hms(23, 59, 59)), u64::max_value());
+        assert!(id_for_time(Utc.ymd(2149, 1, 1).and_hms(0, 0, 0)) < u64::max_value());
+
+        let core = Core::new().unwrap();
+        let token = auth::Token::Bearer("bearer".to_string());
+        let handle = core.handle();
+
+        let timeline = Timeline::new(links::statuses::HOME_TIMELINE, None, &token, &handle)
+            .with_since_time(sample.created_at)
+            .with_until_time(sample.created_at + Duration::seconds(1));
+        assert!(timeline.max_id.unwrap() < sample.id);
+        assert!(timeline.min_id.unwrap() > sample.id);
+    }
+
+    #[test]
+    fn weighted_length() {
+        use super::{text_length, DraftTweet};
+        use error::Error;
+        use futures::Future;
+
+        assert_eq!(text_length(""), 0);
+        assert_eq!(text_length("hello world"), 11);
+        assert_eq!(text_length("\u{1F600}"), 2);
+        assert_eq!(text_length("https://t.co/abc and www.rust-lang.org"), 23 + 5 + 23);
+
+        let text = "x".repeat(258) + " https://example.com/some/path";
+        assert_eq!(text_length(&text), 282);
+
+        let core = Core::new().unwrap();
+        let token = auth::Token::Bearer("bearer".to_string());
+        let handle = core.handle();
+
+        match DraftTweet::new(text).send(&token, &handle).poll() {
+            Err(Error::TweetTooLong(len)) => assert_eq!(len, 282),
+            Err(e) => panic!("unexpected error for long tweet: {}", e),
+            Ok(_) => panic!("long tweet wasn't rejected"),
+        }
+    }
+
+    #[test]
+    fn parse_oembed() {
+        let embed = OEmbed::from_str(r#"{
+            "url": "https://twitter.com/0xabad1dea/status/782349500404862976",
+            "author_name": "abadidea",
+            "author_url": "https://twitter.com/0xabad1dea",
+            "html": "<blockquote class=\"twitter-tweet\"><p lang=\"en\" dir=\"ltr\">text</p></blockquote>\n",
+            "width": 550,
+            "height": null,
+            "type": "rich",
+            "cache_age": "3153600000",
+            "provider_name": "Twitter",
+            "provider_url": "https://twitter.com",
+            "version": "1.0"
+        }"#).unwrap();
+
+        assert_eq!(embed.author_name, "abadidea");
+        assert!(embed.html.starts_with("<blockquote"));
+        assert_eq!(embed.width, Some(550));
+        assert_eq!(embed.height, None);
+        assert_eq!(embed.cache_age, Some(3153600000));
+        assert_eq!(embed.provider_name, "Twitter");
+        assert_eq!(embed.version, "1.0");
+    }
+
+    #[test]
+    fn parse_coordinates() {
+        use rustc_serialize::json;
+
+        let sample_str = {
+            let mut file = File::open("src/tweet/sample-reply.json").unwrap();
+            let mut ret = String::new();
+            file.read_to_string(&mut ret).unwrap();
+            ret
+        };
+        let mut sample = json::Json::from_str(&sample_str).unwrap();
+
+        //twitter gives geojson coordinates in (longitude, latitude) order
+        if let json::Json::Object(ref mut obj) = sample {
+            obj.insert("coordinates".to_string(), json::Json::from_str(r#"{
+                "type": "Point",
+                "coordinates": [-122.4194, 37.7749]
+            }"#).unwrap());
+        }
+
+        let tweet = Tweet::from_json(&sample).unwrap();
+        assert_eq!(tweet.coordinates, Some((37.7749, -122.4194)));
+    }
 }
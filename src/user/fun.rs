@@ -3,10 +3,12 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use rustc_serialize::base64::{self, ToBase64};
 use common::*;
 use auth;
 use links;
+use hyper::StatusCode;
 use cursor;
 
 use super::*;
@@ -19,6 +21,19 @@ use super::*;
 /// with `u64`, just screen names with `&str` or `String`, or even a mix of both (by using `UserID`
 /// directly).
 ///
+/// Twitter only allows 100 accounts to be looked up at once, so if more than that are given, they
+/// will be split into several calls, and the results will be combined once they all finish. See
+/// [`BatchFuture`] for which call's rate-limit information ends up in the final `Response`, and
+/// for what happens if one of the calls fails. If no accounts are given, the future will complete
+/// immediately with an empty list, without calling Twitter at all.
+///
+/// Accounts that are given more than once are only sent to Twitter once, so they don't take up
+/// room in the groups of 100. Separately, Twitter will leave out any accounts that are suspended
+/// or deleted, so the result may have fewer users than the input even without repeats. If you
+/// need to know which ones are missing, use `lookup_map`.
+///
+/// [`BatchFuture`]: ../struct.BatchFuture.html
+///
 /// ## Examples
 ///
 /// ```rust,no_run
@@ -77,35 +92,183 @@ use super::*;
 /// # }
 /// ```
 pub fn lookup<'a, T, I>(accts: I, token: &auth::Token, handle: &Handle)
-    -> FutureResponse<Vec<TwitterUser>>
+    -> BatchFuture<Vec<TwitterUser>>
     where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
 {
-    let mut params = HashMap::new();
-    let (id_param, name_param) = multiple_names_param(accts);
+    lookup_with(accts, UserOptions::default(), token, handle)
+}
+
+/// Look up profile information for several Twitter users, with the given settings for how much
+/// information to return.
+///
+/// This works the same way as `lookup`, but lets you leave out the users' latest tweets or their
+/// profile entities by handing in a [`UserOptions`].
+///
+/// [`UserOptions`]: struct.UserOptions.html
+pub fn lookup_with<'a, T, I>(accts: I, options: UserOptions, token: &auth::Token, handle: &Handle)
+    -> BatchFuture<Vec<TwitterUser>>
+    where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
+{
+    let accts = dedup_in_order(accts.into_iter().map(|acct| acct.into()));
+    let loaders = accts.chunks(100).map(|chunk| {
+        let mut params = HashMap::new();
+        let (id_param, name_param) = multiple_names_param(chunk);
 
-    add_param(&mut params, "user_id", id_param);
-    add_param(&mut params, "screen_name", name_param);
+        add_param(&mut params, "user_id", id_param);
+        add_param(&mut params, "screen_name", name_param);
+        options.add_params(&mut params);
 
-    let req = auth::post(links::users::LOOKUP, token, Some(&params));
+        let req = auth::get_or_post(links::users::LOOKUP, token, Some(&params));
 
-    make_parsed_future(handle, req)
+        make_parsed_future(handle, req)
+    }).collect();
+
+    make_batch_future(loaders)
+}
+
+/// Look up profile information for several Twitter users, and list the accounts that couldn't be
+/// loaded.
+///
+/// This calls `lookup` with the given accounts, then compares what Twitter returned against what
+/// was asked for. The loaded users are returned in the order they were requested (rather than the
+/// order Twitter returned them), and any account that Twitter left out is listed in `missing`.
+/// Twitter doesn't say why an account was left out, but it's usually because it was suspended or
+/// deleted, or because no account has the given screen name.
+///
+/// Accounts requested by screen name are matched up without regard to case, and an account that
+/// was requested more than once only shows up once in `users`.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use egg_mode::user;
+///
+/// let accts = vec!["rustlang", "ThisWeekInRust", "not_an_account_123"];
+/// let loaded = core.run(user::show_many(accts, &token, &handle)).unwrap();
+///
+/// for user in &loaded.users {
+///     println!("{} (@{})", user.name, user.screen_name);
+/// }
+/// for acct in &loaded.missing {
+///     println!("{} couldn't be loaded", acct);
+/// }
+/// # }
+/// ```
+pub fn show_many<'a, T, I>(accts: I, token: &auth::Token, handle: &Handle) -> ShowManyFuture
+    where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
+{
+    let accts = accts.into_iter().map(|acct| acct.into().into_owned()).collect::<Vec<_>>();
+    let loader = lookup(&accts, token, handle);
+
+    ShowManyFuture::new(accts, loader)
+}
+
+/// Look up profile information for several Twitter users, and return a map indicating which
+/// accounts couldn't be found.
+///
+/// Twitter leaves suspended or deleted accounts out of the response to `lookup`, which makes it
+/// hard to tell which of the requested accounts are missing. `lookup_map` instead gives a map
+/// with an entry for every account you asked for: accounts that could be loaded store `Some` and
+/// their user information, whereas accounts that couldn't be loaded store `None`.
+///
+/// The keys of the map are the `UserID`s you gave, converted to own their screen names (see
+/// `UserID::into_owned`). Accounts requested by screen name are matched up without regard to
+/// case, so the key will keep the spelling you used even if Twitter's is different.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use egg_mode::user::{self, UserID};
+///
+/// let accts: Vec<UserID> = vec![165262228.into(), "ThisWeekInRust".into()];
+/// let users = core.run(user::lookup_map(&accts, &token, &handle)).unwrap();
+///
+/// for (acct, user) in users.response.iter() {
+///     match *user {
+///         Some(ref user) => println!("{}: {} (@{})", acct, user.name, user.screen_name),
+///         None => println!("{}: couldn't be loaded", acct),
+///     }
+/// }
+/// # }
+/// ```
+pub fn lookup_map<'a, T, I>(accts: I, token: &auth::Token, handle: &Handle) -> LookupMapFuture
+    where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
+{
+    let accts = accts.into_iter().map(|acct| acct.into().into_owned()).collect::<Vec<_>>();
+    let loader = lookup(&accts, token, handle);
+
+    LookupMapFuture::new(accts, loader)
 }
 
 /// Lookup user information for a single user.
 pub fn show<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
     -> FutureResponse<TwitterUser>
+{
+    show_with(acct, UserOptions::default(), token, handle)
+}
+
+/// Lookup user information for a single user, with the given settings for how much information
+/// to return.
+///
+/// This works the same way as `show`, but lets you leave out the user's latest tweet or their
+/// profile entities by handing in a [`UserOptions`].
+///
+/// [`UserOptions`]: struct.UserOptions.html
+pub fn show_with<'a, T: Into<UserID<'a>>>(acct: T, options: UserOptions,
+                                          token: &auth::Token, handle: &Handle)
+    -> FutureResponse<TwitterUser>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
+    options.add_params(&mut params);
 
     let req = auth::get(links::users::SHOW, token, Some(&params));
 
     make_parsed_future(handle, req)
 }
 
+/// Lookup the profile banner of the given user.
+///
+/// If the user has not uploaded a profile banner, Twitter responds with a "not found" error. The
+/// future returned by this function turns that into a `None`, so you can tell a missing banner
+/// apart from other errors:
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// let banner = core.run(egg_mode::user::profile_banner("rustlang", &token, &handle)).unwrap();
+///
+/// match banner.response {
+///     Some(banner) => println!("banner: {}", banner.web.url),
+///     None => println!("no banner set"),
+/// }
+/// # }
+/// ```
+pub fn profile_banner<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
+    -> ProfileBannerFuture
+{
+    let mut params = HashMap::new();
+    add_name_param(&mut params, &acct.into());
+
+    let req = auth::get(links::users::PROFILE_BANNER, token, Some(&params));
+
+    ProfileBannerFuture::new(make_future_expecting(handle, req, StatusCode::NotFound,
+                                                    parse_profile_banner))
+}
+
 /// Lookup the user IDs that the authenticating user has disabled retweets from.
 ///
-/// Use `update_follow` to enable/disable viewing retweets from a specific user.
+/// Use `set_retweets_visible` to enable/disable viewing retweets from a specific user.
 pub fn friends_no_retweets(token: &auth::Token, handle: &Handle)
     -> FutureResponse<Vec<u64>>
 {
@@ -114,6 +277,31 @@ pub fn friends_no_retweets(token: &auth::Token, handle: &Handle)
     make_parsed_future(handle, req)
 }
 
+/// Lookup the user IDs that the authenticating user has disabled retweets from, as a set.
+///
+/// This makes the same call as `friends_no_retweets`, but collects the IDs into a `HashSet`, so
+/// you can quickly check whether a given retweet should be hidden:
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// # let tweet: egg_mode::tweet::Tweet = unimplemented!();
+/// let suppressed = core.run(egg_mode::user::friends_no_retweets_set(&token, &handle)).unwrap();
+///
+/// let hide = tweet.retweeted_status.is_some() &&
+///            tweet.user.as_ref().map_or(false, |u| suppressed.contains(&u.id));
+/// # }
+/// ```
+pub fn friends_no_retweets_set(token: &auth::Token, handle: &Handle)
+    -> FutureResponse<HashSet<u64>>
+{
+    let req = auth::get(links::users::FRIENDS_NO_RETWEETS, token, None);
+
+    make_parsed_future(handle, req)
+}
+
 /// Lookup relationship settings between two arbitrary users.
 pub fn relation<'a, F, T>(from: F, to: T, token: &auth::Token, handle: &Handle)
     -> FutureResponse<Relationship>
@@ -136,19 +324,108 @@ pub fn relation<'a, F, T>(from: F, to: T, token: &auth::Token, handle: &Handle)
 }
 
 /// Lookup the relations between the authenticated user and the given accounts.
+///
+/// Twitter can only look up 100 accounts per call, so if more accounts than that are given, they
+/// will be split into several calls, and the results will be combined once they all finish. The
+/// combined list keeps the results of each group of 100 in the order they were given. See
+/// [`BatchFuture`] for details.
+///
+/// If no accounts are given, the future will complete immediately with an empty list, without
+/// calling Twitter at all.
+///
+/// [`BatchFuture`]: ../struct.BatchFuture.html
 pub fn relation_lookup<'a, T, I>(accts: I, token: &auth::Token, handle: &Handle)
-    -> FutureResponse<Vec<RelationLookup>>
+    -> BatchFuture<Vec<RelationLookup>>
+    where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
+{
+    let accts = accts.into_iter().map(|acct| acct.into()).collect::<Vec<UserID>>();
+    let loaders = accts.chunks(100).map(|chunk| {
+        let mut params = HashMap::new();
+        let (id_param, name_param) = multiple_names_param(chunk);
+
+        add_param(&mut params, "user_id", id_param);
+        add_param(&mut params, "screen_name", name_param);
+
+        let req = auth::get(links::users::FRIENDSHIP_LOOKUP, token, Some(&params));
+
+        make_parsed_future(handle, req)
+    }).collect();
+
+    make_batch_future(loaders)
+}
+
+/// Lookup the relations between the authenticated user and the given accounts, and pair each
+/// account with its result.
+///
+/// This calls `relation_lookup` with the given accounts, then matches up each `RelationLookup` with
+/// the account that asked for it, by ID or by screen name. The map has an entry for every account
+/// that was given, using the same `UserID` that was passed in as the key. If Twitter didn't return
+/// a relation for an account (say, because it doesn't exist or has been suspended), its entry will
+/// be `None`. Screen names are matched without regard to case, so `"rustlang"` will still find the
+/// result for `@RustLang`.
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use egg_mode::user::{self, UserID};
+///
+/// let accts: Vec<UserID> = vec![165262228.into(), "ThisWeekInRust".into()];
+/// let relations = core.run(user::relation_lookup_map(&accts, &token, &handle)).unwrap();
+///
+/// for (acct, relation) in relations.response.iter() {
+///     match *relation {
+///         Some(ref rel) => println!("{}: following: {}", acct, rel.following()),
+///         None => println!("{}: not returned", acct),
+///     }
+/// }
+/// # }
+/// ```
+pub fn relation_lookup_map<'a, T, I>(accts: I, token: &auth::Token, handle: &Handle)
+    -> RelationLookupMapFuture
     where T: Into<UserID<'a>>, I: IntoIterator<Item=T>
+{
+    let accts = accts.into_iter().map(|acct| acct.into().into_owned()).collect::<Vec<_>>();
+    let loader = relation_lookup(&accts, token, handle);
+
+    RelationLookupMapFuture::new(accts, loader)
+}
+
+/// Check whether the authenticated user has blocked the given account.
+///
+/// This makes a single call to `friendships/lookup`, rather than paging through all of
+/// `blocks_ids`, so it's the better choice when you only need to check one account. If you need
+/// to check many accounts at once, load the whole list with `blocks_ids` and
+/// `CursorIter::collect_ids` instead. If Twitter can't find the given account, the future returned
+/// by this function yields `false`.
+pub fn is_blocked<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<bool>
 {
     let mut params = HashMap::new();
-    let (id_param, name_param) = multiple_names_param(accts);
+    add_name_param(&mut params, &acct.into());
 
-    add_param(&mut params, "user_id", id_param);
-    add_param(&mut params, "screen_name", name_param);
+    let req = auth::get(links::users::FRIENDSHIP_LOOKUP, token, Some(&params));
+
+    make_future(handle, req, parse_is_blocked)
+}
+
+/// Check whether the authenticated user has muted the given account.
+///
+/// This makes a single call to `friendships/lookup`, rather than paging through all of
+/// `mutes_ids`, so it's the better choice when you only need to check one account. If you need to
+/// check many accounts at once, load the whole list with `mutes_ids` and
+/// `CursorIter::collect_ids` instead. If Twitter can't find the given account, the future returned
+/// by this function yields `false`.
+pub fn is_muted<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<bool>
+{
+    let mut params = HashMap::new();
+    add_name_param(&mut params, &acct.into());
 
     let req = auth::get(links::users::FRIENDSHIP_LOOKUP, token, Some(&params));
 
-    make_parsed_future(handle, req)
+    make_future(handle, req, parse_is_muted)
 }
 
 //---Cursored collections---
@@ -177,6 +454,7 @@ pub fn friends_of<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle:
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
     cursor::CursorIter::new(links::users::FRIENDS_LIST, token, handle, Some(params), Some(20))
+        .max_page_size(200)
 }
 
 /// Lookup the users a given account follows, also called their "friends" within the API, but only
@@ -187,13 +465,15 @@ pub fn friends_of<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle:
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To load the full information for
+/// some of those accounts later, hand their IDs to `lookup`, which loads them 100 at a time.
 pub fn friends_ids<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
     -> cursor::CursorIter<'a, cursor::IDCursor>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
     cursor::CursorIter::new(links::users::FRIENDS_IDS, token, handle, Some(params), Some(500))
+        .max_page_size(5000)
 }
 
 /// Lookup the users that follow a given account.
@@ -206,6 +486,7 @@ pub fn followers_of<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handl
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
     cursor::CursorIter::new(links::users::FOLLOWERS_LIST, token, handle, Some(params), Some(20))
+        .max_page_size(200)
 }
 
 /// Lookup the users that follow a given account, but only return their user IDs.
@@ -215,13 +496,15 @@ pub fn followers_of<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handl
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To load the full information for
+/// some of those accounts later, hand their IDs to `lookup`, which loads them 100 at a time.
 pub fn followers_ids<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
     -> cursor::CursorIter<'a, cursor::IDCursor>
 {
     let mut params = HashMap::new();
     add_name_param(&mut params, &acct.into());
     cursor::CursorIter::new(links::users::FOLLOWERS_IDS, token, handle, Some(params), Some(500))
+        .max_page_size(5000)
 }
 
 /// Lookup the users that have been blocked by the authenticated user.
@@ -241,7 +524,8 @@ pub fn blocks(token: &auth::Token, handle: &Handle)
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To load the full information for
+/// some of those accounts later, hand their IDs to `lookup`, which loads them 100 at a time.
 ///
 /// Note that while loading a user's blocks list is a cursored search, it does not allow you to set
 /// the page size. Calling `with_page_size` on a stream returned by this function will not
@@ -269,7 +553,8 @@ pub fn mutes(token: &auth::Token, handle: &Handle)
 ///
 /// Choosing only to load the user IDs instead of the full user information results in a call that
 /// can return more accounts per-page, which can be useful if you anticipate having to page through
-/// several results and don't need all the user information.
+/// several results and don't need all the user information. To load the full information for
+/// some of those accounts later, hand their IDs to `lookup`, which loads them 100 at a time.
 ///
 /// Note that while loading a user's mutes list is a cursored search, it does not allow you to set
 /// the page size. Calling `with_page_size` on a stream returned by this function will not
@@ -284,6 +569,11 @@ pub fn mutes_ids(token: &auth::Token, handle: &Handle)
 /// Lookup the user IDs who have pending requests to follow the authenticated protected user.
 ///
 /// If the authenticated user is not a protected account, this will return an empty collection.
+///
+/// Twitter does not offer a way to accept or deny these requests through the API; that can only
+/// be done from Twitter's own apps. The IDs given here can still be loaded into full profiles with
+/// `lookup`, or checked against later calls to `followers_ids` to see which requests have been
+/// accepted.
 pub fn incoming_requests(token: &auth::Token, handle: &Handle)
     -> cursor::CursorIter<'static, cursor::IDCursor>
 {
@@ -291,6 +581,12 @@ pub fn incoming_requests(token: &auth::Token, handle: &Handle)
 }
 
 /// Lookup the user IDs with which the authenticating user has a pending follow request.
+///
+/// These requests are the ones sent by calling `follow` with a protected account. To withdraw
+/// one, call `unfollow` with the same account. You can also check on a specific request with
+/// `relation_lookup`, whose results will include `Connection::FollowingRequested` for every
+/// account listed here. Once the request is accepted, that account will show up in `friends_ids`
+/// instead.
 pub fn outgoing_requests(token: &auth::Token, handle: &Handle)
     -> cursor::CursorIter<'static, cursor::IDCursor>
 {
@@ -304,10 +600,20 @@ pub fn outgoing_requests(token: &auth::Token, handle: &Handle)
 ///
 /// Upon success, the future returned by this function yields the user that was just followed, even
 /// when following a protected account. In the latter case, this indicates that the follow request
-/// was successfully sent.
+/// was successfully sent, and the account will be listed by `outgoing_requests` until the request
+/// is accepted or withdrawn.
+///
+/// To tell the two cases apart, check `is_pending_follow` on the returned user: if it's `true`,
+/// the follow request is still waiting on the other account, and the authenticated user doesn't
+/// follow them yet.
 ///
 /// Calling this with an account the user already follows may return an error, or ("for performance
 /// reasons") may return success without changing any account settings.
+///
+/// As with every function that takes a `UserID`, the type of `acct` decides how the account is
+/// sent to Twitter: integers like `165262228` are sent as a numeric user ID, and strings like
+/// `"rustlang"` are sent as a screen name, even if they only contain digits. If you have an ID
+/// stored as text, parse it into a `u64` first, or use `UserID::parse`.
 pub fn follow<'a, T: Into<UserID<'a>>>(acct: T, notifications: bool,
                                        token: &auth::Token, handle: &Handle)
     -> FutureResponse<TwitterUser>
@@ -321,12 +627,43 @@ pub fn follow<'a, T: Into<UserID<'a>>>(acct: T, notifications: bool,
     make_parsed_future(handle, req)
 }
 
+/// Follow the given account with the authenticated user, and apply the given settings for it.
+///
+/// This takes two calls to Twitter. The first follows the account, setting device notifications if
+/// `options.notifications` is given (the same as `follow`). Once that succeeds, the second sends
+/// the given retweet setting with `friendships/update` (the same as `update_follow`), and its
+/// response is what the future returned by this function yields. This second call is made even if
+/// `options.retweets` isn't given, to load the `Relationship`; if you don't need it, use `follow`.
+///
+/// If the account is protected, following it only sends a follow request, and Twitter won't change
+/// any follow settings for it until that request is accepted. In that case, the second call is
+/// made without the retweet setting, and the returned `Relationship` will have
+/// `is_pending_follow` set. You can apply the setting with `set_retweets_visible` once the
+/// account accepts the request.
+///
+/// If the first call fails, its error is returned without making the second call.
+pub fn follow_with<'a, T: Into<UserID<'a>>>(acct: T, options: FollowOptions,
+                                            token: &auth::Token, handle: &Handle)
+    -> FollowWithFuture
+{
+    let mut params = HashMap::new();
+    add_name_param(&mut params, &acct.into());
+    if let Some(notifications) = options.notifications {
+        add_param(&mut params, "follow", notifications.to_string());
+    }
+
+    let req = auth::post(links::users::FOLLOW, token, Some(&params));
+
+    FollowWithFuture::new(make_parsed_future(handle, req), options.retweets, token, handle)
+}
+
 /// Unfollow the given account with the authenticated user.
 ///
 /// Upon success, the future returned by this function yields the user that was just unfollowed.
 ///
 /// Calling this with an account the user doesn't follow will return success, even though it doesn't
-/// change any settings.
+/// change any settings. If the user has a pending follow request with the given account (i.e. it's
+/// listed by `outgoing_requests`), this withdraws that request.
 pub fn unfollow<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Handle)
     -> FutureResponse<TwitterUser>
 {
@@ -340,10 +677,14 @@ pub fn unfollow<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &
 
 /// Update notification settings and reweet visibility for the given user.
 ///
+/// To change just one of these settings, `set_device_notifications` and `set_retweets_visible`
+/// are more direct.
+///
 /// Calling this for an account the authenticated user does not already follow will not cause them
 /// to follow that user. It will return an error if you pass `Some(true)` for `notifications` or
 /// `Some(false)` for `retweets`. Any other combination of arguments will return a `Relationship` as
-/// if you had called `relation` between the authenticated user and the given user.
+/// if you had called `relation` between the authenticated user and the given user. To catch those
+/// errors before calling Twitter, use `update_follow_checked` instead.
 pub fn update_follow<'a, T>(acct: T, notifications: Option<bool>, retweets: Option<bool>,
                             token: &auth::Token, handle: &Handle)
     -> FutureResponse<Relationship>
@@ -363,6 +704,73 @@ pub fn update_follow<'a, T>(acct: T, notifications: Option<bool>, retweets: Opti
     make_parsed_future(handle, req)
 }
 
+/// Update notification settings and retweet visibility for the given user, checking first that
+/// the settings can be applied.
+///
+/// This works like `update_follow`, except that if you pass `Some(true)` for `notifications` or
+/// `Some(false)` for `retweets`, it first looks up whether the authenticated user follows the
+/// given account. If they don't, the future returns `Error::InvalidArgument` with the name of the
+/// argument that can't be used, without sending the update to Twitter. This means an extra call to
+/// `friendships/lookup` (which has its own rate limit), so if you already know the user follows the
+/// account, call `update_follow` directly. Any other combination of arguments is sent right away,
+/// just like `update_follow`.
+pub fn update_follow_checked<'a, T>(acct: T, notifications: Option<bool>, retweets: Option<bool>,
+                                    token: &auth::Token, handle: &Handle)
+    -> UpdateFollowFuture
+    where T: Into<UserID<'a>>
+{
+    let acct = acct.into();
+
+    let check = if notifications == Some(true) || retweets == Some(false) {
+        let mut params = HashMap::new();
+        add_name_param(&mut params, &acct);
+
+        let req = auth::get(links::users::FRIENDSHIP_LOOKUP, token, Some(&params));
+
+        Some(make_future(handle, req, parse_is_following))
+    } else {
+        None
+    };
+
+    let update = update_follow(acct, notifications, retweets, token, handle);
+
+    UpdateFollowFuture::new(check, notifications, retweets, update)
+}
+
+/// Set whether retweets from the given account appear in the authenticated user's timeline.
+///
+/// This only changes the retweets setting, leaving device notifications alone. The authenticated
+/// user must already follow the given account to hide their retweets; calling this with `false` for
+/// an account they don't follow will return an error. Calling it with `true` for such an account
+/// succeeds without changing anything.
+///
+/// Upon success, the future returned by this function yields the updated `Relationship` between
+/// the authenticated user and the given account. The accounts whose retweets are hidden can be
+/// loaded with `friends_no_retweets`.
+pub fn set_retweets_visible<'a, T: Into<UserID<'a>>>(acct: T, visible: bool, token: &auth::Token,
+                                                      handle: &Handle)
+    -> FutureResponse<Relationship>
+{
+    update_follow(acct, None, Some(visible), token, handle)
+}
+
+/// Set whether the authenticated user receives device notifications for new tweets from the given
+/// account.
+///
+/// This only changes the device notifications setting, leaving retweet visibility alone. The
+/// authenticated user must already follow the given account to turn notifications on; calling
+/// this with `true` for an account they don't follow will return an error. Calling it with `false`
+/// for such an account succeeds without changing anything.
+///
+/// Upon success, the future returned by this function yields the updated `Relationship` between
+/// the authenticated user and the given account.
+pub fn set_device_notifications<'a, T: Into<UserID<'a>>>(acct: T, on: bool, token: &auth::Token,
+                                                          handle: &Handle)
+    -> FutureResponse<Relationship>
+{
+    update_follow(acct, Some(on), None, token, handle)
+}
+
 /// Block the given account with the authenticated user.
 ///
 /// Upon success, the future returned by this function yields the given user.
@@ -432,3 +840,63 @@ pub fn unmute<'a, T: Into<UserID<'a>>>(acct: T, token: &auth::Token, handle: &Ha
 
     make_parsed_future(handle, req)
 }
+
+/// Remove the authenticated user's profile banner.
+///
+/// Twitter doesn't send anything back from this call, so upon success, the future returned by this
+/// function only yields the rate-limit information. Calling this when the user has no banner
+/// succeeds without changing anything.
+pub fn remove_profile_banner(token: &auth::Token, handle: &Handle) -> FutureResponse<()> {
+    let req = auth::post(links::users::REMOVE_PROFILE_BANNER, token, None);
+
+    make_future(handle, req, parse_empty_response)
+}
+
+/// Set the location that Twitter shows trends for to the authenticated user.
+///
+/// `woeid` is the Yahoo! "where on earth" ID of the location, like `1` for worldwide trends or
+/// `23424977` for the United States. egg-mode doesn't check the ID before sending it; if Twitter
+/// doesn't show trends for the given location, the future returned by this function will return
+/// the error Twitter sends back.
+///
+/// Upon success, the future yields the user's updated account settings.
+pub fn set_trend_location(woeid: i64, token: &auth::Token, handle: &Handle)
+    -> FutureResponse<AccountSettings>
+{
+    let mut params = HashMap::new();
+    add_param(&mut params, "trend_location_woeid", woeid.to_string());
+
+    let req = auth::post(links::users::SETTINGS, token, Some(&params));
+
+    make_parsed_future(handle, req)
+}
+
+/// Upload the given image as the authenticated user's profile background.
+///
+/// The image should be a GIF, JPEG, or PNG, under 800KB in size; Twitter will scale down images
+/// wider than 2048 pixels. Set `tile` to `true` to repeat the image across the background instead
+/// of showing it once, and set `use_image` to `false` to upload the image without turning it on,
+/// to use the plain `profile_background_color` instead.
+///
+/// Upon success, the future returned by this function yields the updated user. Twitter doesn't
+/// always send the user back from this call, in which case the future yields `None`.
+pub fn update_profile_background_image(image: &[u8], tile: bool, use_image: bool,
+                                       token: &auth::Token, handle: &Handle)
+    -> FutureResponse<Option<TwitterUser>>
+{
+    let config = base64::Config {
+        char_set: base64::CharacterSet::Standard,
+        newline: base64::Newline::LF,
+        pad: true,
+        line_length: None,
+    };
+
+    let mut params = HashMap::new();
+    add_param(&mut params, "image", image.to_base64(config));
+    add_param(&mut params, "tile", tile.to_string());
+    add_param(&mut params, "use", use_image.to_string());
+
+    let req = auth::post(links::users::UPDATE_PROFILE_BACKGROUND_IMAGE, token, Some(&params));
+
+    make_future(handle, req, parse_profile_update)
+}
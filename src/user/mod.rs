@@ -19,7 +19,27 @@
 //! - `TwitterUser`/`UserEntities`/`UserEntityDetail`: returned by many functions in this module,
 //!   these types (`TwitterUser` contains the other two) describe the content of a user's profile,
 //!   and a handful of settings relating to how their profile is displayed.
+//! - `WithheldScope`: part of `TwitterUser` (and `Tweet`), this says whether the withheld
+//!   content is a single tweet or the whole account.
+//! - `UserOptions`: given to `show_with` and `lookup_with`, this sets how much information is
+//!   returned with each user.
+//! - `FollowOptions`/`FollowWithFuture`: given to and returned by `follow_with`, these follow an
+//!   account and apply its notification and retweet settings in one go.
 //! - `UserSearch`: returned by `search`, this is a stream of search results.
+//! - `ProfileBanner`/`BannerSize`: returned by `profile_banner`, these types hold the URLs to a
+//!   user's profile banner at various sizes.
+//! - `AccountSettings`/`TrendLocation`: returned by `set_trend_location`, these types hold the
+//!   authenticated user's account settings.
+//! - `LookupMapFuture`: returned by `lookup_map`, this resolves to a map from each requested
+//!   account to its `TwitterUser`, if it could be loaded.
+//! - `RelationLookupMapFuture`: returned by `relation_lookup_map`, this resolves to a map from
+//!   each requested account to its `RelationLookup`, if Twitter returned one.
+//! - `UpdateFollowFuture`: returned by `update_follow_checked`, this checks that the
+//!   authenticated user follows an account before changing their follow settings for it.
+//! - `ShowManyFuture`/`ShowMany`: returned by `show_many`, these types hold the users that could
+//!   be loaded, in the order they were requested, along with the accounts that couldn't.
+//! - `SnapshotDiff`: compares two sets of user IDs (say, from `followers_ids` on different days)
+//!   to see which accounts were added or removed.
 //!
 //! ## Functions
 //!
@@ -29,18 +49,23 @@
 //! request write access to authenticated accounts.
 //!
 //! - `block`/`report_spam`/`unblock`
-//! - `follow`/`unfollow`/`update_follow`
+//! - `follow`/`follow_with`/`unfollow`/`update_follow`/`update_follow_checked`
+//! - `set_retweets_visible`/`set_device_notifications`
 //! - `mute`/`unmute`
+//! - `update_profile_background_image`/`remove_profile_banner`
+//! - `set_trend_location`
 //!
 //! ### Direct lookup
 //!
 //! These functions return single users, or groups of users without having to iterate over the
 //! results.
 //!
-//! - `show`
-//! - `lookup`/`lookup_ids`/`lookup_names`
-//! - `friends_no_retweets`
-//! - `relation`/`relation_lookup`
+//! - `show`/`show_with`/`show_many`
+//! - `lookup`/`lookup_with`/`lookup_map`/`lookup_ids`/`lookup_names`
+//! - `profile_banner`
+//! - `friends_no_retweets`/`friends_no_retweets_set`
+//! - `relation`/`relation_lookup`/`relation_lookup_map`
+//! - `is_blocked`/`is_muted`
 //!
 //! ### Cursored lookup
 //!
@@ -56,7 +81,9 @@
 //! - `incoming_requests`/`outgoing_requests`
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use futures::{Future, Stream, Poll, Async};
 use rustc_serialize::json;
@@ -85,18 +112,99 @@ pub use self::fun::*;
 /// * `&u64` (convenient when used with iterators)
 /// * `&str`
 /// * `&&str` (convenient when used with iterators)
+/// * `String`
 /// * `&String` (to counteract the fact that deref coercion doesn't work with generics)
 /// * `&UserID` (convenient when used with iterators)
 ///
+/// Note that `u64` is the only integer type that converts into `UserID`. Supporting more than one
+/// would mean that plain integer literals (like in `user::show(165262228, ...)`) would no longer
+/// know which type to be. If you have IDs stored as another type (like `u32` or `i64`), convert
+/// them with `as u64` first.
+///
+/// Screen names are stored as a `Cow<str>`, so a `UserID` can either borrow its screen name or
+/// own it outright. Converting from a `String` will move it into the `UserID`, so you can hand in
+/// names you built at runtime without having to keep them around separately.
+///
 /// This way, when a function in egg-mode has a paremeter of type `T: Into<UserID<'a>>`, you can
 /// call it with any of these types, and it will be converted automatically. egg-mode will then use
 /// the proper parameter when performing the call to Twitter.
-#[derive(Debug, Copy, Clone)]
+///
+/// When converting from text, a leading `@` is stripped off, so `"@rustlang"` and `"rustlang"`
+/// both refer to the same account. Screen names are otherwise kept as given: Twitter treats them
+/// without regard to case, so `"RustLang"` refers to that account too, but the `UserID` will still
+/// say `"RustLang"`. (This also means that two `UserID`s whose names only differ in case won't
+/// compare equal. Use `matches` to check a `UserID` against a user the same way Twitter does.)
+///
+/// If you're taking user names from somewhere that could contain either form (like a command-line
+/// argument), `UserID::parse` will sort them out for you, and the `Display` implementation will
+/// write them back out the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum UserID<'a> {
     /// Referring via the account's numeric ID.
     ID(u64),
     /// Referring via the account's screen name.
-    ScreenName(&'a str),
+    ScreenName(Cow<'a, str>),
+}
+
+impl<'a> UserID<'a> {
+    /// Parses the given text as either a screen name or a numeric ID.
+    ///
+    /// Text with a leading `@` is always treated as a screen name, with the `@` stripped off. Text
+    /// that is entirely a number is treated as a numeric ID, and anything else is treated as a
+    /// screen name as-is. This means that if you want to refer to an account whose screen name is
+    /// entirely digits, you need to give the leading `@`.
+    ///
+    /// (This isn't a `FromStr` implementation because `ScreenName` borrows its text, and
+    /// `FromStr` doesn't allow the result to borrow from its input.)
+    pub fn parse(input: &'a str) -> UserID<'a> {
+        if input.starts_with('@') {
+            UserID::ScreenName(Cow::Borrowed(&input[1..]))
+        } else if let Ok(id) = input.parse::<u64>() {
+            UserID::ID(id)
+        } else {
+            UserID::ScreenName(Cow::Borrowed(input))
+        }
+    }
+
+    /// Converts this `UserID` into one that owns its screen name, if it has one.
+    ///
+    /// This is useful if you need to keep a `UserID` around longer than the text it was made
+    /// from, like when storing it as a key in a map.
+    pub fn into_owned(self) -> UserID<'static> {
+        match self {
+            UserID::ID(id) => UserID::ID(id),
+            UserID::ScreenName(name) => UserID::ScreenName(Cow::Owned(name.into_owned())),
+        }
+    }
+
+    /// Returns whether this `UserID` refers to the given user.
+    ///
+    /// Screen names are compared without regard to case, the same way Twitter treats them.
+    pub fn matches(&self, user: &TwitterUser) -> bool {
+        match *self {
+            UserID::ID(id) => user.id == id,
+            UserID::ScreenName(ref name) => user.screen_name.eq_ignore_ascii_case(name),
+        }
+    }
+
+    /// Returns whether this `UserID` refers to the account in the given `RelationLookup`.
+    fn matches_relation(&self, relation: &RelationLookup) -> bool {
+        match *self {
+            UserID::ID(id) => relation.id == id,
+            UserID::ScreenName(ref name) => relation.screen_name.eq_ignore_ascii_case(name),
+        }
+    }
+}
+
+/// `Display` impl that writes numeric IDs as the plain number, and screen names with a leading
+/// `@`. The result can be handed back to `UserID::parse` to get the same `UserID`.
+impl<'a> fmt::Display for UserID<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UserID::ID(id) => write!(f, "{}", id),
+            UserID::ScreenName(ref name) => write!(f, "@{}", name),
+        }
+    }
 }
 
 impl<'a> From<u64> for UserID<'a> {
@@ -111,27 +219,45 @@ impl<'a> From<&'a u64> for UserID<'a> {
     }
 }
 
+/// Strips the leading `@` off the given screen name, if it has one.
+fn trim_at(name: &str) -> &str {
+    if name.starts_with('@') {
+        &name[1..]
+    } else {
+        name
+    }
+}
+
 impl<'a> From<&'a str> for UserID<'a> {
     fn from(name: &'a str) -> UserID<'a> {
-        UserID::ScreenName(name)
+        UserID::ScreenName(Cow::Borrowed(trim_at(name)))
     }
 }
 
 impl<'a, 'b> From<&'b &'a str> for UserID<'a> {
     fn from(name: &'b &'a str) -> UserID<'a> {
-        UserID::ScreenName(*name)
+        UserID::ScreenName(Cow::Borrowed(trim_at(*name)))
+    }
+}
+
+impl<'a> From<String> for UserID<'a> {
+    fn from(mut name: String) -> UserID<'a> {
+        if name.starts_with('@') {
+            name.remove(0);
+        }
+        UserID::ScreenName(Cow::Owned(name))
     }
 }
 
 impl<'a> From<&'a String> for UserID<'a> {
     fn from(name: &'a String) -> UserID<'a> {
-        UserID::ScreenName(name.as_str())
+        UserID::ScreenName(Cow::Borrowed(trim_at(name.as_str())))
     }
 }
 
 impl<'a> From<&'a UserID<'a>> for UserID<'a> {
     fn from(id: &'a UserID<'a>) -> UserID<'a> {
-        *id
+        id.clone()
     }
 }
 
@@ -186,7 +312,15 @@ impl<'a> From<&'a UserID<'a>> for UserID<'a> {
 /// * `show_all_inline_media`
 /// * `time_zone`/`utc_offset`
 /// * `withheld_in_countries`/`withheld_scope`
-#[derive(Debug, Clone)]
+///
+/// ## Serialization
+///
+/// `TwitterUser` implements serde's `Serialize` and `Deserialize`, so you can save users to disk
+/// and load them back later without calling Twitter again. Note that the format this uses is
+/// egg-mode's own representation of the struct, not the JSON that Twitter returns, so you can't
+/// use `Deserialize` to parse a raw API response. The `status` field is not included when
+/// serializing, and will be `None` when a user is loaded back in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TwitterUser {
     /// Indicates this user has an account with "contributor mode" enabled, allowing
     /// for Tweets issued by the user to be co-authored by another account. Rarely `true`.
@@ -195,20 +329,33 @@ pub struct TwitterUser {
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// When true, indicates that this user has not altered the theme or background of
     /// their user profile.
+    ///
+    /// Along with `default_profile_image`, this is a common signal for new or automated accounts.
     pub default_profile: bool,
     /// When true, indicates that the user has not uploaded their own avatar and a default
-    /// egg avatar is used instead.
+    /// egg avatar is used instead. See also `has_custom_avatar`.
     pub default_profile_image: bool,
     /// The user-defined string describing their account.
     pub description: Option<String>,
+    /// The email address the user has registered with Twitter, if they have one and your app has
+    /// been given permission to see it.
+    ///
+    /// This is only ever filled in by `auth::verify_tokens_with_email`; every other call leaves
+    /// it as `None`. Twitter also doesn't send it for accounts without a confirmed address.
+    #[serde(default)]
+    pub email: Option<String>,
     /// Link information that has been parsed out of the `url` or `description` fields given by the
     /// user.
     pub entities: UserEntities,
     /// The number of tweets this user has favorited or liked in the account's lifetime.
     /// The term "favourites" and its British spelling are used for historical reasons.
-    pub favourites_count: i32,
+    pub favourites_count: i64,
     /// When true, indicates that the authenticating user has issued a follow request to
     /// this protected account.
+    ///
+    /// This is how `follow` signals that it sent a follow request instead of following the
+    /// account outright: the returned user will have this set, even though the authenticated user
+    /// doesn't follow them yet. See `is_pending_follow`.
     pub follow_request_sent: Option<bool>,
     /// Indicates whether the authenticating user is following this account. Deprecated
     /// (and thus hidden) due to increasing error conditions where this returns None.
@@ -216,11 +363,11 @@ pub struct TwitterUser {
     /// The number of followers this account has.
     ///
     /// In certain server-stress conditions, this may temporarily mistakenly return 0.
-    pub followers_count: i32,
+    pub followers_count: i64,
     /// The number of users this account follows, aka its "followings".
     ///
     /// In certain server-stress conditions, this may temporarily mistakenly return 0.
-    pub friends_count: i32,
+    pub friends_count: i64,
     /// Indicates whether this user as enabled their tweets to be geotagged.
     ///
     /// If this is set for the current user, then they can attach geographic data when
@@ -235,10 +382,14 @@ pub struct TwitterUser {
     /// Codes are formatted as a language tag from [BCP 47][]. Only indicates the user's
     /// interface language, not necessarily the content of their Tweets.
     ///
+    /// egg-mode normalizes this code to lowercase, with subtags separated by `-` (like `"en-gb"`).
+    /// If Twitter doesn't send a language for this user, this is an empty string. To check whether
+    /// the language is written right-to-left, use `is_rtl_language`.
+    ///
     /// [BCP 47]: https://tools.ietf.org/html/bcp47
     pub lang: String,
     /// The number of public lists the user is a member of.
-    pub listed_count: i32,
+    pub listed_count: i64,
     /// The user-entered location field from their profile. Not necessarily parseable
     /// or even a location.
     pub location: Option<String>,
@@ -310,25 +461,37 @@ pub struct TwitterUser {
     /// [may not be completely reliable][stale-embed] in this embed.
     ///
     /// [stale-embed]: https://dev.twitter.com/docs/faq/basics/why-are-embedded-objects-stale-or-inaccurate
+    #[serde(skip)]
     pub status: Option<Box<tweet::Tweet>>,
     /// The number of tweets (including retweets) posted by this user.
-    pub statuses_count: i32,
+    pub statuses_count: i64,
     /// The full name of the time zone the user has set their UI preference to.
+    ///
+    /// This is `None` if the user hasn't set a time zone, or if Twitter has hidden it.
     pub time_zone: Option<String>,
     /// The website link given by this user in their profile.
     pub url: Option<String>,
-    /// The UTC offset of `time_zone` in minutes.
+    /// The UTC offset of `time_zone` in seconds.
+    ///
+    /// As with `time_zone`, this is `None` if the user hasn't set a time zone, or if Twitter has
+    /// hidden it.
     pub utc_offset: Option<i32>,
     /// Indicates whether this user is a verified account.
     pub verified: bool,
-    /// When present, lists the countries this user has been withheld from.
+    /// When present, lists the countries this user has been withheld from, as two-letter country
+    /// codes.
+    ///
+    /// Twitter also uses two special codes here: `"XX"` means the content is withheld in all
+    /// countries, and `"XY"` means it's withheld due to a DMCA request. `is_withheld_in` takes
+    /// these into account.
     pub withheld_in_countries: Option<Vec<String>>,
-    /// When present, indicates whether the content being withheld is a "status" or "user".
-    pub withheld_scope: Option<String>,
+    /// When present, indicates whether the content being withheld is a single tweet or the whole
+    /// account.
+    pub withheld_scope: Option<WithheldScope>,
 }
 
 /// Container for URL entity information that may be paired with a user's profile.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserEntities {
     /// URL information that has been parsed out of the user's `description`. If no URLs were
     /// detected, then the contained Vec will be empty.
@@ -342,7 +505,7 @@ pub struct UserEntities {
 }
 
 /// Represents a collection of URL entity information paired with a specific user profile field.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserEntityDetail {
     /// Collection of URL entity information.
     ///
@@ -351,13 +514,281 @@ pub struct UserEntityDetail {
     pub urls: Vec<entities::UrlEntity>,
 }
 
+impl TwitterUser {
+    /// Compares two users by when their accounts were made, oldest first, for sorting users
+    /// chronologically.
+    ///
+    /// This compares the parsed `created_at` timestamps rather than Twitter's date text, and orders
+    /// accounts made in the same second by ID. Hand it to `sort_by`, like
+    /// `users.sort_by(TwitterUser::cmp_created_at)`, or swap the arguments to sort newest first.
+    ///
+    /// (As with `Tweet::cmp_created_at`, this isn't an `Ord` implementation, since `Ord` needs
+    /// `Eq`, and two copies of the same user can differ in fields like `followers_count`.)
+    pub fn cmp_created_at(a: &TwitterUser, b: &TwitterUser) -> cmp::Ordering {
+        (a.created_at, a.id).cmp(&(b.created_at, b.id))
+    }
+
+    /// Returns whether this user's profile is withheld in the given country, given as a two-letter
+    /// country code like `"DE"`.
+    ///
+    /// This checks `withheld_in_countries`, counting the special `"XX"` code as "withheld in every
+    /// country". Country codes are compared without regard to case.
+    pub fn is_withheld_in(&self, country: &str) -> bool {
+        match self.withheld_in_countries {
+            Some(ref countries) => countries.iter().any(|c| c == "XX" ||
+                                                           c.eq_ignore_ascii_case(country)),
+            None => false,
+        }
+    }
+
+    /// Estimates how many pages `followers_of` or `followers_ids` will load for this user, with
+    /// the given page size.
+    ///
+    /// This is based on `followers_count`, so it's only an estimate: the count can be out of date,
+    /// and Twitter may return fewer results per page than asked for. Returns `None` if the count is
+    /// zero (which Twitter sometimes sends by mistake) or if `page_size` is less than 1.
+    ///
+    /// ```rust,no_run
+    /// # extern crate egg_mode; extern crate tokio_core;
+    /// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+    /// # fn main() {
+    /// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+    /// let user = core.run(egg_mode::user::show("rustlang", &token, &handle)).unwrap();
+    ///
+    /// if let Some(pages) = user.follower_pages(5000) {
+    ///     println!("loading about {} pages of followers", pages);
+    /// }
+    ///
+    /// let followers = egg_mode::user::followers_ids("rustlang", &token, &handle)
+    ///                     .with_page_size(5000);
+    /// # }
+    /// ```
+    pub fn follower_pages(&self, page_size: i32) -> Option<usize> {
+        estimate_pages(self.followers_count, page_size)
+    }
+
+    /// Estimates how many pages `friends_of` or `friends_ids` will load for this user, with the
+    /// given page size.
+    ///
+    /// As with `follower_pages`, this is based on `friends_count`, so it's only an estimate.
+    /// Returns `None` if the count is zero or if `page_size` is less than 1.
+    pub fn friend_pages(&self, page_size: i32) -> Option<usize> {
+        estimate_pages(self.friends_count, page_size)
+    }
+
+    /// Returns whether the authenticated user has sent a follow request to this user that they
+    /// haven't accepted yet.
+    ///
+    /// When `follow` is called on a protected account, Twitter returns success and sets this flag,
+    /// rather than following the account right away. This is a shortcut for checking
+    /// `follow_request_sent`, treating a missing flag as `false`.
+    pub fn is_pending_follow(&self) -> bool {
+        self.follow_request_sent.unwrap_or(false)
+    }
+
+    /// Returns whether this user has uploaded their own avatar, rather than using Twitter's
+    /// default one.
+    ///
+    /// This checks `default_profile_image`, as well as whether `profile_image_url_https` points
+    /// to one of Twitter's default avatar images, in case the flag is out of date.
+    pub fn has_custom_avatar(&self) -> bool {
+        !self.default_profile_image &&
+            !self.profile_image_url_https.contains("/default_profile_images/")
+    }
+
+    /// Parses `profile_background_color` into its red, green, and blue components.
+    ///
+    /// Like the other `*_rgb` methods, this accepts both six-digit (`"1DA1F2"`) and three-digit
+    /// (`"333"`) hex colors, with or without a leading `#`. Returns `None` if the color
+    /// couldn't be parsed.
+    pub fn profile_background_rgb(&self) -> Option<(u8, u8, u8)> {
+        parse_hex_color(&self.profile_background_color)
+    }
+
+    /// Parses `profile_link_color` into its red, green, and blue components.
+    pub fn profile_link_rgb(&self) -> Option<(u8, u8, u8)> {
+        parse_hex_color(&self.profile_link_color)
+    }
+
+    /// Parses `profile_sidebar_border_color` into its red, green, and blue components.
+    pub fn profile_sidebar_border_rgb(&self) -> Option<(u8, u8, u8)> {
+        parse_hex_color(&self.profile_sidebar_border_color)
+    }
+
+    /// Parses `profile_sidebar_fill_color` into its red, green, and blue components.
+    pub fn profile_sidebar_fill_rgb(&self) -> Option<(u8, u8, u8)> {
+        parse_hex_color(&self.profile_sidebar_fill_color)
+    }
+
+    /// Parses `profile_text_color` into its red, green, and blue components.
+    pub fn profile_text_rgb(&self) -> Option<(u8, u8, u8)> {
+        parse_hex_color(&self.profile_text_color)
+    }
+
+    /// Returns whether this user's interface language (given by `lang`) is written right-to-left,
+    /// like Arabic or Hebrew.
+    ///
+    /// This can be used to lay out text the user has written, like their `description`, though
+    /// keep in mind that the user's interface language doesn't necessarily match the language they
+    /// write in.
+    pub fn is_rtl_language(&self) -> bool {
+        let primary = self.lang.split('-').next().unwrap_or("");
+
+        RTL_LANGUAGES.contains(&primary)
+    }
+
+    /// Returns a URL to the "bigger" (73px by 73px) version of the user's avatar.
+    ///
+    /// This is based on `profile_image_url`, so it will use the same protocol as that field. If
+    /// the URL doesn't have the usual `_normal` size specifier, it is returned unchanged.
+    pub fn profile_image_url_bigger(&self) -> String {
+        resize_profile_image(&self.profile_image_url, "_bigger")
+    }
+
+    /// Returns a URL to the "mini" (24px by 24px) version of the user's avatar.
+    ///
+    /// This is based on `profile_image_url`, so it will use the same protocol as that field. If
+    /// the URL doesn't have the usual `_normal` size specifier, it is returned unchanged.
+    pub fn profile_image_url_mini(&self) -> String {
+        resize_profile_image(&self.profile_image_url, "_mini")
+    }
+
+    /// Returns a URL to the original, full-size version of the user's avatar.
+    ///
+    /// This is based on `profile_image_url`, so it will use the same protocol as that field. If
+    /// the URL doesn't have the usual `_normal` size specifier, it is returned unchanged.
+    pub fn profile_image_url_original(&self) -> String {
+        resize_profile_image(&self.profile_image_url, "")
+    }
+}
+
+/// Primary language subtags for languages that are written right-to-left. `iw` is the old code
+/// for Hebrew, which Twitter used for some time.
+const RTL_LANGUAGES: &'static [&'static str] = &["ar", "dv", "fa", "he", "iw", "ps", "sd", "ug",
+                                                  "ur", "yi"];
+
+/// Parses a hex color like `"1DA1F2"` or `"#333"` into its red, green, and blue components.
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().trim_left_matches('#');
+    if !hex.chars().all(|c| c.is_digit(16)) {
+        return None;
+    }
+
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+
+    match hex.len() {
+        6 => match (channel(&hex[0..2]), channel(&hex[2..4]), channel(&hex[4..6])) {
+            (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+            _ => None,
+        },
+        3 => {
+            let mut rgb = hex.chars().map(|c| c.to_digit(16).unwrap() as u8 * 0x11);
+            match (rgb.next(), rgb.next(), rgb.next()) {
+                (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Divides the given count into pages of the given size, rounding up.
+fn estimate_pages(count: i64, page_size: i32) -> Option<usize> {
+    if count <= 0 || page_size < 1 {
+        return None;
+    }
+
+    let page_size = page_size as i64;
+    Some(((count + page_size - 1) / page_size) as usize)
+}
+
+/// Normalizes a language code from Twitter into lowercase, with `-` between subtags.
+fn normalize_lang(lang: Option<String>) -> String {
+    lang.map(|lang| lang.trim().to_lowercase().replace('_', "-")).unwrap_or_default()
+}
+
+/// Swaps out the `_normal` size specifier in the given avatar URL for the given one.
+///
+/// Twitter's avatar URLs look like `.../profile_images/[id]/[name]_normal.[ext]`, so this looks
+/// for `_normal` right before the extension (or the end of the URL, if there's no extension) in
+/// the last path segment. If it isn't there, the URL is returned as-is.
+fn resize_profile_image(url: &str, size: &str) -> String {
+    let name_start = url.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let name_end = url[name_start..].rfind('.').map(|i| name_start + i).unwrap_or(url.len());
+
+    if url[name_start..name_end].ends_with("_normal") {
+        let suffix_start = name_end - "_normal".len();
+        format!("{}{}{}", &url[..suffix_start], size, &url[name_end..])
+    } else {
+        url.to_string()
+    }
+}
+
+/// Parses the response from a profile update, which is usually the updated user, but can also be
+/// an empty body. In the latter case, this returns `None`.
+fn parse_profile_update(full_resp: String, headers: &Headers)
+    -> Result<Response<Option<TwitterUser>>, error::Error>
+{
+    if full_resp.trim().is_empty() {
+        return Ok(Response::map(rate_headers(headers), |_| None));
+    }
+
+    make_response(full_resp, headers)
+}
+
+/// Parses the response from `friendships/lookup` for a single account, and returns whether that
+/// account has the given `Connection` to the authenticated user. If Twitter couldn't find the
+/// account, this returns `false`.
+fn parse_connection(full_resp: String, headers: &Headers, connection: Connection)
+    -> Result<Response<bool>, error::Error>
+{
+    let relations: Response<Vec<RelationLookup>> = try!(make_response(full_resp, headers));
+
+    Ok(Response::map(relations, |rels| {
+        rels.iter().any(|rel| rel.connections.contains(&connection))
+    }))
+}
+
+fn parse_is_blocked(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
+    parse_connection(full_resp, headers, Connection::Blocking)
+}
+
+fn parse_is_muted(full_resp: String, headers: &Headers) -> Result<Response<bool>, error::Error> {
+    parse_connection(full_resp, headers, Connection::Muting)
+}
+
+fn parse_is_following(full_resp: String, headers: &Headers)
+    -> Result<Response<bool>, error::Error>
+{
+    parse_connection(full_resp, headers, Connection::Following)
+}
+
+/// Checks whether the given `update_follow` settings can be applied to an account, depending on
+/// whether the authenticated user follows it.
+fn check_follow_update(following: bool, notifications: Option<bool>, retweets: Option<bool>)
+    -> Result<(), error::Error>
+{
+    if following {
+        Ok(())
+    } else if notifications == Some(true) {
+        Err(error::Error::InvalidArgument("notifications",
+                                          "can't turn on notifications for an account the \
+                                           authenticated user doesn't follow"))
+    } else if retweets == Some(false) {
+        Err(error::Error::InvalidArgument("retweets",
+                                          "can't hide retweets from an account the authenticated \
+                                           user doesn't follow"))
+    } else {
+        Ok(())
+    }
+}
+
 impl FromJson for TwitterUser {
     fn from_json(input: &json::Json) -> Result<Self, error::Error> {
         if !input.is_object() {
             return Err(InvalidResponse("TwitterUser received json that wasn't an object", Some(input.to_string())));
         }
 
-        field_present!(input, contributors_enabled);
         field_present!(input, created_at);
         field_present!(input, default_profile);
         field_present!(input, default_profile_image);
@@ -367,7 +798,6 @@ impl FromJson for TwitterUser {
         field_present!(input, geo_enabled);
         field_present!(input, id);
         field_present!(input, is_translator);
-        field_present!(input, lang);
         field_present!(input, listed_count);
         field_present!(input, name);
         field_present!(input, profile_background_color);
@@ -405,6 +835,7 @@ impl FromJson for TwitterUser {
             default_profile: try!(field(input, "default_profile")),
             default_profile_image: try!(field(input, "default_profile_image")),
             description: description,
+            email: try!(field(input, "email")),
             entities: entities,
             favourites_count: try!(field(input, "favourites_count")),
             follow_request_sent: try!(field(input, "follow_request_sent")),
@@ -414,7 +845,7 @@ impl FromJson for TwitterUser {
             geo_enabled: try!(field(input, "geo_enabled")),
             id: try!(field(input, "id")),
             is_translator: try!(field(input, "is_translator")),
-            lang: try!(field(input, "lang")),
+            lang: normalize_lang(try!(field(input, "lang"))),
             listed_count: try!(field(input, "listed_count")),
             location: try!(field(input, "location")),
             name: try!(field(input, "name")),
@@ -463,15 +894,706 @@ impl FromJson for UserEntities {
     }
 }
 
-impl FromJson for UserEntityDetail {
-    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
-        if !input.is_object() {
-            return Err(InvalidResponse("UserEntityDetail received json that wasn't an object", Some(input.to_string())));
+impl FromJson for UserEntityDetail {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("UserEntityDetail received json that wasn't an object", Some(input.to_string())));
+        }
+
+        Ok(UserEntityDetail {
+            urls: try!(field(input, "urls")),
+        })
+    }
+}
+
+/// Represents what content is being withheld, as given in the `withheld_scope` of a `TwitterUser`
+/// or a `Tweet`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WithheldScope {
+    /// A single tweet is being withheld.
+    Status,
+    /// The whole account is being withheld.
+    User,
+    /// Twitter gave a scope that egg-mode doesn't recognize. The enclosed value is the scope as
+    /// Twitter gave it.
+    Unknown(String),
+}
+
+impl FromJson for WithheldScope {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        match input.as_string() {
+            Some("status") => Ok(WithheldScope::Status),
+            Some("user") => Ok(WithheldScope::User),
+            Some(other) => Ok(WithheldScope::Unknown(other.to_string())),
+            None => Err(InvalidResponse("WithheldScope received json that wasn't a string",
+                                        Some(input.to_string()))),
+        }
+    }
+}
+
+/// Represents the sizes of a user's profile banner, as returned by `profile_banner`.
+///
+/// Each field holds the URL and dimensions of the banner at a size meant for a given platform.
+/// Twitter has added sizes to this list over time, so the sizes named by their dimensions may not
+/// be present for every user.
+#[derive(Debug, Clone)]
+pub struct ProfileBanner {
+    /// The banner sized for display on the web.
+    pub web: BannerSize,
+    /// The banner sized for display on the web, at double resolution.
+    pub web_retina: BannerSize,
+    /// The banner sized for display on an iPad.
+    pub ipad: BannerSize,
+    /// The banner sized for display on an iPad, at double resolution.
+    pub ipad_retina: BannerSize,
+    /// The banner sized for display on mobile devices.
+    pub mobile: BannerSize,
+    /// The banner sized for display on mobile devices, at double resolution.
+    pub mobile_retina: BannerSize,
+    /// The banner at 300 by 100 pixels.
+    pub size_300x100: Option<BannerSize>,
+    /// The banner at 600 by 200 pixels.
+    pub size_600x200: Option<BannerSize>,
+    /// The banner at 1500 by 500 pixels, which is also the size it was originally uploaded at.
+    pub size_1500x500: Option<BannerSize>,
+}
+
+impl FromJson for ProfileBanner {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("ProfileBanner received json that wasn't an object", Some(input.to_string())));
+        }
+
+        field_present!(input, sizes);
+
+        let sizes = input.find("sizes").unwrap();
+
+        field_present!(sizes, web);
+        field_present!(sizes, web_retina);
+        field_present!(sizes, ipad);
+        field_present!(sizes, ipad_retina);
+        field_present!(sizes, mobile);
+        field_present!(sizes, mobile_retina);
+
+        Ok(ProfileBanner {
+            web: try!(field(sizes, "web")),
+            web_retina: try!(field(sizes, "web_retina")),
+            ipad: try!(field(sizes, "ipad")),
+            ipad_retina: try!(field(sizes, "ipad_retina")),
+            mobile: try!(field(sizes, "mobile")),
+            mobile_retina: try!(field(sizes, "mobile_retina")),
+            size_300x100: try!(field(sizes, "300x100")),
+            size_600x200: try!(field(sizes, "600x200")),
+            size_1500x500: try!(field(sizes, "1500x500")),
+        })
+    }
+}
+
+/// Represents a single size of a user's profile banner.
+#[derive(Debug, Clone)]
+pub struct BannerSize {
+    /// The width of the banner at this size, in pixels.
+    pub w: i32,
+    /// The height of the banner at this size, in pixels.
+    pub h: i32,
+    /// The URL to load the banner at this size.
+    pub url: String,
+}
+
+impl FromJson for BannerSize {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("BannerSize received json that wasn't an object", Some(input.to_string())));
+        }
+
+        field_present!(input, w);
+        field_present!(input, h);
+        field_present!(input, url);
+
+        Ok(BannerSize {
+            w: try!(field(input, "w")),
+            h: try!(field(input, "h")),
+            url: try!(field(input, "url")),
+        })
+    }
+}
+
+/// Represents the authenticated user's account settings, as returned by `set_trend_location`.
+///
+/// This only holds the more commonly-used settings that Twitter sends back.
+#[derive(Debug, Clone)]
+pub struct AccountSettings {
+    /// The authenticated user's screen name.
+    pub screen_name: String,
+    /// Whether the user's tweets are protected, i.e. only visible to their followers.
+    pub protected: bool,
+    /// Whether the user has turned on adding their location to their tweets.
+    pub geo_enabled: bool,
+    /// Whether other users can find this account by its email address.
+    pub discoverable_by_email: bool,
+    /// The BCP 47 code of the language the user has chosen for Twitter's interface.
+    pub language: String,
+    /// The name of the time zone the user has chosen, if any.
+    pub time_zone: Option<String>,
+    /// The locations that trends are shown for, for this user. This is usually a single location,
+    /// and may be empty if the user hasn't chosen one.
+    pub trend_location: Vec<TrendLocation>,
+}
+
+impl FromJson for AccountSettings {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("AccountSettings received json that wasn't an object", Some(input.to_string())));
+        }
+
+        field_present!(input, screen_name);
+        field_present!(input, protected);
+        field_present!(input, language);
+
+        let time_zone = input.find("time_zone").unwrap_or(&json::Json::Null);
+
+        Ok(AccountSettings {
+            screen_name: try!(field(input, "screen_name")),
+            protected: try!(field(input, "protected")),
+            geo_enabled: field(input, "geo_enabled").unwrap_or(false),
+            discoverable_by_email: field(input, "discoverable_by_email").unwrap_or(false),
+            language: try!(field(input, "language")),
+            time_zone: try!(field(time_zone, "name")),
+            trend_location: try!(field::<Option<Vec<TrendLocation>>>(input, "trend_location"))
+                                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Represents a location that trends can be shown for.
+#[derive(Debug, Clone)]
+pub struct TrendLocation {
+    /// The Yahoo! "where on earth" ID (WOEID) of this location.
+    pub woeid: i64,
+    /// The name of this location.
+    pub name: String,
+    /// The name of the country this location is in.
+    pub country: String,
+    /// The two-letter code of the country this location is in, or `None` for locations that cover
+    /// more than one country, like worldwide trends.
+    pub country_code: Option<String>,
+    /// The WOEID of the location that contains this one.
+    pub parent_id: i64,
+}
+
+impl FromJson for TrendLocation {
+    fn from_json(input: &json::Json) -> Result<Self, error::Error> {
+        if !input.is_object() {
+            return Err(InvalidResponse("TrendLocation received json that wasn't an object", Some(input.to_string())));
+        }
+
+        field_present!(input, woeid);
+        field_present!(input, name);
+
+        Ok(TrendLocation {
+            woeid: try!(field(input, "woeid")),
+            name: try!(field(input, "name")),
+            country: field(input, "country").unwrap_or_default(),
+            country_code: try!(field(input, "countryCode")),
+            parent_id: field(input, "parentid").unwrap_or(0),
+        })
+    }
+}
+
+/// `Future` which represents a call to `profile_banner`.
+///
+/// When this future completes, it will return `None` if the user has not set a profile banner,
+/// instead of returning the "not found" error that Twitter gives. Twitter still sends rate-limit
+/// headers with that error, so the `Response` carries them either way.
+#[must_use = "futures do nothing unless polled"]
+pub struct ProfileBannerFuture {
+    loader: FutureResponse<Option<ProfileBanner>>,
+}
+
+impl ProfileBannerFuture {
+    #[doc(hidden)]
+    pub fn new(loader: FutureResponse<Option<ProfileBanner>>) -> ProfileBannerFuture {
+        ProfileBannerFuture {
+            loader: loader,
+        }
+    }
+}
+
+impl Future for ProfileBannerFuture {
+    type Item = Response<Option<ProfileBanner>>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.loader.poll()
+    }
+}
+
+/// Parses the response to `profile_banner`, turning Twitter's "not found" answer into `None`.
+fn parse_profile_banner(full_resp: String, headers: &Headers)
+    -> Result<Response<Option<ProfileBanner>>, error::Error>
+{
+    //twitter answers a user without a banner with a 404, either with a "page not found" error
+    //or with no body at all
+    if full_resp.trim().is_empty() {
+        return Ok(Response::map(rate_headers(headers), |_| None));
+    }
+
+    if let Ok(err) = json::decode::<error::TwitterErrors>(&full_resp) {
+        if err.has_code(error::codes::PAGE_NOT_FOUND) {
+            return Ok(Response::map(rate_headers(headers), |_| None));
+        }
+
+        return Err(error::Error::TwitterError(err));
+    }
+
+    let banner: WebResponse<ProfileBanner> = make_response(full_resp, headers);
+
+    banner.map(|banner| Response::map(banner, Some))
+}
+
+/// `Future` which represents a call to `lookup_map`.
+///
+/// When this future completes, it will return a map with an entry for every account that was
+/// requested, whether Twitter returned it or not.
+#[must_use = "futures do nothing unless polled"]
+pub struct LookupMapFuture {
+    accts: Option<Vec<UserID<'static>>>,
+    loader: BatchFuture<Vec<TwitterUser>>,
+}
+
+impl LookupMapFuture {
+    #[doc(hidden)]
+    pub fn new(accts: Vec<UserID<'static>>, loader: BatchFuture<Vec<TwitterUser>>)
+        -> LookupMapFuture
+    {
+        LookupMapFuture {
+            accts: Some(accts),
+            loader: loader,
+        }
+    }
+}
+
+impl Future for LookupMapFuture {
+    type Item = Response<HashMap<UserID<'static>, Option<TwitterUser>>>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let resp = match self.loader.poll() {
+            Err(e) => return Err(e),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(resp)) => resp,
+        };
+
+        if let Some(accts) = self.accts.take() {
+            Ok(Async::Ready(Response::map(resp, |users| map_users(accts, users))))
+        } else {
+            Err(error::Error::FutureAlreadyCompleted)
+        }
+    }
+}
+
+/// Pairs up the given users with the accounts that were requested, storing `None` for the
+/// accounts that weren't returned.
+fn map_users(accts: Vec<UserID<'static>>, users: Vec<TwitterUser>)
+    -> HashMap<UserID<'static>, Option<TwitterUser>>
+{
+    accts.into_iter().map(|acct| {
+        let user = users.iter().find(|user| acct.matches(user)).cloned();
+        (acct, user)
+    }).collect()
+}
+
+/// `Future` which represents a call to `update_follow_checked`.
+///
+/// If the requested settings need the authenticated user to follow the account, this first checks
+/// that they do, and only then sends the update.
+#[must_use = "futures do nothing unless polled"]
+pub struct UpdateFollowFuture {
+    check: Option<FutureResponse<bool>>,
+    settings: (Option<bool>, Option<bool>),
+    update: FutureResponse<Relationship>,
+}
+
+impl UpdateFollowFuture {
+    #[doc(hidden)]
+    pub fn new(check: Option<FutureResponse<bool>>, notifications: Option<bool>,
+               retweets: Option<bool>, update: FutureResponse<Relationship>)
+        -> UpdateFollowFuture
+    {
+        UpdateFollowFuture {
+            check: check,
+            settings: (notifications, retweets),
+            update: update,
+        }
+    }
+}
+
+impl Future for UpdateFollowFuture {
+    type Item = Response<Relationship>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(mut check) = self.check.take() {
+            match check.poll() {
+                Err(e) => return Err(e),
+                Ok(Async::NotReady) => {
+                    self.check = Some(check);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(following)) => {
+                    let (notifications, retweets) = self.settings;
+                    try!(check_follow_update(following.response, notifications, retweets));
+                }
+            }
+        }
+
+        self.update.poll()
+    }
+}
+
+/// Settings to apply to an account when following it with `follow_with`.
+///
+/// Leaving out a setting will keep Twitter's default behavior, which is to leave device
+/// notifications off and show the account's retweets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FollowOptions {
+    /// Whether to turn on device notifications for the account's tweets.
+    pub notifications: Option<bool>,
+    /// Whether the account's retweets should appear in the authenticated user's timeline.
+    pub retweets: Option<bool>,
+}
+
+impl FollowOptions {
+    /// Creates a new `FollowOptions` that keeps Twitter's default settings.
+    pub fn new() -> FollowOptions {
+        FollowOptions::default()
+    }
+
+    /// Sets whether to turn on device notifications for the account's tweets.
+    pub fn notifications(self, notifications: bool) -> FollowOptions {
+        FollowOptions {
+            notifications: Some(notifications),
+            ..self
+        }
+    }
+
+    /// Sets whether the account's retweets should appear in the authenticated user's timeline.
+    pub fn retweets(self, retweets: bool) -> FollowOptions {
+        FollowOptions {
+            retweets: Some(retweets),
+            ..self
+        }
+    }
+}
+
+/// `Future` which represents a call to `follow_with`.
+///
+/// This first follows the account, then loads the resulting `Relationship` with
+/// `friendships/update`, setting retweet visibility along the way if the follow went through.
+#[must_use = "futures do nothing unless polled"]
+pub struct FollowWithFuture {
+    follow: Option<FutureResponse<TwitterUser>>,
+    retweets: Option<bool>,
+    token: auth::Token,
+    handle: Handle,
+    update: Option<FutureResponse<Relationship>>,
+}
+
+impl FollowWithFuture {
+    #[doc(hidden)]
+    pub fn new(follow: FutureResponse<TwitterUser>, retweets: Option<bool>, token: &auth::Token,
+               handle: &Handle)
+        -> FollowWithFuture
+    {
+        FollowWithFuture {
+            follow: Some(follow),
+            retweets: retweets,
+            token: token.clone(),
+            handle: handle.clone(),
+            update: None,
+        }
+    }
+}
+
+impl Future for FollowWithFuture {
+    type Item = Response<Relationship>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(mut follow) = self.follow.take() {
+            match follow.poll() {
+                Err(e) => return Err(e),
+                Ok(Async::NotReady) => {
+                    self.follow = Some(follow);
+                    return Ok(Async::NotReady);
+                }
+                Ok(Async::Ready(user)) => {
+                    let retweets = follow_up_retweets(&user.response, self.retweets);
+                    self.update = Some(update_follow(user.id, None, retweets,
+                                                     &self.token, &self.handle));
+                }
+            }
+        }
+
+        match self.update {
+            Some(ref mut update) => update.poll(),
+            None => Err(error::Error::FutureAlreadyCompleted),
+        }
+    }
+}
+
+/// Returns the retweet setting to send after following the given user. Twitter won't change
+/// follow settings for a protected account until the follow request is accepted, so none are sent
+/// for a pending follow.
+fn follow_up_retweets(user: &TwitterUser, retweets: Option<bool>) -> Option<bool> {
+    if user.is_pending_follow() {
+        None
+    } else {
+        retweets
+    }
+}
+
+/// The users loaded by `show_many`, along with the accounts that couldn't be loaded.
+#[derive(Debug, Clone)]
+pub struct ShowMany {
+    /// The users that were loaded, in the order they were requested.
+    ///
+    /// If the same account was requested more than once (say, by both its ID and its screen
+    /// name), it only appears here once.
+    pub users: Vec<TwitterUser>,
+    /// The accounts that Twitter didn't return, in the order they were requested. These are
+    /// usually suspended or deleted accounts, or screen names that don't exist.
+    pub missing: Vec<UserID<'static>>,
+}
+
+/// `Future` which represents a call to `show_many`.
+///
+/// When this future completes, every account that was requested will either have its user in
+/// `users`, or be listed in `missing`.
+#[must_use = "futures do nothing unless polled"]
+pub struct ShowManyFuture {
+    accts: Option<Vec<UserID<'static>>>,
+    loader: BatchFuture<Vec<TwitterUser>>,
+}
+
+impl ShowManyFuture {
+    #[doc(hidden)]
+    pub fn new(accts: Vec<UserID<'static>>, loader: BatchFuture<Vec<TwitterUser>>)
+        -> ShowManyFuture
+    {
+        ShowManyFuture {
+            accts: Some(accts),
+            loader: loader,
+        }
+    }
+}
+
+impl Future for ShowManyFuture {
+    type Item = Response<ShowMany>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let resp = match self.loader.poll() {
+            Err(e) => return Err(e),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(resp)) => resp,
+        };
+
+        if let Some(accts) = self.accts.take() {
+            Ok(Async::Ready(Response::map(resp, |users| split_users(accts, users))))
+        } else {
+            Err(error::Error::FutureAlreadyCompleted)
+        }
+    }
+}
+
+/// Sorts the given users into the order the accounts were requested, and lists the accounts that
+/// weren't returned.
+fn split_users(accts: Vec<UserID<'static>>, mut users: Vec<TwitterUser>) -> ShowMany {
+    let mut ret = ShowMany {
+        users: Vec::with_capacity(users.len()),
+        missing: Vec::new(),
+    };
+
+    for acct in accts {
+        if ret.users.iter().any(|user| acct.matches(user)) {
+            continue;
+        }
+
+        match users.iter().position(|user| acct.matches(user)) {
+            Some(idx) => ret.users.push(users.remove(idx)),
+            None => ret.missing.push(acct),
+        }
+    }
+
+    ret
+}
+
+/// `Future` which represents a call to `relation_lookup_map`.
+///
+/// When this future completes, it will return a map with an entry for every account that was
+/// requested, whether Twitter returned it or not.
+#[must_use = "futures do nothing unless polled"]
+pub struct RelationLookupMapFuture {
+    accts: Option<Vec<UserID<'static>>>,
+    loader: BatchFuture<Vec<RelationLookup>>,
+}
+
+impl RelationLookupMapFuture {
+    #[doc(hidden)]
+    pub fn new(accts: Vec<UserID<'static>>, loader: BatchFuture<Vec<RelationLookup>>)
+        -> RelationLookupMapFuture
+    {
+        RelationLookupMapFuture {
+            accts: Some(accts),
+            loader: loader,
+        }
+    }
+}
+
+impl Future for RelationLookupMapFuture {
+    type Item = Response<HashMap<UserID<'static>, Option<RelationLookup>>>;
+    type Error = error::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let resp = match self.loader.poll() {
+            Err(e) => return Err(e),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(resp)) => resp,
+        };
+
+        if let Some(accts) = self.accts.take() {
+            Ok(Async::Ready(Response::map(resp, |relations| map_relations(accts, relations))))
+        } else {
+            Err(error::Error::FutureAlreadyCompleted)
+        }
+    }
+}
+
+/// Pairs up the given relations with the accounts that were requested, storing `None` for the
+/// accounts that weren't returned.
+fn map_relations(accts: Vec<UserID<'static>>, relations: Vec<RelationLookup>)
+    -> HashMap<UserID<'static>, Option<RelationLookup>>
+{
+    accts.into_iter().map(|acct| {
+        let relation = relations.iter().find(|rel| acct.matches_relation(rel)).cloned();
+        (acct, relation)
+    }).collect()
+}
+
+/// Optional settings for how much information is returned with each `TwitterUser`.
+///
+/// This is used by [`show_with`] and [`lookup_with`] to trim down the user information returned by
+/// Twitter, if you don't need all of it. By default, Twitter includes the user's most recent tweet
+/// in the `status` field, and the parsed `entities` of their profile. Leaving out a setting will
+/// keep Twitter's default behavior.
+///
+/// [`show_with`]: fn.show_with.html
+/// [`lookup_with`]: fn.lookup_with.html
+///
+/// ```rust,no_run
+/// # extern crate egg_mode; extern crate tokio_core; extern crate futures;
+/// # use egg_mode::Token; use tokio_core::reactor::{Core, Handle};
+/// # fn main() {
+/// # let (token, mut core, handle): (Token, Core, Handle) = unimplemented!();
+/// use egg_mode::user::{self, UserOptions};
+///
+/// let opts = UserOptions::new().skip_status(true).include_entities(false);
+/// let rustlang = core.run(user::show_with("rustlang", opts, &token, &handle)).unwrap();
+///
+/// assert!(rustlang.status.is_none());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserOptions {
+    /// Whether to leave out the user's most recent tweet.
+    pub skip_status: Option<bool>,
+    /// Whether to include the `entities` field with the user's profile.
+    pub include_entities: Option<bool>,
+}
+
+impl UserOptions {
+    /// Creates a new `UserOptions` that keeps Twitter's default settings.
+    pub fn new() -> UserOptions {
+        UserOptions::default()
+    }
+
+    /// Sets whether to leave out the user's most recent tweet from the `status` field.
+    pub fn skip_status(self, skip_status: bool) -> UserOptions {
+        UserOptions {
+            skip_status: Some(skip_status),
+            ..self
+        }
+    }
+
+    /// Sets whether to include the `entities` field with the user's profile.
+    ///
+    /// Note that `TwitterUser::entities` will be empty if this is set to `false`. This also leaves
+    /// out the entities of the user's most recent tweet in `status`, so its `entities` will be
+    /// empty too.
+    pub fn include_entities(self, include_entities: bool) -> UserOptions {
+        UserOptions {
+            include_entities: Some(include_entities),
+            ..self
+        }
+    }
+
+    /// Adds the parameters for these settings to the given `ParamList`.
+    fn add_params(&self, params: &mut ParamList) {
+        if let Some(skip_status) = self.skip_status {
+            add_param(params, "skip_status", skip_status.to_string());
+        }
+
+        if let Some(include_entities) = self.include_entities {
+            add_param(params, "include_entities", include_entities.to_string());
+        }
+    }
+}
+
+/// The accounts that were added to or removed from a set of users between two snapshots.
+///
+/// This is meant for comparing the results of calls like `followers_ids` or `friends_ids` made at
+/// different times, to see who followed or unfollowed an account in the meantime. No network
+/// calls are made; it only compares the IDs it's given. To compare lists of full `TwitterUser`s,
+/// map them to their `id` first.
+///
+/// ```rust
+/// use egg_mode::user::SnapshotDiff;
+///
+/// let yesterday = vec![1, 2, 3];
+/// let today = vec![2, 3, 4, 5];
+///
+/// let diff = SnapshotDiff::between(yesterday, today);
+/// assert!(diff.added.contains(&4) && diff.added.contains(&5));
+/// assert!(diff.removed.contains(&1));
+/// assert_eq!(diff.added.len(), 2);
+/// assert_eq!(diff.removed.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// The IDs that are in the new snapshot, but not the old one.
+    pub added: HashSet<u64>,
+    /// The IDs that are in the old snapshot, but not the new one.
+    pub removed: HashSet<u64>,
+}
+
+impl SnapshotDiff {
+    /// Compares the given snapshots of user IDs. Duplicate IDs in either snapshot are ignored.
+    pub fn between<I, J>(old: I, new: J) -> SnapshotDiff
+        where I: IntoIterator<Item=u64>, J: IntoIterator<Item=u64>
+    {
+        let old = old.into_iter().collect::<HashSet<u64>>();
+        let new = new.into_iter().collect::<HashSet<u64>>();
+
+        SnapshotDiff {
+            added: new.difference(&old).cloned().collect(),
+            removed: old.difference(&new).cloned().collect(),
         }
+    }
 
-        Ok(UserEntityDetail {
-            urls: try!(field(input, "urls")),
-        })
+    /// Returns whether the two snapshots held the same set of IDs.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
     }
 }
 
@@ -517,10 +1639,11 @@ impl FromJson for UserEntityDetail {
 /// # }
 /// ```
 ///
-/// `UserSearch` has a couple adaptors of its own that you can use before consuming it.
-/// `with_page_size` will let you set how many users are pulled in with a single network call, and
-/// `start_at_page` lets you start your search at a specific page. Calling either of these after
-/// starting iteration will clear any current results.
+/// `UserSearch` has a few adaptors of its own that you can use before consuming it.
+/// `with_page_size` will let you set how many users are pulled in with a single network call,
+/// `start_at_page` lets you start your search at a specific page, and `include_entities` lets you
+/// leave out the `entities` of each user's profile. Calling any of these after starting iteration
+/// will clear any current results.
 ///
 /// The `Stream` implementation yields `Response<TwitterUser>` on a successful iteration, and
 /// `Error` for errors, so network errors, rate-limit errors and other issues are passed directly
@@ -528,6 +1651,10 @@ impl FromJson for UserEntityDetail {
 /// re-initiate the late network call; this way, you can wait for your network connection to return
 /// or for your rate limit to refresh and try again from the same position.
 ///
+/// Twitter only serves the first 50 pages of a user search. The stream ends once it loads a page
+/// with fewer results than `page_size`, or once it finishes page 50, whichever comes first. After
+/// it ends, `end` tells you which of these happened.
+///
 /// ## Manual paging
 ///
 /// The `Stream` implementation works by loading in a page of results (with size set by default or
@@ -568,20 +1695,56 @@ pub struct UserSearch<'a> {
     pub page_num: i32,
     /// The number of user records per page of results. Defaults to 10, maximum of 20.
     pub page_size: i32,
+    /// Whether to include the `entities` field with each user. If not set, Twitter's default of
+    /// including them is used.
+    pub include_entities: Option<bool>,
     current_loader: Option<FutureResponse<Vec<TwitterUser>>>,
     current_results: Option<ResponseIter<TwitterUser>>,
+    current_len: usize,
+    end: Option<UserSearchEnd>,
+}
+
+/// The last page of results that Twitter will serve for a user search.
+const USER_SEARCH_MAX_PAGE: i32 = 50;
+
+/// Represents the reason a `UserSearch` stopped returning results.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UserSearchEnd {
+    /// Every user that matched the search has been returned.
+    Exhausted,
+    /// The search reached page 50, the last page Twitter serves. There may be more users that
+    /// match the search, which you can find by narrowing down the query.
+    Capped,
 }
 
 impl<'a> UserSearch<'a> {
     /// Sets the page size used for the search query.
     ///
+    /// Twitter returns at most 20 users per page of a user search, so larger values will be
+    /// lowered to 20.
+    ///
     /// Calling this will invalidate any current search results, making the next call to `next()`
     /// perform a network call.
     pub fn with_page_size(self, page_size: i32) -> Self {
         UserSearch {
-            page_size: page_size,
+            page_size: ::std::cmp::min(page_size, 20),
+            current_loader: None,
+            current_results: None,
+            end: None,
+            ..self
+        }
+    }
+
+    /// Sets whether to include the `entities` field with each user returned by the search.
+    ///
+    /// Calling this will invalidate any current search results, making the next call to `next()`
+    /// perform a network call.
+    pub fn include_entities(self, include_entities: bool) -> Self {
+        UserSearch {
+            include_entities: Some(include_entities),
             current_loader: None,
             current_results: None,
+            end: None,
             ..self
         }
     }
@@ -595,10 +1758,16 @@ impl<'a> UserSearch<'a> {
             page_num: page_num,
             current_loader: None,
             current_results: None,
+            end: None,
             ..self
         }
     }
 
+    /// Returns why this search stopped returning results, or `None` if it hasn't ended yet.
+    pub fn end(&self) -> Option<UserSearchEnd> {
+        self.end
+    }
+
     /// Performs the search for the current page of results.
     ///
     /// This will automatically be called if you use the `UserSearch` as an iterator. This method is
@@ -610,6 +1779,10 @@ impl<'a> UserSearch<'a> {
         add_param(&mut params, "page", self.page_num.to_string());
         add_param(&mut params, "count", self.page_size.to_string());
 
+        if let Some(include_entities) = self.include_entities {
+            add_param(&mut params, "include_entities", include_entities.to_string());
+        }
+
         let req = auth::get(links::users::SEARCH, &self.token, Some(&params));
 
         make_parsed_future(&self.handle, req)
@@ -625,8 +1798,11 @@ impl<'a> UserSearch<'a> {
             query: query.into(),
             page_num: 1,
             page_size: 10,
+            include_entities: None,
             current_loader: None,
             current_results: None,
+            current_len: 0,
+            end: None,
         }
     }
 }
@@ -636,13 +1812,25 @@ impl<'a> Stream for UserSearch<'a> {
     type Error = error::Error;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.end.is_some() {
+            return Ok(Async::Ready(None));
+        }
+
         if let Some(mut fut) = self.current_loader.take() {
             match fut.poll() {
                 Ok(Async::NotReady) => {
                     self.current_loader = Some(fut);
                     return Ok(Async::NotReady);
                 }
-                Ok(Async::Ready(res)) => self.current_results = Some(res.into_iter()),
+                Ok(Async::Ready(res)) => {
+                    if res.response.is_empty() {
+                        self.end = Some(UserSearchEnd::Exhausted);
+                        return Ok(Async::Ready(None));
+                    }
+
+                    self.current_len = res.response.len();
+                    self.current_results = Some(res.into_iter());
+                }
                 Err(e) => {
                     //Invalidate current results so we don't increment the page number again
                     self.current_results = None;
@@ -654,7 +1842,11 @@ impl<'a> Stream for UserSearch<'a> {
         if let Some(ref mut results) = self.current_results {
             if let Some(user) = results.next() {
                 return Ok(Async::Ready(Some(user)));
-            } else if (results.len() as i32) < self.page_size {
+            } else if (self.current_len as i32) < self.page_size {
+                self.end = Some(UserSearchEnd::Exhausted);
+                return Ok(Async::Ready(None));
+            } else if self.page_num >= USER_SEARCH_MAX_PAGE {
+                self.end = Some(UserSearchEnd::Capped);
                 return Ok(Async::Ready(None));
             } else {
                 self.page_num += 1;
@@ -680,6 +1872,51 @@ pub struct Relationship {
     pub source: RelationSource,
 }
 
+impl Relationship {
+    /// Returns whether the source account follows the target account.
+    pub fn is_following(&self) -> bool {
+        self.source.following
+    }
+
+    /// Returns whether the target account follows the source account.
+    pub fn is_followed_by(&self) -> bool {
+        self.source.followed_by
+    }
+
+    /// Returns whether the source account has sent a follow request to the target account that
+    /// hasn't been accepted yet.
+    ///
+    /// Twitter only gives this information if the source account is the authenticated user, so
+    /// this will return `false` for other source accounts.
+    pub fn is_pending_follow(&self) -> bool {
+        self.source.following_requested.unwrap_or(false)
+    }
+
+    /// Returns whether the source account is blocking the target account.
+    ///
+    /// Twitter only gives this information if the source account is the authenticated user, so
+    /// this will return `false` for other source accounts.
+    pub fn is_blocking(&self) -> bool {
+        self.source.blocking.unwrap_or(false)
+    }
+
+    /// Returns whether the source account has muted the target account.
+    ///
+    /// Twitter only gives this information if the source account is the authenticated user, so
+    /// this will return `false` for other source accounts.
+    pub fn is_muting(&self) -> bool {
+        self.source.muting.unwrap_or(false)
+    }
+
+    /// Returns whether the source account has chosen to see retweets from the target account.
+    ///
+    /// Twitter only gives this information if the source account is the authenticated user, so
+    /// this will return `false` for other source accounts.
+    pub fn wants_retweets(&self) -> bool {
+        self.source.want_retweets.unwrap_or(false)
+    }
+}
+
 impl FromJson for Relationship {
     fn from_json(input: &json::Json) -> Result<Self, error::Error> {
         if !input.is_object() {
@@ -759,6 +1996,9 @@ pub struct RelationSource {
     /// Indicates whether this source account is blocking the target account. If the source account
     /// is not the authenticated user, holds `None` instead.
     pub blocking: Option<bool>,
+    /// Indicates whether this source account has muted the target account. If the source account
+    /// is not the authenticated user, holds `None` instead.
+    pub muting: Option<bool>,
     /// Indicates whether this source account has reported the target account for spam. If the source
     /// account is not the authenticated user, holds `None` instead.
     pub marked_spam: Option<bool>,
@@ -774,6 +2014,10 @@ pub struct RelationSource {
     /// Indicates whether this source account has decided to receive mobile notifications for the
     /// target account. If the source account is not the authenticated user, holds `None` instead.
     pub notifications_enabled: Option<bool>,
+    /// Indicates whether this source account has sent a follow request to the (protected) target
+    /// account that hasn't been accepted yet. If the source account is not the authenticated user,
+    /// holds `None` instead.
+    pub following_requested: Option<bool>,
 }
 
 impl FromJson for RelationSource {
@@ -795,10 +2039,12 @@ impl FromJson for RelationSource {
             followed_by: try!(field(input, "followed_by")),
             can_dm: try!(field(input, "can_dm")),
             blocking: try!(field(input, "blocking")),
+            muting: try!(field(input, "muting")),
             marked_spam: try!(field(input, "marked_spam")),
             all_replies: try!(field(input, "all_replies")),
             want_retweets: try!(field(input, "want_retweets")),
             notifications_enabled: try!(field(input, "notifications_enabled")),
+            following_requested: try!(field(input, "following_requested")),
         })
     }
 }
@@ -807,7 +2053,7 @@ impl FromJson for RelationSource {
 ///
 /// This is returned by `relation_lookup`, as opposed to `Relationship`, which is returned by
 /// `relation`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RelationLookup {
     /// The display name of the target account.
     pub name: String,
@@ -822,6 +2068,33 @@ pub struct RelationLookup {
     pub connections: Vec<Connection>,
 }
 
+impl RelationLookup {
+    /// Returns whether the authenticated user follows the target account.
+    pub fn following(&self) -> bool {
+        self.connections.contains(&Connection::Following)
+    }
+
+    /// Returns whether the target account follows the authenticated user.
+    pub fn followed_by(&self) -> bool {
+        self.connections.contains(&Connection::FollowedBy)
+    }
+
+    /// Returns whether the authenticated user has blocked the target account.
+    pub fn blocking(&self) -> bool {
+        self.connections.contains(&Connection::Blocking)
+    }
+
+    /// Returns whether the authenticated user has muted the target account.
+    pub fn muting(&self) -> bool {
+        self.connections.contains(&Connection::Muting)
+    }
+
+    /// Returns whether the authenticated user has a pending request to follow the target account.
+    pub fn following_requested(&self) -> bool {
+        self.connections.contains(&Connection::FollowingRequested)
+    }
+}
+
 impl FromJson for RelationLookup {
     fn from_json(input: &json::Json) -> Result<Self, error::Error> {
         if !input.is_object() {
@@ -843,7 +2116,7 @@ impl FromJson for RelationLookup {
 }
 
 /// Represents the ways a target account can be connected to another account.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Connection {
     /// The target account has no relation.
     None,
@@ -879,3 +2152,814 @@ impl FromJson for Connection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{UserID, RelationLookup, ProfileBanner, AccountSettings, resize_profile_image};
+    use super::{lookup, relation_lookup};
+    use auth;
+    use common::FromJson;
+    use cursor;
+    use rustc_serialize::json;
+
+    use super::{TwitterUser, Relationship, Connection, UserSearch, UserSearchEnd, normalize_lang};
+    use super::WithheldScope;
+    use common::Response;
+    use futures::{Future, Stream, Async};
+    use tokio_core::reactor::Core;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    fn load_file(path: &str) -> String {
+        let mut file = File::open(path).unwrap();
+        let mut ret = String::new();
+        file.read_to_string(&mut ret).unwrap();
+        ret
+    }
+
+    #[test]
+    fn parse_profile_updates() {
+        use super::parse_profile_update;
+        use common::Headers;
+
+        let user = parse_profile_update(load_file("src/user/sample-user.json"), &Headers::new())
+            .unwrap();
+        assert_eq!(user.response.map(|u| u.followers_count), Some(1234));
+
+        let empty = parse_profile_update(String::new(), &Headers::new()).unwrap();
+        assert!(empty.response.is_none());
+        assert_eq!(empty.rate_limit, -1);
+    }
+
+    #[test]
+    fn parse_user() {
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+
+        assert_eq!(user.id, 2244994945);
+        assert_eq!(user.screen_name, "zoe_unicode");
+        assert_eq!(user.name, "Zoë ☕ Ünïcode");
+        assert_eq!(user.location, Some("Zürich, Schweiz".to_string()));
+        assert_eq!(user.url, Some("https://t.co/XyZ987wvU".to_string()));
+        assert_eq!(user.lang, "de");
+        assert_eq!(user.time_zone, Some("Bern".to_string()));
+        assert_eq!(user.utc_offset, Some(3600));
+        assert_eq!(user.created_at.timestamp(), 1386995755);
+
+        assert_eq!(user.followers_count, 1234);
+        assert_eq!(user.friends_count, 321);
+        assert_eq!(user.listed_count, 12);
+        assert_eq!(user.favourites_count, 4567);
+        assert_eq!(user.statuses_count, 8910);
+
+        assert!(!user.protected);
+        assert!(!user.verified);
+        assert!(user.geo_enabled);
+        assert!(!user.contributors_enabled);
+        assert!(!user.is_translator);
+        assert!(!user.default_profile);
+        assert!(!user.default_profile_image);
+        assert_eq!(user.following, Some(true));
+        assert_eq!(user.follow_request_sent, Some(false));
+        assert_eq!(user.notifications, Some(false));
+
+        assert_eq!(user.profile_background_color, "C0DEED");
+        assert_eq!(user.profile_background_tile, Some(false));
+        assert_eq!(user.profile_link_color, "1DA1F2");
+        assert_eq!(user.profile_sidebar_border_color, "C0DEED");
+        assert_eq!(user.profile_sidebar_fill_color, "DDEEF6");
+        assert_eq!(user.profile_text_color, "333333");
+        assert_eq!(user.profile_link_rgb(), Some((0x1D, 0xA1, 0xF2)));
+        assert_eq!(user.profile_text_rgb(), Some((0x33, 0x33, 0x33)));
+        assert!(user.profile_use_background_image);
+        assert_eq!(user.profile_banner_url,
+                   Some("https://pbs.twimg.com/profile_banners/2244994945/1511191983".to_string()));
+        assert!(user.profile_image_url_https.ends_with("BbazLO9L_normal.jpg"));
+        assert!(user.status.is_none());
+
+        assert_eq!(user.withheld_in_countries, Some(vec!["DE".to_string(), "FR".to_string()]));
+        assert_eq!(user.withheld_scope, Some(WithheldScope::User));
+
+        //entity ranges arrive as codepoint offsets, but should be turned into byte offsets so they
+        //can slice the (non-ASCII) description
+        let description = user.description.as_ref().unwrap();
+        let entity = &user.entities.description.urls[0];
+        assert_eq!(entity.range, (28, 50));
+        assert_eq!(&description[entity.range.0..entity.range.1], entity.url);
+        assert_eq!(entity.expanded_url, "https://blog.example.com");
+
+        let url_entities = user.entities.url.as_ref().unwrap();
+        assert_eq!(url_entities.urls[0].range, (0, 22));
+        assert_eq!(url_entities.urls[0].display_url, "example.com/zoë");
+    }
+
+    #[test]
+    fn parse_user_minimal() {
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user-minimal.json")).unwrap();
+
+        assert_eq!(user.id, 783214);
+        assert_eq!(user.screen_name, "Twitter");
+        assert!(user.protected);
+        assert!(user.verified);
+        assert!(user.default_profile_image);
+
+        //null or missing optional fields should all come through as None or their defaults
+        assert!(user.description.is_none());
+        assert!(user.location.is_none());
+        assert!(user.url.is_none());
+        assert!(user.time_zone.is_none());
+        assert!(user.utc_offset.is_none());
+        assert!(user.profile_background_image_url.is_none());
+        assert!(user.profile_background_tile.is_none());
+        assert!(user.profile_banner_url.is_none());
+        assert!(user.show_all_inline_media.is_none());
+        assert!(user.following.is_none());
+        assert!(user.follow_request_sent.is_none());
+        assert!(user.status.is_none());
+        assert!(user.withheld_in_countries.is_none());
+        assert!(user.withheld_scope.is_none());
+        assert!(user.entities.description.urls.is_empty());
+        assert!(user.entities.url.is_none());
+        assert!(!user.contributors_enabled);
+    }
+
+    fn search_with_page(page_num: i32, page_size: i32, users: usize) -> UserSearch<'static> {
+        let core = Core::new().unwrap();
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        let page = Response {
+            rate_limit: 180,
+            rate_limit_remaining: 179,
+            rate_limit_reset: 0,
+            response: vec![user; users],
+        };
+
+        let mut search = UserSearch::new("rustlang", &auth::Token::Bearer("bearer".to_string()),
+                                         &core.handle());
+        search.page_num = page_num;
+        search.page_size = page_size;
+        search.current_len = users;
+        search.current_results = Some(page.into_iter());
+        search
+    }
+
+    #[test]
+    fn user_search_end() {
+        //a short page means there's nothing left to load
+        let mut search = search_with_page(3, 20, 5);
+        for _ in 0..5 {
+            match search.poll() {
+                Ok(Async::Ready(Some(_))) => (),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        assert!(search.end().is_none());
+        assert!(match search.poll() { Ok(Async::Ready(None)) => true, _ => false });
+        assert_eq!(search.end(), Some(UserSearchEnd::Exhausted));
+
+        //a full page 50 is as far as Twitter goes
+        let mut search = search_with_page(50, 2, 2);
+        assert!(match search.poll() { Ok(Async::Ready(Some(_))) => true, _ => false });
+        assert!(match search.poll() { Ok(Async::Ready(Some(_))) => true, _ => false });
+        assert!(match search.poll() { Ok(Async::Ready(None)) => true, _ => false });
+        assert_eq!(search.end(), Some(UserSearchEnd::Capped));
+        assert_eq!(search.page_num, 50);
+
+        //once it's ended, it stays ended
+        assert!(match search.poll() { Ok(Async::Ready(None)) => true, _ => false });
+
+        //restarting the search clears the end
+        let search = search.start_at_page(1);
+        assert!(search.end().is_none());
+    }
+
+    #[test]
+    fn parse_large_counts() {
+        //counts past i32::MAX (2,147,483,647) need to come through intact
+        let input = load_file("src/user/sample-user.json")
+            .replace(r#""followers_count": 1234"#, r#""followers_count": 3000000000"#)
+            .replace(r#""statuses_count": 8910"#, r#""statuses_count": 9007199254740993"#);
+        let user = TwitterUser::from_str(&input).unwrap();
+
+        assert_eq!(user.followers_count, 3000000000);
+        assert_eq!(user.statuses_count, 9007199254740993);
+    }
+
+    #[test]
+    fn sort_by_created_at() {
+        use chrono::Duration;
+
+        let older = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+
+        let mut newer = older.clone();
+        newer.id = older.id - 1;
+        newer.created_at = older.created_at + Duration::seconds(1);
+
+        let mut same_time = older.clone();
+        same_time.id = older.id + 1;
+
+        let mut users = vec![newer.clone(), same_time.clone(), older.clone()];
+        users.sort_by(TwitterUser::cmp_created_at);
+        assert_eq!(users.iter().map(|u| u.id).collect::<Vec<_>>(),
+                   vec![older.id, same_time.id, newer.id]);
+    }
+
+    #[test]
+    fn show_without_entities() {
+        use super::{show_with, UserOptions};
+        use common::mock;
+        use hyper::{Headers, StatusCode};
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        mock::respond(&handle, StatusCode::Ok, Headers::new(),
+                      load_file("src/user/sample-user-no-entities.json"));
+
+        let opts = UserOptions::new().include_entities(false);
+        match show_with("zoe_unicode", opts, &token, &handle).poll() {
+            Ok(Async::Ready(user)) => {
+                assert_eq!(user.id, 2244994945);
+                assert!(user.entities.description.urls.is_empty());
+
+                let status = user.response.status.unwrap();
+                assert_eq!(status.id, 782349500404862976);
+                assert!(status.entities.urls.is_empty());
+                assert!(status.entities.media.is_none());
+            }
+            Ok(Async::NotReady) => panic!("mocked show_with wasn't ready"),
+            Err(e) => panic!("mocked show_with returned an error: {}", e),
+        }
+    }
+
+    #[test]
+    fn page_estimates() {
+        let mut user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert_eq!(user.followers_count, 1234);
+        assert_eq!(user.follower_pages(20), Some(62));
+        assert_eq!(user.follower_pages(5000), Some(1));
+        assert_eq!(user.follower_pages(1234), Some(1));
+        assert_eq!(user.follower_pages(617), Some(2));
+        assert_eq!(user.follower_pages(0), None);
+        assert_eq!(user.friend_pages(200), Some(2));
+
+        user.followers_count = 0;
+        assert_eq!(user.follower_pages(20), None);
+    }
+
+    #[test]
+    fn user_email() {
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert_eq!(user.email, None);
+
+        let input = load_file("src/user/sample-user.json")
+            .replace(r#""following": true"#, r#""following": true, "email": "me@example.com""#);
+        let user = TwitterUser::from_str(&input).unwrap();
+        assert_eq!(user.email, Some("me@example.com".to_string()));
+    }
+
+    #[test]
+    fn pending_follow() {
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert!(!user.is_pending_follow());
+
+        let input = load_file("src/user/sample-user.json")
+            .replace(r#""following": true"#, r#""following": false"#)
+            .replace(r#""follow_request_sent": false"#, r#""follow_request_sent": true"#);
+        let user = TwitterUser::from_str(&input).unwrap();
+        assert!(user.is_pending_follow());
+
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user-minimal.json")).unwrap();
+        assert!(!user.is_pending_follow());
+
+        let input = load_file("src/user/sample-relationship.json")
+            .replace(r#""following": true,
+      "followed_by": false,
+      "live_following": false,
+      "following_received": null,
+      "following_requested": null"#,
+                     r#""following": false,
+      "followed_by": false,
+      "live_following": false,
+      "following_received": null,
+      "following_requested": true"#);
+        let relation = Relationship::from_str(&input).unwrap();
+        assert!(!relation.is_following());
+        assert!(relation.is_pending_follow());
+    }
+
+    #[test]
+    fn custom_avatar() {
+        let mut user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert!(user.has_custom_avatar());
+
+        user.profile_image_url_https =
+            "https://abs.twimg.com/sticky/default_profile_images/default_profile_normal.png".into();
+        assert!(!user.has_custom_avatar());
+
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user-minimal.json")).unwrap();
+        assert!(user.default_profile);
+        assert!(!user.has_custom_avatar());
+    }
+
+    #[test]
+    fn user_lang() {
+        let mut user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert!(!user.is_rtl_language());
+
+        user.lang = normalize_lang(Some("AR".to_string()));
+        assert_eq!(user.lang, "ar");
+        assert!(user.is_rtl_language());
+
+        user.lang = normalize_lang(Some("en_GB".to_string()));
+        assert_eq!(user.lang, "en-gb");
+        assert!(!user.is_rtl_language());
+
+        user.lang = normalize_lang(None);
+        assert_eq!(user.lang, "");
+        assert!(!user.is_rtl_language());
+
+        let input = load_file("src/user/sample-user-minimal.json").replace(r#""lang": "en""#,
+                                                                             r#""lang": null"#);
+        let user = TwitterUser::from_str(&input).unwrap();
+        assert_eq!(user.lang, "");
+    }
+
+    #[test]
+    fn parse_relationship() {
+        let relation = Relationship::from_str(&load_file("src/user/sample-relationship.json")).unwrap();
+
+        assert_eq!(relation.source.id, 165262228);
+        assert_eq!(relation.source.screen_name, "rustlang");
+        assert!(relation.source.following);
+        assert!(!relation.source.followed_by);
+        assert!(relation.source.can_dm);
+        assert_eq!(relation.source.blocking, Some(false));
+        assert_eq!(relation.source.muting, Some(false));
+        assert_eq!(relation.source.marked_spam, Some(false));
+        assert_eq!(relation.source.all_replies, Some(false));
+        assert_eq!(relation.source.want_retweets, Some(true));
+        assert_eq!(relation.source.notifications_enabled, Some(false));
+
+        assert_eq!(relation.target.id, 2244994945);
+        assert_eq!(relation.target.screen_name, "zoe_unicode");
+        assert!(relation.target.followed_by);
+        assert!(!relation.target.following);
+
+        assert!(relation.is_following());
+        assert!(!relation.is_followed_by());
+        assert!(relation.wants_retweets());
+        assert!(!relation.is_pending_follow());
+    }
+
+    #[test]
+    fn parse_relation_lookup() {
+        let input = load_file("src/user/sample-relation-lookup.json");
+        let relations = Vec::<RelationLookup>::from_str(&input).unwrap();
+
+        assert_eq!(relations.len(), 2);
+
+        assert_eq!(relations[0].id, 2244994945);
+        assert_eq!(relations[0].name, "Zoë ☕ Ünïcode");
+        assert_eq!(relations[0].screen_name, "zoe_unicode");
+        assert_eq!(relations[0].connections, vec![Connection::Following, Connection::FollowedBy]);
+
+        assert_eq!(relations[1].id, 783214);
+        assert_eq!(relations[1].connections, vec![Connection::None]);
+        assert!(!relations[1].following());
+    }
+
+    #[test]
+    fn hex_colors() {
+        use super::parse_hex_color;
+
+        assert_eq!(parse_hex_color("C0DEED"), Some((0xC0, 0xDE, 0xED)));
+        assert_eq!(parse_hex_color("#c0deed"), Some((0xC0, 0xDE, 0xED)));
+        assert_eq!(parse_hex_color("F0A"), Some((0xFF, 0x00, 0xAA)));
+        assert_eq!(parse_hex_color("#333"), Some((0x33, 0x33, 0x33)));
+
+        assert_eq!(parse_hex_color(""), None);
+        assert_eq!(parse_hex_color("C0DEE"), None);
+        assert_eq!(parse_hex_color("GGGGGG"), None);
+        assert_eq!(parse_hex_color("+1+2+3"), None);
+        assert_eq!(parse_hex_color("ÀÀ"), None);
+    }
+
+    #[test]
+    fn follow_update_checks() {
+        use super::check_follow_update;
+        use error::Error;
+
+        assert!(check_follow_update(true, Some(true), Some(false)).is_ok());
+        assert!(check_follow_update(false, Some(false), Some(true)).is_ok());
+        assert!(check_follow_update(false, None, None).is_ok());
+
+        match check_follow_update(false, Some(true), None) {
+            Err(Error::InvalidArgument("notifications", _)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match check_follow_update(false, None, Some(false)) {
+            Err(Error::InvalidArgument("retweets", _)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn show_many_split() {
+        use super::split_users;
+
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        let id = user.id;
+        let name = user.screen_name.to_uppercase();
+
+        let accts: Vec<UserID<'static>> = vec!["nobody_here".into(), name.into(), id.into(),
+                                               1.into()];
+        let split = split_users(accts, vec![user]);
+
+        assert_eq!(split.users.len(), 1);
+        assert_eq!(split.users[0].id, id);
+        assert_eq!(split.missing, vec![UserID::from("nobody_here"), UserID::from(1)]);
+    }
+
+    #[test]
+    fn relation_lookup_mapping() {
+        use super::map_relations;
+
+        let input = load_file("src/user/sample-relation-lookup.json");
+        let relations = Vec::<RelationLookup>::from_str(&input).unwrap();
+
+        let accts: Vec<UserID<'static>> = vec!["Zoe_Unicode".into(), 783214.into(),
+                                               "nobody_here".into()];
+        let map = map_relations(accts, relations);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&UserID::from("Zoe_Unicode")].as_ref().unwrap().id, 2244994945);
+        assert_eq!(map[&UserID::from(783214)].as_ref().unwrap().screen_name, "Twitter");
+        assert!(map[&UserID::from("nobody_here")].is_none());
+    }
+
+    #[test]
+    fn connection_checks() {
+        use super::{parse_is_blocked, parse_is_muted};
+        use common::Headers;
+
+        let blocking = r#"[{"name": "spammer", "screen_name": "spammer", "id": 1234,
+                            "id_str": "1234", "connections": ["blocking", "muting"]}]"#;
+        assert!(parse_is_blocked(blocking.to_string(), &Headers::new()).unwrap().response);
+        assert!(parse_is_muted(blocking.to_string(), &Headers::new()).unwrap().response);
+
+        let input = load_file("src/user/sample-relation-lookup.json");
+        assert!(!parse_is_blocked(input.clone(), &Headers::new()).unwrap().response);
+        assert!(!parse_is_muted(input, &Headers::new()).unwrap().response);
+
+        //accounts Twitter can't find are left out of the response
+        assert!(!parse_is_blocked("[]".to_string(), &Headers::new()).unwrap().response);
+    }
+
+    #[test]
+    fn parse_user_id() {
+        match UserID::parse("@rustlang") {
+            UserID::ScreenName(name) => assert_eq!(name, "rustlang"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+
+        match UserID::parse("rustlang") {
+            UserID::ScreenName(name) => assert_eq!(name, "rustlang"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+
+        match UserID::parse("165262228") {
+            UserID::ID(id) => assert_eq!(id, 165262228),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+
+        match UserID::parse("@1234") {
+            UserID::ScreenName(name) => assert_eq!(name, "1234"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+
+        assert_eq!(UserID::ID(165262228).to_string(), "165262228");
+        assert_eq!(UserID::ScreenName("rustlang".into()).to_string(), "@rustlang");
+    }
+
+    #[test]
+    fn profile_image_sizes() {
+        let url = "https://pbs.twimg.com/profile_images/1234/avatar_normal.png";
+        assert_eq!(resize_profile_image(url, "_bigger"),
+                   "https://pbs.twimg.com/profile_images/1234/avatar_bigger.png");
+        assert_eq!(resize_profile_image(url, "_mini"),
+                   "https://pbs.twimg.com/profile_images/1234/avatar_mini.png");
+        assert_eq!(resize_profile_image(url, ""),
+                   "https://pbs.twimg.com/profile_images/1234/avatar.png");
+
+        let url = "http://pbs.twimg.com/profile_images/1234/avatar_normal";
+        assert_eq!(resize_profile_image(url, "_bigger"),
+                   "http://pbs.twimg.com/profile_images/1234/avatar_bigger");
+
+        let url = "http://pbs.twimg.com/profile_images/1234/avatar.jpeg";
+        assert_eq!(resize_profile_image(url, "_bigger"), url);
+        assert_eq!(resize_profile_image(url, ""), url);
+    }
+
+    #[test]
+    fn relation_lookup_connections() {
+        let input = json::Json::from_str(r#"{
+            "name": "Rust Language",
+            "screen_name": "rustlang",
+            "id": 165262228,
+            "id_str": "165262228",
+            "connections": ["following", "following_requested", "muting"]
+        }"#).unwrap();
+        let relation = RelationLookup::from_json(&input).unwrap();
+
+        assert!(relation.following());
+        assert!(relation.following_requested());
+        assert!(relation.muting());
+        assert!(!relation.followed_by());
+        assert!(!relation.blocking());
+    }
+
+    #[test]
+    fn pending_follow_requests() {
+        //a page from friendships/outgoing, and the friendships/lookup response for the same
+        //account while the request is still pending
+        let page = json::Json::from_str(r#"{
+            "ids": [165262228],
+            "next_cursor": 0,
+            "next_cursor_str": "0",
+            "previous_cursor": 0,
+            "previous_cursor_str": "0"
+        }"#).unwrap();
+        let page = cursor::IDCursor::from_json(&page).unwrap();
+        assert_eq!(page.ids, vec![165262228]);
+        assert_eq!(page.next_cursor, 0);
+
+        let input = json::Json::from_str(r#"[{
+            "name": "Rust Language",
+            "screen_name": "rustlang",
+            "id": 165262228,
+            "id_str": "165262228",
+            "connections": ["following_requested"]
+        }]"#).unwrap();
+        let relations = Vec::<RelationLookup>::from_json(&input).unwrap();
+
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].id, page.ids[0]);
+        assert!(relations[0].following_requested());
+        assert!(!relations[0].following());
+    }
+
+    #[test]
+    fn user_id_conversions() {
+        match UserID::from(String::from("rustlang")) {
+            UserID::ScreenName(name) => assert_eq!(name, "rustlang"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+
+        let name = String::from("rustlang");
+        let id = UserID::from(&name);
+        match UserID::from(&id) {
+            UserID::ScreenName(name) => assert_eq!(name, "rustlang"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn user_id_screen_names() {
+        let expected = UserID::ScreenName("rustlang".into());
+        let name = String::from("@rustlang");
+
+        assert_eq!(UserID::from("@rustlang"), expected);
+        assert_eq!(UserID::from(&"@rustlang"), expected);
+        assert_eq!(UserID::from(&name), expected);
+        assert_eq!(UserID::from(name), expected);
+        assert_eq!(UserID::from("rustlang"), expected);
+
+        //case is left alone, since twitter handles that itself
+        match UserID::from("@RustLang") {
+            UserID::ScreenName(name) => assert_eq!(name, "RustLang"),
+            other => panic!("unexpected UserID: {:?}", other),
+        }
+        assert_ne!(UserID::from("RustLang"), expected);
+
+        //only one @ is removed, so the rest goes to twitter as-is
+        assert_eq!(UserID::from("@@rustlang"), UserID::ScreenName("@rustlang".into()));
+    }
+
+    #[test]
+    fn parse_account_settings() {
+        let settings = AccountSettings::from_str(&load_file("src/user/sample-account-settings.json"))
+                                       .unwrap();
+
+        assert_eq!(settings.screen_name, "rustlang");
+        assert!(!settings.protected);
+        assert!(settings.geo_enabled);
+        assert_eq!(settings.language, "en");
+        assert_eq!(settings.time_zone, Some("Pacific Time (US & Canada)".to_string()));
+        assert_eq!(settings.trend_location.len(), 1);
+        assert_eq!(settings.trend_location[0].woeid, 2357024);
+        assert_eq!(settings.trend_location[0].name, "Atlanta");
+        assert_eq!(settings.trend_location[0].country_code, Some("US".to_string()));
+        assert_eq!(settings.trend_location[0].parent_id, 23424977);
+
+        //worldwide trends have no country code, and accounts might not have a time zone
+        let input = json::Json::from_str(r#"{
+            "screen_name": "rustlang", "protected": true, "language": "de", "time_zone": null,
+            "trend_location": [{"country": "", "countryCode": null, "name": "Worldwide",
+                                "parentid": 0, "woeid": 1}]
+        }"#).unwrap();
+        let settings = AccountSettings::from_json(&input).unwrap();
+
+        assert!(settings.protected);
+        assert!(settings.time_zone.is_none());
+        assert_eq!(settings.trend_location[0].woeid, 1);
+        assert!(settings.trend_location[0].country_code.is_none());
+    }
+
+    #[test]
+    fn parse_profile_banner() {
+        let input = json::Json::from_str(r#"{
+            "sizes": {
+                "ipad": {"h": 313, "w": 626, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/ipad"},
+                "ipad_retina": {"h": 626, "w": 1252, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/ipad_retina"},
+                "web": {"h": 260, "w": 520, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/web"},
+                "web_retina": {"h": 520, "w": 1040, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/web_retina"},
+                "mobile": {"h": 160, "w": 320, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/mobile"},
+                "mobile_retina": {"h": 320, "w": 640, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/mobile_retina"},
+                "300x100": {"h": 100, "w": 300, "url": "https://pbs.twimg.com/profile_banners/6253282/1347394302/300x100"}
+            }
+        }"#).unwrap();
+        let banner = ProfileBanner::from_json(&input).unwrap();
+
+        assert_eq!(banner.web.w, 520);
+        assert_eq!(banner.web.url, "https://pbs.twimg.com/profile_banners/6253282/1347394302/web");
+        assert_eq!(banner.mobile_retina.h, 320);
+        assert_eq!(banner.size_300x100.map(|s| s.w), Some(300));
+        assert!(banner.size_1500x500.is_none());
+    }
+
+    #[test]
+    fn missing_profile_banner() {
+        use super::profile_banner;
+        use common::mock;
+        use hyper::{Headers, StatusCode};
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        let mut headers = Headers::new();
+        headers.set_raw("X-Rate-Limit-Limit", "180");
+        headers.set_raw("X-Rate-Limit-Remaining", "179");
+        headers.set_raw("X-Rate-Limit-Reset", "1500000000");
+
+        mock::respond(&handle, StatusCode::NotFound, headers.clone(), "");
+        mock::respond(&handle, StatusCode::NotFound, headers.clone(),
+                      r#"{"errors":[{"code":34,"message":"Sorry, that page does not exist."}]}"#);
+        mock::respond(&handle, StatusCode::NotFound, headers,
+                      r#"{"errors":[{"code":50,"message":"User not found."}]}"#);
+
+        for _ in 0..2 {
+            match profile_banner("rustlang", &token, &handle).poll() {
+                Ok(Async::Ready(resp)) => {
+                    assert!(resp.response.is_none());
+                    assert_eq!(resp.rate_limit, 180);
+                    assert_eq!(resp.rate_limit_remaining, 179);
+                    assert_eq!(resp.rate_limit_reset, 1500000000);
+                }
+                Ok(Async::NotReady) => panic!("mocked profile_banner wasn't ready"),
+                Err(e) => panic!("mocked profile_banner returned an error: {}", e),
+            }
+        }
+
+        match profile_banner("rustlang", &token, &handle).poll() {
+            Err(ref e) if e.has_twitter_code(50) => (),
+            Err(e) => panic!("mocked profile_banner returned the wrong error: {}", e),
+            Ok(_) => panic!("mocked profile_banner didn't return an error"),
+        }
+    }
+
+    #[test]
+    fn empty_lookups() {
+        //the core is never run, so if either of these tried to make a network call, they would
+        //return NotReady instead of finishing immediately
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        match lookup(Vec::<u64>::new(), &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => {
+                assert!(resp.response.is_empty());
+                assert_eq!(resp.rate_limit, -1);
+            },
+            Ok(Async::NotReady) => panic!("empty lookup tried to make a network call"),
+            Err(e) => panic!("empty lookup returned an error: {}", e),
+        }
+
+        match relation_lookup(Vec::<&str>::new(), &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => assert!(resp.response.is_empty()),
+            Ok(Async::NotReady) => panic!("empty relation_lookup tried to make a network call"),
+            Err(e) => panic!("empty relation_lookup returned an error: {}", e),
+        }
+    }
+
+    #[test]
+    fn relation_lookup_chunks() {
+        use common::mock;
+        use hyper::{Headers, Method, StatusCode};
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        let body = load_file("src/user/sample-relation-lookup.json");
+        mock::respond(&handle, StatusCode::Ok, Headers::new(), body.clone());
+        mock::respond(&handle, StatusCode::Ok, Headers::new(), body);
+
+        let ids = (1..151).collect::<Vec<u64>>();
+        match relation_lookup(ids, &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => assert_eq!(resp.response.len(), 4),
+            Ok(Async::NotReady) => panic!("mocked relation_lookup wasn't ready"),
+            Err(e) => panic!("mocked relation_lookup returned an error: {}", e),
+        }
+
+        let requests = mock::requests(&handle);
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|&(ref method, _)| *method == Method::Get));
+        assert!(requests[0].1.contains("user_id=1%2C2%2C"));
+        assert!(requests[1].1.contains("user_id=101%2C102%2C"));
+    }
+
+    #[test]
+    fn lookup_dedup() {
+        use common::mock;
+        use hyper::{Headers, StatusCode};
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        mock::respond(&handle, StatusCode::Ok, Headers::new(), "[]");
+
+        match lookup(vec![1, 1, 2], &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => assert!(resp.response.is_empty()),
+            Ok(Async::NotReady) => panic!("mocked lookup wasn't ready"),
+            Err(e) => panic!("mocked lookup returned an error: {}", e),
+        }
+
+        let requests = mock::requests(&handle);
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].1.contains("user_id=1%2C2"));
+        assert!(!requests[0].1.contains("user_id=1%2C1"));
+    }
+
+    #[test]
+    fn follow_with_settings() {
+        use super::{follow_with, follow_up_retweets, FollowOptions};
+        use common::mock;
+        use links;
+        use hyper::{Headers, Method, StatusCode};
+
+        let user = TwitterUser::from_str(&load_file("src/user/sample-user.json")).unwrap();
+        assert_eq!(follow_up_retweets(&user, Some(false)), Some(false));
+        assert_eq!(follow_up_retweets(&user, None), None);
+
+        let input = load_file("src/user/sample-user.json")
+            .replace(r#""following": true"#, r#""following": false"#)
+            .replace(r#""follow_request_sent": false"#, r#""follow_request_sent": true"#);
+        let pending = TwitterUser::from_str(&input).unwrap();
+        assert_eq!(follow_up_retweets(&pending, Some(false)), None);
+
+        let core = Core::new().unwrap();
+        let handle = core.handle();
+        let token = auth::Token::Bearer("token".to_string());
+
+        mock::respond(&handle, StatusCode::Ok, Headers::new(), input);
+        mock::respond(&handle, StatusCode::Ok, Headers::new(),
+                      load_file("src/user/sample-relationship.json"));
+
+        let opts = FollowOptions::new().notifications(true).retweets(false);
+        match follow_with("rustlang", opts, &token, &handle).poll() {
+            Ok(Async::Ready(resp)) => assert_eq!(resp.target.screen_name, "zoe_unicode"),
+            Ok(Async::NotReady) => panic!("mocked follow_with wasn't ready"),
+            Err(e) => panic!("mocked follow_with returned an error: {}", e),
+        }
+
+        let requests = mock::requests(&handle);
+        assert_eq!(requests, vec![(Method::Post, links::users::FOLLOW.to_string()),
+                                  (Method::Post, links::users::FRIENDSHIP_UPDATE.to_string())]);
+    }
+
+    #[test]
+    fn snapshot_diff() {
+        use super::SnapshotDiff;
+
+        let diff = SnapshotDiff::between(vec![1, 2, 2, 3], vec![3, 4, 4]);
+        assert_eq!(diff.added, vec![4].into_iter().collect());
+        assert_eq!(diff.removed, vec![1, 2].into_iter().collect());
+        assert!(!diff.is_empty());
+
+        assert!(SnapshotDiff::between(vec![1, 2], vec![2, 1]).is_empty());
+        assert!(SnapshotDiff::between(Vec::new(), Vec::new()).is_empty());
+    }
+}